@@ -1,5 +1,7 @@
 use criterion::{criterion_group, BenchmarkId, Criterion};
 use phylo2vec::tree_vec::ops;
+use phylo2vec::tree_vec::ops::metrics;
+use phylo2vec::tree_vec::ops::newick;
 use phylo2vec::utils::sample_vector;
 use std::ops::Range;
 use std::time::Duration;
@@ -25,7 +27,7 @@ fn bench_to_newick(c: &mut Criterion) {
             |b, &size| {
                 b.iter(|| {
                     let v = sample_vector(size, true);
-                    ops::to_newick(&v)
+                    ops::to_newick_from_vector(&v)
                 });
             },
         );
@@ -37,7 +39,7 @@ fn bench_to_newick(c: &mut Criterion) {
             |b, &size| {
                 b.iter(|| {
                     let v = sample_vector(size, false);
-                    ops::to_newick(&v)
+                    ops::to_newick_from_vector(&v)
                 });
             },
         );
@@ -45,6 +47,71 @@ fn bench_to_newick(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark the ordered fast path against the general to_newick on ordered inputs
+fn bench_to_newick_ordered(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_newick_ordered");
+    // Set logarithmic scale for plot
+    group.plot_config(
+        criterion::PlotConfiguration::default().summary_scale(criterion::AxisScale::Logarithmic),
+    );
+
+    for i in SAMPLE_SIZES {
+        let sample_size = 2_i32.checked_pow(i).unwrap() as usize;
+        let v = sample_vector(sample_size, true);
+
+        group.bench_with_input(BenchmarkId::new("general", sample_size), &v, |b, v| {
+            b.iter(|| ops::to_newick_from_vector(v));
+        });
+        group.bench_with_input(BenchmarkId::new("ordered", sample_size), &v, |b, v| {
+            b.iter(|| ops::to_newick_ordered(v));
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark the direct label-free Newick against to_newick + remove_parent_labels
+fn bench_to_newick_no_labels(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_newick_no_labels");
+    // Set logarithmic scale for plot
+    group.plot_config(
+        criterion::PlotConfiguration::default().summary_scale(criterion::AxisScale::Logarithmic),
+    );
+
+    for i in SAMPLE_SIZES {
+        let sample_size = 2_i32.checked_pow(i).unwrap() as usize;
+        let v = sample_vector(sample_size, false);
+
+        group.bench_with_input(
+            BenchmarkId::new("remove_parent_labels", sample_size),
+            &v,
+            |b, v| {
+                b.iter(|| newick::remove_parent_labels(&ops::to_newick_from_vector(v)));
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("direct", sample_size), &v, |b, v| {
+            b.iter(|| ops::to_newick_no_labels(v));
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark robinson_foulds (bitset-based bipartitions) on large trees
+fn bench_robinson_foulds(c: &mut Criterion) {
+    let mut group = c.benchmark_group("robinson_foulds");
+    let n_leaves = 2000;
+    let v1 = sample_vector(n_leaves, false);
+    let v2 = sample_vector(n_leaves, false);
+
+    group.bench_with_input(
+        BenchmarkId::from_parameter(n_leaves),
+        &(v1, v2),
+        |b, (v1, v2)| {
+            b.iter(|| metrics::robinson_foulds(v1, v2));
+        },
+    );
+    group.finish();
+}
+
 /// Benchmark to_vector
 fn bench_to_vector(c: &mut Criterion) {
     let mut group = c.benchmark_group("to_vector");
@@ -60,7 +127,7 @@ fn bench_to_vector(c: &mut Criterion) {
             |b, &size| {
                 // Generate the Newick string once outside the benchmark loop
                 let v = sample_vector(size, true);
-                let newick = ops::to_newick(&v);
+                let newick = ops::to_newick_from_vector(&v);
 
                 // Benchmark only the to_vector operation
                 b.iter(|| ops::to_vector(&newick));
@@ -75,5 +142,5 @@ criterion_group! {
     config = Criterion::default()
         .sample_size(10)
         .warm_up_time(Duration::from_millis(1000));
-    targets = bench_to_newick, bench_to_vector
+    targets = bench_to_newick, bench_to_newick_ordered, bench_to_newick_no_labels, bench_to_vector, bench_robinson_foulds
 }
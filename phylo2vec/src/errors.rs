@@ -0,0 +1,110 @@
+use thiserror::Error;
+
+use crate::tree_vec::ops::newick::NewickError;
+
+/// Errors raised when validating or sampling Phylo2Vec objects
+#[derive(Error, Debug, PartialEq)]
+pub enum Phylo2VecError {
+    /// A per-position sampling bound exceeds the Phylo2Vec constraint (bound <= 2 * index)
+    #[error("Invalid bound: bounds[{index}] = {bound} is out of bounds (max = {max})")]
+    InvalidBound {
+        index: usize,
+        bound: usize,
+        max: usize,
+    },
+    /// An element of a Phylo2Vec vector violates the constraint v[i] <= 2 * i
+    #[error("Validation failed: v[{index}] = {value} is out of bounds (max = {max})")]
+    OutOfBounds {
+        index: usize,
+        value: usize,
+        max: usize,
+    },
+    /// The number of branch length pairs does not match the number of internal nodes
+    #[error("Invalid branch lengths: expected {expected} entries, found {found}")]
+    BranchLengthsMismatch { expected: usize, found: usize },
+    /// The number of taxa does not match the number of leaves
+    #[error("Invalid taxa: expected {expected} entries, found {found}")]
+    TaxaMismatch { expected: usize, found: usize },
+    /// A node id does not refer to a non-root node of the tree
+    #[error("Invalid node: {node} is out of bounds (max = {max})")]
+    InvalidNode { node: usize, max: usize },
+    /// Two nodes given as a cherry are not siblings in the tree
+    #[error("Invalid cherry: ({c1}, {c2}) are not siblings")]
+    InvalidCherry { c1: usize, c2: usize },
+    /// A non-root node has no branch length while others do
+    #[error("Missing branch length for node {node}")]
+    MissingBranchLength { node: usize },
+    /// A binary encoding of a Phylo2Vec vector is malformed
+    #[error("Invalid binary encoding: {0}")]
+    InvalidEncoding(String),
+    /// A CSV representation of a Phylo2Vec object is malformed
+    #[error("Invalid CSV at line {line}: {reason}")]
+    InvalidCsv { line: usize, reason: String },
+    /// A row of a Phylo2Vec matrix does not have 3 columns (vector, two branch lengths)
+    #[error("Invalid matrix: row {row} has {columns} columns (expected 3)")]
+    InvalidMatrixRow { row: usize, columns: usize },
+    /// A list of (parent, child) edges does not describe a rooted binary tree
+    #[error("Invalid edge list: {0}")]
+    InvalidEdges(String),
+    /// A tree of a batch does not have the same number of leaves as the first one
+    #[error("Invalid batch: tree {index} has {found} leaves (expected {expected})")]
+    LeafCountMismatch {
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// An element of a batch could not be parsed
+    #[error("Invalid input at index {index}: {source}")]
+    InvalidBatchItem { index: usize, source: NewickError },
+    /// A Newick string could not be parsed
+    #[error("Invalid Newick string: {0}")]
+    Newick(#[from] NewickError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(Phylo2VecError::InvalidBound { index: 2, bound: 5, max: 4 }, "Invalid bound: bounds[2] = 5 is out of bounds (max = 4)")]
+    #[case(Phylo2VecError::OutOfBounds { index: 1, value: 3, max: 2 }, "Validation failed: v[1] = 3 is out of bounds (max = 2)")]
+    #[case(Phylo2VecError::BranchLengthsMismatch { expected: 3, found: 2 }, "Invalid branch lengths: expected 3 entries, found 2")]
+    #[case(Phylo2VecError::TaxaMismatch { expected: 4, found: 3 }, "Invalid taxa: expected 4 entries, found 3")]
+    #[case(Phylo2VecError::InvalidNode { node: 6, max: 5 }, "Invalid node: 6 is out of bounds (max = 5)")]
+    #[case(Phylo2VecError::InvalidCherry { c1: 0, c2: 1 }, "Invalid cherry: (0, 1) are not siblings")]
+    #[case(Phylo2VecError::MissingBranchLength { node: 1 }, "Missing branch length for node 1")]
+    #[case(Phylo2VecError::InvalidEncoding("truncated".to_string()), "Invalid binary encoding: truncated")]
+    #[case(Phylo2VecError::InvalidCsv { line: 2, reason: "expected 3 columns, found 2".to_string() }, "Invalid CSV at line 2: expected 3 columns, found 2")]
+    #[case(Phylo2VecError::InvalidMatrixRow { row: 1, columns: 2 }, "Invalid matrix: row 1 has 2 columns (expected 3)")]
+    #[case(Phylo2VecError::InvalidEdges("node 5 has 3 children".to_string()), "Invalid edge list: node 5 has 3 children")]
+    #[case(Phylo2VecError::LeafCountMismatch { index: 2, expected: 4, found: 3 }, "Invalid batch: tree 2 has 3 leaves (expected 4)")]
+    #[case(Phylo2VecError::InvalidBatchItem { index: 1, source: NewickError::MissingSemicolon }, "Invalid input at index 1: Missing semicolon at the end of the Newick string")]
+    #[case(
+        Phylo2VecError::Newick(NewickError::MissingSemicolon),
+        "Invalid Newick string: Missing semicolon at the end of the Newick string"
+    )]
+    fn test_display(#[case] error: Phylo2VecError, #[case] expected: &str) {
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[rstest]
+    fn test_from_newick_error() {
+        fn parse(newick: &str) -> Result<usize, Phylo2VecError> {
+            let (ancestry, _) = crate::tree_vec::ops::newick::get_cherries_with_bls(newick)?;
+            Ok(ancestry.len())
+        }
+
+        assert_eq!(parse("((0,2)3,1)4;"), Ok(2));
+        assert_eq!(
+            parse("((0:inf,1)2,3)4;"),
+            Err(Phylo2VecError::Newick(NewickError::NonFiniteBranchLength(
+                "inf".to_string()
+            )))
+        );
+
+        // Both error types can be boxed as `std::error::Error`
+        let error: Box<dyn std::error::Error> = Box::new(parse("((0:inf,1)2,3)4;").unwrap_err());
+        assert!(error.source().is_some());
+    }
+}
@@ -0,0 +1,176 @@
+use crate::errors::Phylo2VecError;
+use crate::tree_vec::ops::matrix::matrix_num_leaves;
+use crate::utils::INTEGER_TOLERANCE;
+
+/// Header row of a Phylo2Vec matrix in CSV format
+pub const MATRIX_CSV_HEADER: &str = "v,bl_left,bl_right";
+
+/// Write a Phylo2Vec matrix in CSV format
+///
+/// The first line is the header `v,bl_left,bl_right`, followed by one line per
+/// row of the matrix. The vector part is written as an integer.
+///
+/// # Panics
+///
+/// Panics if a row does not have exactly 3 columns
+///
+/// # Example
+/// ```
+/// use phylo2vec::io::matrix_to_csv;
+///
+/// let m = vec![vec![0.0, 0.1, 0.2], vec![1.0, 0.3, 0.4]];
+/// assert_eq!(matrix_to_csv(&m), "v,bl_left,bl_right\n0,0.1,0.2\n1,0.3,0.4\n");
+/// ```
+pub fn matrix_to_csv(m: &[Vec<f32>]) -> String {
+    if let Err(error) = matrix_num_leaves(m) {
+        panic!("{}", error);
+    }
+
+    let mut csv = format!("{}\n", MATRIX_CSV_HEADER);
+    for row in m.iter() {
+        csv.push_str(&format!("{},{},{}\n", row[0].round(), row[1], row[2]));
+    }
+    csv
+}
+
+/// Read a Phylo2Vec matrix in CSV format
+///
+/// The CSV must start with the header `v,bl_left,bl_right`. Values may be
+/// written in any float format (e.g., `1.000000000000000000e+00` as written
+/// by `numpy.savetxt`), but the first column must hold integers.
+///
+/// # Errors
+///
+/// Returns `Phylo2VecError::InvalidCsv` if the header is missing, if a line
+/// does not have 3 columns or if a value is not a finite number, and
+/// `Phylo2VecError::OutOfBounds` if the vector part is not a valid vector
+///
+/// # Example
+/// ```
+/// use phylo2vec::io::matrix_from_csv;
+///
+/// let m = matrix_from_csv("v,bl_left,bl_right\n0,0.1,0.2\n1,0.3,0.4\n").unwrap();
+/// assert_eq!(m, vec![vec![0.0, 0.1, 0.2], vec![1.0, 0.3, 0.4]]);
+/// ```
+pub fn matrix_from_csv(csv: &str) -> Result<Vec<Vec<f32>>, Phylo2VecError> {
+    let mut lines = csv.lines().enumerate();
+
+    match lines.next() {
+        Some((_, header)) if header.trim() == MATRIX_CSV_HEADER => {}
+        _ => {
+            return Err(Phylo2VecError::InvalidCsv {
+                line: 1,
+                reason: format!("expected header '{}'", MATRIX_CSV_HEADER),
+            })
+        }
+    }
+
+    let mut m: Vec<Vec<f32>> = Vec::new();
+    for (i, line) in lines {
+        let line_number = i + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 3 {
+            return Err(Phylo2VecError::InvalidCsv {
+                line: line_number,
+                reason: format!("expected 3 columns, found {}", fields.len()),
+            });
+        }
+
+        let mut row = Vec::with_capacity(3);
+        for field in fields {
+            let value = match field.parse::<f32>() {
+                // `parse` accepts "inf" and "nan", which are not valid branch lengths
+                Ok(value) if value.is_finite() => value,
+                _ => {
+                    return Err(Phylo2VecError::InvalidCsv {
+                        line: line_number,
+                        reason: format!("'{}' is not a finite number", field),
+                    })
+                }
+            };
+            row.push(value);
+        }
+
+        let rounded = row[0].round();
+        if rounded < 0.0 || (row[0] - rounded).abs() > INTEGER_TOLERANCE {
+            return Err(Phylo2VecError::InvalidCsv {
+                line: line_number,
+                reason: format!("'{}' is not a non-negative integer", row[0]),
+            });
+        }
+        row[0] = rounded;
+
+        let index = m.len();
+        if rounded as usize > 2 * index {
+            return Err(Phylo2VecError::OutOfBounds {
+                index,
+                value: rounded as usize,
+                max: 2 * index,
+            });
+        }
+
+        m.push(row);
+    }
+
+    Ok(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::sample_matrix;
+    use rstest::*;
+
+    #[rstest]
+    #[case(sample_matrix(2, false))]
+    #[case(sample_matrix(10, false))]
+    #[case(sample_matrix(100, true))]
+    fn test_matrix_csv_round_trip(#[case] m: Vec<Vec<f32>>) {
+        let csv = matrix_to_csv(&m);
+        assert_eq!(csv.lines().count(), m.len() + 1);
+        assert_eq!(matrix_from_csv(&csv).unwrap(), m);
+    }
+
+    #[rstest]
+    fn test_matrix_from_csv_numpy() {
+        let csv = "v,bl_left,bl_right\n\
+                   0.000000000000000000e+00,1.000000000000000056e-01,2.000000000000000111e-01\n\
+                   2.000000000000000000e+00,5.000000000000000000e-01,7.500000000000000000e-01\n";
+        let m = matrix_from_csv(csv).unwrap();
+        assert_eq!(m, vec![vec![0.0, 0.1, 0.2], vec![2.0, 0.5, 0.75]]);
+    }
+
+    #[rstest]
+    #[case("", 1)]
+    #[case("0,0.1,0.2\n", 1)]
+    #[case("v,bl_left,bl_right\n0,0.1\n", 2)]
+    #[case("v,bl_left,bl_right\n0,0.1,0.2\n1,0.3,0.4,0.5\n", 3)]
+    #[case("v,bl_left,bl_right\n0,abc,0.2\n", 2)]
+    #[case("v,bl_left,bl_right\n0,inf,0.2\n", 2)]
+    #[case("v,bl_left,bl_right\n0,0.1,0.2\nNaN,0.3,0.4\n", 3)]
+    #[case("v,bl_left,bl_right\n0.5,0.1,0.2\n", 2)]
+    #[case("v,bl_left,bl_right\n-1,0.1,0.2\n", 2)]
+    fn test_matrix_from_csv_invalid(#[case] csv: &str, #[case] expected_line: usize) {
+        match matrix_from_csv(csv) {
+            Err(Phylo2VecError::InvalidCsv { line, .. }) => assert_eq!(line, expected_line),
+            other => panic!("Expected an InvalidCsv error, got {:?}", other),
+        }
+    }
+
+    #[rstest]
+    fn test_matrix_from_csv_out_of_bounds() {
+        let csv = "v,bl_left,bl_right\n0,0.1,0.2\n3,0.3,0.4\n";
+        assert_eq!(
+            matrix_from_csv(csv),
+            Err(Phylo2VecError::OutOfBounds {
+                index: 1,
+                value: 3,
+                max: 2
+            })
+        );
+    }
+}
@@ -1,2 +1,5 @@
+pub mod errors;
+#[cfg(feature = "matrix")]
+pub mod io;
 pub mod tree_vec;
 pub mod utils;
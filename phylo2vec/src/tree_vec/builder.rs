@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use crate::errors::Phylo2VecError;
+use crate::tree_vec::ops::vector::get_ancestry;
+use crate::tree_vec::TreeVec;
+
+/// Builder for a `TreeVec` annotated with branch lengths
+///
+/// `TreeVec` stores branch lengths in the row order of the ancestry, which is
+/// easy to get wrong by hand. The builder instead accepts branch lengths keyed
+/// by node id (the length of the edge above the node) or by cherry, and
+/// assembles them in the internal order when calling `build`.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::builder::TreeVecBuilder;
+///
+/// // ((0,2)5,(1,3)4)6;
+/// let tree = TreeVecBuilder::new(vec![0, 0, 1])
+///     .cherry_branch_lengths(0, 2, (0.1, 0.3))
+///     .cherry_branch_lengths(1, 3, (0.2, 0.4))
+///     .branch_length(4, 0.5)
+///     .branch_length(5, 0.6)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TreeVecBuilder {
+    data: Vec<usize>,
+    branch_lengths: HashMap<usize, f64>,
+    cherries: Vec<(usize, usize)>,
+    taxa: Option<Vec<String>>,
+}
+
+impl TreeVecBuilder {
+    /// Creates a new builder for the tree described by `data`
+    ///
+    /// # Arguments
+    /// * `data` - Phylo2Vec vector containing the tree structure
+    pub fn new(data: Vec<usize>) -> Self {
+        TreeVecBuilder {
+            data,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the length of the branch above a node
+    ///
+    /// # Arguments
+    /// * `node` - Id of the node (leaf or internal node, except the root)
+    /// * `branch_length` - Length of the branch leading to `node`
+    pub fn branch_length(mut self, node: usize, branch_length: f64) -> Self {
+        self.branch_lengths.insert(node, branch_length);
+        self
+    }
+
+    /// Sets the lengths of the branches above the two nodes of a cherry
+    ///
+    /// # Arguments
+    /// * `c1` - Id of the first child
+    /// * `c2` - Id of the second child (sibling of `c1`)
+    /// * `branch_lengths` - Lengths of the branches leading to `c1` and `c2`
+    pub fn cherry_branch_lengths(
+        mut self,
+        c1: usize,
+        c2: usize,
+        branch_lengths: (f64, f64),
+    ) -> Self {
+        self.cherries.push((c1, c2));
+        self.branch_lengths.insert(c1, branch_lengths.0);
+        self.branch_lengths.insert(c2, branch_lengths.1);
+        self
+    }
+
+    /// Sets the taxon names of the leaves
+    ///
+    /// # Arguments
+    /// * `taxa` - Taxon names, indexed by leaf
+    pub fn taxa(mut self, taxa: Vec<String>) -> Self {
+        self.taxa = Some(taxa);
+        self
+    }
+
+    /// Builds the `TreeVec`
+    ///
+    /// If no branch length was given, the tree has no branch lengths.
+    /// Otherwise, every node except the root must have one.
+    ///
+    /// # Returns
+    /// A validated `TreeVec`, or a `Phylo2VecError` if the vector, the taxa
+    /// or the branch lengths are inconsistent
+    pub fn build(self) -> Result<TreeVec, Phylo2VecError> {
+        // Validate the vector first, as the ancestry cannot be built otherwise
+        TreeVec::new(self.data.clone(), None, None).validate()?;
+
+        let branch_lengths = if self.branch_lengths.is_empty() {
+            None
+        } else {
+            Some(self._assemble_branch_lengths()?)
+        };
+
+        let tree = TreeVec::new(self.data, branch_lengths, self.taxa);
+        tree.validate()?;
+
+        Ok(tree)
+    }
+
+    // Assemble the branch lengths in the row order of the ancestry
+    fn _assemble_branch_lengths(&self) -> Result<Vec<(f64, f64)>, Phylo2VecError> {
+        let ancestry = get_ancestry(&self.data);
+        let root = 2 * self.data.len();
+
+        if let Some(&node) = self.branch_lengths.keys().find(|&&node| node >= root) {
+            return Err(Phylo2VecError::InvalidNode {
+                node,
+                max: root.saturating_sub(1),
+            });
+        }
+
+        let mut parents = vec![usize::MAX; root + 1];
+        for &[c1, c2, p] in ancestry.iter() {
+            parents[c1] = p;
+            parents[c2] = p;
+        }
+        for &(c1, c2) in self.cherries.iter() {
+            if c1 == c2 || parents[c1] != parents[c2] {
+                return Err(Phylo2VecError::InvalidCherry { c1, c2 });
+            }
+        }
+
+        let get_bl = |node: usize| {
+            self.branch_lengths
+                .get(&node)
+                .copied()
+                .ok_or(Phylo2VecError::MissingBranchLength { node })
+        };
+
+        ancestry
+            .iter()
+            .map(|&[c1, c2, _]| Ok((get_bl(c1)?, get_bl(c2)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_vec::ops::newick::build_newick_with_bls;
+    use rstest::*;
+
+    fn to_newick_with_bls(tree: &TreeVec) -> String {
+        let bls: Vec<[f32; 2]> = tree
+            .branch_lengths
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|&(bl1, bl2)| [bl1 as f32, bl2 as f32])
+            .collect();
+        build_newick_with_bls(&tree.get_ancestry(), &bls)
+    }
+
+    /// Test building a tree with branch lengths given per node id
+    #[rstest]
+    #[case(vec![0, 0, 1], vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6], "((0:0.1,2:0.3)5:0.6,(1:0.2,3:0.4)4:0.5)6;")]
+    #[case(vec![0, 1, 2], vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6], "(0:0.1,(1:0.2,(2:0.3,3:0.4)4:0.5)5:0.6)6;")]
+    #[case(vec![0], vec![1.0, 2.0], "(0:1.0,1:2.0)2;")]
+    fn test_builder_node_ids(#[case] v: Vec<usize>, #[case] bls: Vec<f64>, #[case] expected: &str) {
+        let mut builder = TreeVecBuilder::new(v);
+        for (node, &bl) in bls.iter().enumerate() {
+            builder = builder.branch_length(node, bl);
+        }
+        let tree = builder.build().unwrap();
+
+        assert_eq!(to_newick_with_bls(&tree), expected);
+    }
+
+    /// Test that cherries and node ids produce the same tree
+    #[rstest]
+    fn test_builder_cherries() {
+        let from_cherries = TreeVecBuilder::new(vec![0, 0, 1])
+            .cherry_branch_lengths(2, 0, (0.3, 0.1))
+            .cherry_branch_lengths(1, 3, (0.2, 0.4))
+            .cherry_branch_lengths(4, 5, (0.5, 0.6))
+            .taxa(vec!["a".into(), "b".into(), "c".into(), "d".into()])
+            .build()
+            .unwrap();
+
+        let expected = TreeVec::new(
+            vec![0, 0, 1],
+            Some(vec![(0.2, 0.4), (0.1, 0.3), (0.6, 0.5)]),
+            Some(vec!["a".into(), "b".into(), "c".into(), "d".into()]),
+        );
+
+        assert_eq!(from_cherries, expected);
+    }
+
+    #[rstest]
+    fn test_builder_no_branch_lengths() {
+        let tree = TreeVecBuilder::new(vec![0, 0, 1]).build().unwrap();
+        assert_eq!(tree, TreeVec::new(vec![0, 0, 1], None, None));
+    }
+
+    #[rstest]
+    #[case(TreeVecBuilder::new(vec![0, 3, 1]), Phylo2VecError::OutOfBounds { index: 1, value: 3, max: 2 })]
+    #[case(TreeVecBuilder::new(vec![0, 0, 1]).branch_length(6, 1.0), Phylo2VecError::InvalidNode { node: 6, max: 5 })]
+    #[case(TreeVecBuilder::new(vec![0, 0, 1]).cherry_branch_lengths(0, 1, (1.0, 1.0)), Phylo2VecError::InvalidCherry { c1: 0, c2: 1 })]
+    #[case(TreeVecBuilder::new(vec![0, 0, 1]).cherry_branch_lengths(0, 2, (1.0, 1.0)), Phylo2VecError::MissingBranchLength { node: 1 })]
+    #[case(TreeVecBuilder::new(vec![0, 0, 1]).taxa(vec!["a".into()]), Phylo2VecError::TaxaMismatch { expected: 4, found: 1 })]
+    fn test_builder_invalid(#[case] builder: TreeVecBuilder, #[case] expected: Phylo2VecError) {
+        assert_eq!(builder.build().unwrap_err(), expected);
+    }
+}
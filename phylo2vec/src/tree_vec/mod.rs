@@ -1,4 +1,9 @@
-use crate::utils::sample_vector;
+use std::collections::{HashMap, HashSet};
+
+use crate::errors::Phylo2VecError;
+use crate::tree_vec::ops::newick::NewickError;
+use crate::tree_vec::ops::vector::{_get_clade_keys, CladeKey};
+use crate::utils::{sample_vector, validate_v};
 
 // Import the types module
 pub mod types;
@@ -6,16 +11,37 @@ pub mod types;
 // Import the operations modules
 pub mod ops;
 
+// Import the tree trait
+pub mod tree;
+pub use tree::Tree;
+
+// Import the builder
+pub mod builder;
+pub use builder::TreeVecBuilder;
+
 /// A vector representation of a phylogenetic tree
 ///
 /// Contains the tree structure, branch lengths, taxa, and rootedness
+///
+/// Note that the derived `PartialEq` compares *all* fields, so two trees with
+/// the same topology but different branch lengths or taxa are not equal.
+/// Use [`TreeVec::eq_topology`] to compare the tree structures only.
+///
+/// Multifurcating trees are obtained with [`TreeVec::contract_edge`]: `data`
+/// then holds a binary resolution of the tree, and `contracted` lists the
+/// internal nodes whose parent edge has been removed.
+///
+/// With the `serde` feature enabled, trees can be serialized (e.g., to JSON)
+/// with all their fields.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TreeVec {
     n_leaf: usize,
     data: Vec<usize>,
     branch_lengths: Option<Vec<(f64, f64)>>,
     taxa: Option<Vec<String>>,
     is_rooted: bool,
+    contracted: Vec<usize>,
 }
 
 /// Implementation of the `TreeVec` struct
@@ -41,6 +67,7 @@ impl TreeVec {
             is_rooted: true,
             branch_lengths: branch_lengths,
             taxa: taxa,
+            contracted: Vec::new(),
         }
     }
 
@@ -57,12 +84,105 @@ impl TreeVec {
         TreeVec::new(v, None, None)
     }
 
+    /// Creates a new `TreeVec` instance from a Newick string
+    ///
+    /// Leaves labelled with the integers 0..n-1 keep their labels. Otherwise
+    /// (e.g., named leaves or 1-based labels), leaves are relabelled in their
+    /// order of appearance and their labels are stored in `taxa`. Branch
+    /// lengths are stored if the string has any; missing ones are set to 0.
+    /// An empty string yields an empty tree.
+    ///
+    /// # Arguments
+    /// * `newick` - Newick string of a rooted binary tree
+    ///
+    /// # Returns
+    /// A new `TreeVec` instance, or a `NewickError` if the string cannot be parsed
+    /// or if two leaves have the same label
+    pub fn from_newick(newick: &str) -> Result<Self, NewickError> {
+        if newick.is_empty() {
+            return Ok(TreeVec::new(Vec::new(), None, None));
+        }
+
+        let (newick_int, labels) = ops::newick::create_label_mapping(newick)?;
+        let mut sorted_labels: Vec<Option<usize>> =
+            labels.iter().map(|label| label.parse().ok()).collect();
+        sorted_labels.sort_unstable();
+        let is_default = sorted_labels
+            .iter()
+            .enumerate()
+            .all(|(i, &label)| label == Some(i));
+
+        let (newick, taxa) = if is_default {
+            (newick.to_string(), None)
+        } else {
+            (newick_int, Some(labels))
+        };
+
+        // Parse the topology first, which validates the Newick string
+        let data = ops::try_to_vector(&ops::newick::remove_branch_lengths(&newick))?;
+        if !newick.contains(':') {
+            return Ok(TreeVec::new(data, None, taxa));
+        }
+
+        let with_parents = ops::has_parents(&newick);
+        let (ancestry, bls) = if with_parents {
+            ops::newick::get_cherries_with_bls(&newick)?
+        } else {
+            ops::newick::get_cherries_no_parents_with_bls(&newick)?
+        };
+
+        // Match the edges of the parsed ancestry to those of the vector by clade
+        let bls: Vec<(f64, f64)> = bls
+            .iter()
+            .map(|&[bl1, bl2]| (bl1 as f64, bl2 as f64))
+            .collect();
+        let bl_map = _get_branch_length_map(&ancestry, &bls, with_parents, |leaf| (leaf, 1));
+        let branch_lengths = _get_branch_lengths(&data, &bl_map);
+        Ok(TreeVec::new(data, Some(branch_lengths), taxa))
+    }
+
     /// Converts the tree to Newick format
     ///
+    /// If the tree has taxa, leaves are labelled with their names and
+    /// internal nodes are not labelled (see `ops::to_newick_with_taxa`).
+    /// Contracted edges (see `contract_edge`) are not written, so the nodes
+    /// they joined appear as a single multifurcating node.
+    /// If the tree has branch lengths, each node except the root is annotated
+    /// with the length of the edge above it, so that the string can be parsed
+    /// back with `get_cherries_with_bls` (or `get_cherries_no_parents_with_bls`
+    /// with taxa).
+    ///
     /// # Returns
     /// A String containing the Newick representation of the tree
     pub fn to_newick(&self) -> String {
-        return ops::to_newick_from_vector(&self.data);
+        let ancestry = self.get_ancestry();
+
+        let write_bl = ops::newick::_branch_length_writer(
+            &ancestry,
+            self.branch_lengths.as_deref().unwrap_or_default(),
+            None,
+        );
+
+        match &self.taxa {
+            Some(taxa) => ops::_to_newick_with_taxa(&ancestry, &self.contracted, taxa, write_bl),
+            None => ops::newick::_write_newick_contracted(
+                &ancestry,
+                &self.contracted,
+                |newick, leaf| {
+                    ops::newick::_write_integer(newick, leaf);
+                    write_bl(newick, leaf);
+                },
+                |newick, node| {
+                    ops::newick::_write_integer(newick, node);
+                    write_bl(newick, node);
+                },
+            ),
+        }
+    }
+
+    /// Gets the Phylo2Vec vector of the tree
+    pub fn data(&self) -> &[usize] {
+        &self.data
     }
 
     /// Gets the ancestry matrix representation of the tree
@@ -81,7 +201,11 @@ impl TreeVec {
     ///
     /// # Result
     /// Modifies the tree structure by adding the new leaf and updating indices
+    ///
+    /// # Panics
+    /// Panics if the tree is not binary
     pub fn add_leaf(&mut self, leaf: usize, branch: usize) {
+        assert!(self.is_binary(), "Tree must be binary");
         let mut vec = self.data.clone();
         self.data = ops::add_leaf(&mut vec, leaf, branch);
     }
@@ -95,13 +219,382 @@ impl TreeVec {
     /// The index of the sister node of the removed leaf
     ///
     /// # Side effects
-    /// Modifies the tree structure by removing the leaf and updating indices
+    /// Modifies the tree structure by removing the leaf and updating indices.
+    /// The taxon of the removed leaf is dropped. The edges above the sister
+    /// and the former parent of the leaf are merged into a single edge whose
+    /// length is their sum; all other branch lengths are kept.
+    ///
+    /// # Panics
+    /// Panics if the tree is not binary
     pub fn remove_leaf(&mut self, leaf: usize) -> usize {
+        assert!(self.is_binary(), "Tree must be binary");
         let mut vec = self.data.clone();
         let (data, sister_leaf) = ops::remove_leaf(&mut vec, leaf);
+
+        if let Some(bls) = &self.branch_lengths {
+            // Clade keys once the leaf is removed and the leaves above it are
+            // shifted down. The parent of the leaf has the same key as its
+            // sister, whose edge absorbs the edge above the parent.
+            let leaf_key = |l: usize| match l.cmp(&leaf) {
+                std::cmp::Ordering::Less => (l, 1),
+                std::cmp::Ordering::Equal => (usize::MAX, 0),
+                std::cmp::Ordering::Greater => (l - 1, 1),
+            };
+            let bl_map = _get_branch_length_map(&self.get_ancestry(), bls, true, leaf_key);
+
+            self.branch_lengths = Some(_get_branch_lengths(&data, &bl_map));
+        }
+
+        if let Some(taxa) = &mut self.taxa {
+            taxa.remove(leaf);
+        }
+
         self.data = data;
         return sister_leaf;
     }
+
+    /// Removes several leaves from the tree
+    ///
+    /// Gives the same tree as calling `remove_leaf` on each leaf in descending
+    /// order, but the ancestry is traversed once and the vector is built once.
+    /// The remaining leaves are relabelled by rank and the taxa of the removed
+    /// leaves are dropped. Chains of edges left by suppressed internal nodes
+    /// are merged into a single edge whose length is their sum.
+    ///
+    /// # Arguments
+    /// * `leaves` - Indices of the leaves to remove
+    ///
+    /// # Panics
+    /// Panics if a leaf is out of bounds or duplicated, if all leaves are removed,
+    /// or if the tree is not binary
+    pub fn remove_leaves(&mut self, leaves: &[usize]) {
+        assert!(self.is_binary(), "Tree must be binary");
+        let n_leaves = self.data.len() + 1;
+        let mut removed = vec![false; n_leaves];
+        for &leaf in leaves {
+            assert!(
+                leaf < n_leaves,
+                "Leaf {} is out of bounds (max = {})",
+                leaf,
+                n_leaves - 1
+            );
+            assert!(!removed[leaf], "Leaf {} is duplicated", leaf);
+            removed[leaf] = true;
+        }
+        let kept: Vec<usize> = (0..n_leaves).filter(|&leaf| !removed[leaf]).collect();
+        let data = ops::subtree::restrict_to_leaves(&self.data, &kept);
+
+        if let Some(bls) = &self.branch_lengths {
+            // Clade keys once the kept leaves are relabelled by rank. A node
+            // with a single kept child has the same key as this child, so the
+            // edges of suppressed nodes are summed with the edge below.
+            let leaf_key = |leaf: usize| match kept.binary_search(&leaf) {
+                Ok(rank) => (rank, 1),
+                Err(_) => (usize::MAX, 0),
+            };
+            let bl_map = _get_branch_length_map(&self.get_ancestry(), bls, true, leaf_key);
+
+            self.branch_lengths = Some(_get_branch_lengths(&data, &bl_map));
+        }
+
+        if let Some(taxa) = &mut self.taxa {
+            let mut leaf = 0;
+            taxa.retain(|_| {
+                leaf += 1;
+                !removed[leaf - 1]
+            });
+        }
+
+        self.data = data;
+    }
+
+    /// Swaps two leaves of the tree
+    ///
+    /// Leaf `a` takes the place of leaf `b` in the tree and vice versa,
+    /// so the (integer-labelled) topology changes unless both leaves are
+    /// symmetric (e.g., in the same cherry). Branch lengths stay attached to
+    /// their edges and taxa follow the positions: the name shown at each
+    /// position of the tree is unchanged, only the leaf ids are exchanged.
+    ///
+    /// # Arguments
+    /// * `a` - Index of the first leaf
+    /// * `b` - Index of the second leaf
+    ///
+    /// # Panics
+    /// Panics if `a` or `b` is not a leaf of the tree, or if the tree is not binary
+    pub fn swap_leaves(&mut self, a: usize, b: usize) {
+        let n_leaves = self.data.len() + 1;
+        assert!(
+            a < n_leaves && b < n_leaves,
+            "Leaves must be in [0, {}), got {} and {}",
+            n_leaves,
+            a,
+            b
+        );
+
+        let mut perm: Vec<usize> = (0..n_leaves).collect();
+        perm.swap(a, b);
+        self._permute_leaves(&perm);
+    }
+
+    /// Reverses the leaf labels of the tree: leaf `i` becomes leaf `n - 1 - i`
+    ///
+    /// This gives the mirror image of the leaf labelling, e.g. for plotting.
+    /// As with `swap_leaves`, branch lengths stay attached to their edges and
+    /// taxa follow the positions. Reversing twice restores the original tree.
+    pub fn reverse_leaf_order(&mut self) {
+        let n_leaves = self.data.len() + 1;
+        let perm: Vec<usize> = (0..n_leaves).rev().collect();
+        self._permute_leaves(&perm);
+    }
+
+    /// Relabels the leaves of the tree: leaf `i` becomes leaf `perm[i]`
+    ///
+    /// `data` is recomputed for the relabelled tree, branch lengths are
+    /// remapped to the same edges, and `taxa[i]` moves to `taxa[perm[i]]`.
+    fn _permute_leaves(&mut self, perm: &[usize]) {
+        assert!(self.is_binary(), "Tree must be binary");
+        if self.data.is_empty() {
+            return;
+        }
+        let n_leaves = self.data.len() + 1;
+
+        let mut ancestry = self.get_ancestry();
+        for row in ancestry.iter_mut() {
+            for node in row.iter_mut().take(2) {
+                if *node < n_leaves {
+                    *node = perm[*node];
+                }
+            }
+        }
+
+        // Internal node labels are not canonical anymore, so go through a Newick without them
+        let new_data = ops::to_vector(&ops::newick::remove_parent_labels(&ops::build_newick(
+            &ancestry,
+        )));
+
+        if let Some(bls) = &self.branch_lengths {
+            let bl_map = _get_branch_length_map(&ancestry, bls, true, |leaf| (leaf, 1));
+            self.branch_lengths = Some(_get_branch_lengths(&new_data, &bl_map));
+        }
+
+        if let Some(taxa) = &self.taxa {
+            let mut new_taxa = taxa.clone();
+            for (i, name) in taxa.iter().enumerate() {
+                new_taxa[perm[i]] = name.clone();
+            }
+            self.taxa = Some(new_taxa);
+        }
+
+        self.data = new_data;
+    }
+
+    /// Multiplies all branch lengths by a constant factor
+    ///
+    /// Does nothing if the tree has no branch lengths.
+    ///
+    /// # Arguments
+    /// * `factor` - Scaling factor applied to every branch length
+    pub fn scale_branch_lengths(&mut self, factor: f64) {
+        if let Some(bls) = &mut self.branch_lengths {
+            for (bl1, bl2) in bls.iter_mut() {
+                *bl1 *= factor;
+                *bl2 *= factor;
+            }
+        }
+    }
+
+    /// Contracts the edge above an internal node
+    ///
+    /// The node is merged with its parent: its children become children of
+    /// the parent, which yields a multifurcating tree. `data` keeps the binary
+    /// resolution of the tree, and the contracted edge is reflected in
+    /// `to_newick` and `eq_topology`. If the tree has branch lengths, the
+    /// length of the contracted edge is set to 0 and added to each edge below
+    /// the merged node, so that root-to-leaf distances are preserved.
+    /// Contracting an edge twice has no effect.
+    ///
+    /// # Arguments
+    /// * `internal_node` - Internal node below the edge to contract
+    ///
+    /// # Panics
+    /// Panics if `internal_node` is a leaf or the root
+    pub fn contract_edge(&mut self, internal_node: usize) {
+        let n_leaves = self.data.len() + 1;
+        let root = 2 * n_leaves - 2;
+        assert!(
+            internal_node >= n_leaves && internal_node < root,
+            "Internal node must be in [{}, {}), got {}",
+            n_leaves,
+            root,
+            internal_node
+        );
+
+        match self.contracted.binary_search(&internal_node) {
+            Ok(_) => return,
+            Err(pos) => self.contracted.insert(pos, internal_node),
+        }
+
+        if let Some(bls) = &mut self.branch_lengths {
+            let ancestry = ops::get_ancestry(&self.data);
+
+            // Length of the edge above each node
+            let mut node_bls = vec![0.0; 2 * n_leaves - 1];
+            for (&[c1, c2, _], &(bl1, bl2)) in ancestry.iter().zip(bls.iter()) {
+                node_bls[c1] = bl1;
+                node_bls[c2] = bl2;
+            }
+
+            // The edges below the merged node may hang from previously contracted nodes
+            let bl = std::mem::take(&mut node_bls[internal_node]);
+            let mut stack = vec![internal_node];
+            while let Some(node) = stack.pop() {
+                let [c1, c2, _] = ancestry[node - n_leaves];
+                for child in [c1, c2] {
+                    if self.contracted.binary_search(&child).is_ok() {
+                        stack.push(child);
+                    } else {
+                        node_bls[child] += bl;
+                    }
+                }
+            }
+
+            for (pair, &[c1, c2, _]) in bls.iter_mut().zip(ancestry.iter()) {
+                *pair = (node_bls[c1], node_bls[c2]);
+            }
+        }
+    }
+
+    /// Checks whether two trees have the same topology
+    ///
+    /// Unlike `==`, which compares all fields (including branch lengths and
+    /// taxa), this only compares the tree structures. As the vector is a
+    /// unique encoding of a labelled topology, two binary trees are
+    /// topologically equal if and only if their vectors are equal. If edges
+    /// have been contracted, the sets of clades of the two trees are compared
+    /// instead, as a multifurcating tree has several binary resolutions.
+    ///
+    /// # Arguments
+    /// * `other` - The tree to compare against
+    ///
+    /// # Returns
+    /// `true` if both trees have the same topology
+    pub fn eq_topology(&self, other: &TreeVec) -> bool {
+        if self.contracted.is_empty() && other.contracted.is_empty() {
+            return self.data == other.data;
+        }
+        self.data.len() == other.data.len() && self._get_clades() == other._get_clades()
+    }
+
+    /// Leaves below each internal node that was not merged into its parent
+    fn _get_clades(&self) -> HashSet<Vec<u64>> {
+        let n_leaves = self.data.len() + 1;
+        ops::descendant_bitsets(&self.data)
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| self.contracted.binary_search(&(n_leaves + i)).is_err())
+            .map(|(_, bitset)| bitset)
+            .collect()
+    }
+
+    /// Checks that every internal node of the tree has exactly two children
+    ///
+    /// A Phylo2Vec vector always describes a binary tree, so this only fails
+    /// after an edge has been contracted (see `contract_edge`). Operations
+    /// that edit the vector (e.g., `add_leaf`) require a binary tree.
+    ///
+    /// # Returns
+    /// `true` if the tree is binary
+    pub fn is_binary(&self) -> bool {
+        self.contracted.is_empty()
+    }
+
+    /// Checks the internal consistency of the tree
+    ///
+    /// Verifies that:
+    /// * `data` satisfies the Phylo2Vec constraints (v[i] <= 2 * i)
+    /// * `branch_lengths`, if present, has one entry per internal node (n - 1)
+    /// * `taxa`, if present, has one entry per leaf (n)
+    /// * contracted edges are above internal nodes other than the root
+    ///
+    /// # Returns
+    /// `Ok(())` if the tree is consistent, otherwise the first `Phylo2VecError` encountered
+    pub fn validate(&self) -> Result<(), Phylo2VecError> {
+        validate_v(&self.data)?;
+
+        let n_leaves = self.data.len() + 1;
+
+        if let Some(bls) = &self.branch_lengths {
+            if bls.len() != n_leaves - 1 {
+                return Err(Phylo2VecError::BranchLengthsMismatch {
+                    expected: n_leaves - 1,
+                    found: bls.len(),
+                });
+            }
+        }
+
+        if let Some(taxa) = &self.taxa {
+            if taxa.len() != n_leaves {
+                return Err(Phylo2VecError::TaxaMismatch {
+                    expected: n_leaves,
+                    found: taxa.len(),
+                });
+            }
+        }
+
+        let root = 2 * n_leaves - 2;
+        for &node in self.contracted.iter() {
+            if node < n_leaves || node >= root {
+                return Err(Phylo2VecError::InvalidNode {
+                    node,
+                    max: root.saturating_sub(1),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Displays the tree as a Newick string (see `TreeVec::to_newick`)
+///
+/// Use `Debug` to print the raw vector, branch lengths and taxa.
+impl std::fmt::Display for TreeVec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_newick())
+    }
+}
+
+/// Map the clade of each node of an ancestry to the length of the edge above it
+///
+/// See `ops::vector::_get_clade_keys` for `has_parents` and `leaf_key`. The
+/// edges of ignored leaves are dropped, and a node left with a single child
+/// has the same key as this child, so the lengths of their edges are summed.
+fn _get_branch_length_map(
+    ancestry: &types::Ancestry,
+    bls: &[(f64, f64)],
+    has_parents: bool,
+    leaf_key: impl Fn(usize) -> CladeKey,
+) -> HashMap<CladeKey, f64> {
+    let mut bl_map = HashMap::with_capacity(2 * bls.len());
+    let keys = _get_clade_keys(ancestry, has_parents, leaf_key);
+    for (&[key1, key2], &(bl1, bl2)) in keys.iter().zip(bls.iter()) {
+        for (key, bl) in [(key1, bl1), (key2, bl2)] {
+            if key.1 > 0 {
+                *bl_map.entry(key).or_insert(0.0) += bl;
+            }
+        }
+    }
+    bl_map
+}
+
+/// Get the branch lengths of a vector from the lengths of the edges above its clades
+fn _get_branch_lengths(data: &[usize], bl_map: &HashMap<CladeKey, f64>) -> Vec<(f64, f64)> {
+    let ancestry = ops::get_ancestry(data);
+    _get_clade_keys(&ancestry, true, |leaf| (leaf, 1))
+        .iter()
+        .map(|[key1, key2]| (bl_map[key1], bl_map[key2]))
+        .collect()
 }
 
 #[cfg(test)]
@@ -140,6 +633,242 @@ mod tests {
         assert_eq!(tree.taxa, None);
     }
 
+    /// Test the creation of a tree from a Newick string
+    ///
+    /// Tests are using 4 leaf trees with and without parent labels and branch lengths
+    #[rstest]
+    #[case("((0,2)5,(1,3)4)6;", vec![0, 0, 1], None, None)]
+    #[case("((0,2),(1,3));", vec![0, 0, 1], None, None)]
+    #[case("((0:0.3,2:0.4)5:0.5,(1:0.1,3:0.2)4:0.6)6;", vec![0, 0, 1], Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), None)]
+    #[case("((0:0.3,2:0.4):0.5,(1:0.1,3:0.2):0.6);", vec![0, 0, 1], Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), None)]
+    #[case("((0:0.3,2):0.5,(1,3));", vec![0, 0, 1], Some(vec![(0.0, 0.0), (0.3, 0.0), (0.5, 0.0)]), None)]
+    #[case("", vec![], None, None)]
+    fn test_from_newick(
+        #[case] newick: &str,
+        #[case] expected_v: Vec<usize>,
+        #[case] expected_bls: Option<Vec<(f64, f64)>>,
+        #[case] expected_taxa: Option<Vec<&str>>,
+    ) {
+        let tree = TreeVec::from_newick(newick).unwrap();
+        assert_eq!(tree.data, expected_v);
+        assert_eq!(
+            tree.taxa,
+            expected_taxa.map(|taxa| taxa.into_iter().map(String::from).collect())
+        );
+
+        // Compare branch lengths parsed as f32
+        let bls = tree.branch_lengths.map(|bls| {
+            bls.into_iter()
+                .map(|(bl1, bl2)| (bl1 as f32, bl2 as f32))
+                .collect::<Vec<_>>()
+        });
+        let expected_bls = expected_bls.map(|bls| {
+            bls.into_iter()
+                .map(|(bl1, bl2)| (bl1 as f32, bl2 as f32))
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(bls, expected_bls);
+    }
+
+    /// Test that non-default leaf labels are relabelled and stored as taxa
+    #[rstest]
+    #[case("((a:0.3,c:0.4):0.5,(b:0.1,d:0.2):0.6);", vec!["a", "c", "b", "d"])]
+    #[case("((1:0.3,3:0.4):0.5,(2:0.1,4:0.2):0.6);", vec!["1", "3", "2", "4"])]
+    #[case("((0:0.3,1:0.4):0.5,(2:0.1,5:0.2):0.6);", vec!["0", "1", "2", "5"])]
+    fn test_from_newick_taxa(#[case] newick: &str, #[case] expected_taxa: Vec<&str>) {
+        let tree = TreeVec::from_newick(newick).unwrap();
+        let expected = TreeVec::from_newick("((0:0.3,1:0.4):0.5,(2:0.1,3:0.2):0.6);").unwrap();
+
+        assert_eq!(tree.data, expected.data);
+        assert_eq!(tree.branch_lengths, expected.branch_lengths);
+        assert_eq!(
+            tree.taxa,
+            Some(expected_taxa.into_iter().map(String::from).collect())
+        );
+    }
+
+    #[rstest]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(100, true))]
+    fn test_from_newick_round_trip(#[case] v: Vec<usize>) {
+        let tree = TreeVec::new(v, None, None);
+        assert_eq!(TreeVec::from_newick(&tree.to_newick()), Ok(tree));
+    }
+
+    #[rstest]
+    #[case(
+        "((0:0.3,2:0.4)5:0.5,(1:0.1,3:0.2)4:0.6)6;",
+        "((0:0.3,2:0.4)5:0.5,(1:0.1,3:0.2)4:0.6)6;"
+    )]
+    #[case(
+        "((a:0.3,c:0.4):0.5,(b:0.1,d:0.2):0.6);",
+        "((a:0.3,c:0.4):0.5,(b:0.1,d:0.2):0.6);"
+    )]
+    #[case("((0:1e-3,2)5,(1,3)4)6;", "((0:0.001,2:0)5:0,(1:0,3:0)4:0)6;")]
+    fn test_to_newick_branch_lengths(#[case] newick: &str, #[case] expected: &str) {
+        let tree = TreeVec::from_newick(newick).unwrap();
+        assert_eq!(tree.to_newick(), expected);
+    }
+
+    /// Test that branch lengths survive a round trip, also after removing a leaf
+    #[rstest]
+    #[case(10)]
+    #[case(100)]
+    fn test_to_newick_branch_lengths_round_trip(#[case] n_leaves: usize) {
+        let v = sample_vector(n_leaves, false);
+        let bls: Vec<(f64, f64)> = (0..n_leaves - 1)
+            .map(|i| (0.5 * i as f64, 0.25 * i as f64 + 1.0))
+            .collect();
+        let mut tree = TreeVec::new(v, Some(bls), None);
+        assert_eq!(TreeVec::from_newick(&tree.to_newick()), Ok(tree.clone()));
+
+        tree.remove_leaf(n_leaves / 2);
+        assert!(tree.validate().is_ok());
+        let parsed = TreeVec::from_newick(&tree.to_newick()).unwrap();
+        assert_eq!(parsed.data, tree.data);
+        assert_eq!(parsed.branch_lengths, tree.branch_lengths);
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], None, None, "((0,2)5,(1,3)4)6;")]
+    #[case(vec![0, 0, 1], Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), None, "((0:0.3,2:0.4)5:0.5,(1:0.1,3:0.2)4:0.6)6;")]
+    #[case(vec![0], None, Some(vec!["Homo sapiens".to_string(), "Gorilla".to_string()]), "('Homo sapiens',Gorilla);")]
+    fn test_display(
+        #[case] v: Vec<usize>,
+        #[case] branch_lengths: Option<Vec<(f64, f64)>>,
+        #[case] taxa: Option<Vec<String>>,
+        #[case] expected: &str,
+    ) {
+        let tree = TreeVec::new(v, branch_lengths, taxa);
+        assert_eq!(format!("{tree}"), expected);
+        assert_eq!(tree.to_string(), tree.to_newick());
+    }
+
+    #[rstest]
+    #[cfg(feature = "serde")]
+    fn test_serde_round_trip() {
+        let n_leaves = 50;
+        let v = sample_vector(n_leaves, false);
+        let bls: Vec<(f64, f64)> = (0..n_leaves - 1)
+            .map(|i| (0.125 * i as f64, 1.0 + 0.5 * i as f64))
+            .collect();
+        let taxa: Vec<String> = (0..n_leaves).map(|i| format!("taxon \"{}\"", i)).collect();
+        let tree = TreeVec::new(v.clone(), Some(bls), Some(taxa));
+
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(serde_json::from_str::<TreeVec>(&json).unwrap(), tree);
+
+        let tree = TreeVec::new(v, None, None);
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(serde_json::from_str::<TreeVec>(&json).unwrap(), tree);
+    }
+
+    /// Test that removing a leaf merges the edges around its former parent
+    #[rstest]
+    #[case("(((0:1,1:2)4:3,2:4)5:5,3:6)6;", 1, "((0:4,1:4)3:5,2:6)4;")]
+    #[case("((a:1,b:2):3,(c:4,d:5):6);", 2, "((a:1,b:2):3,d:11);")]
+    #[case("((0:1,1:2)3:3,2:4)4;", 2, "(0:1,1:2)2;")]
+    fn test_remove_leaf_branch_lengths(
+        #[case] newick: &str,
+        #[case] leaf: usize,
+        #[case] expected: &str,
+    ) {
+        let mut tree = TreeVec::from_newick(newick).unwrap();
+        tree.remove_leaf(leaf);
+        assert!(tree.validate().is_ok());
+        assert_eq!(tree.to_newick(), expected);
+    }
+
+    #[rstest]
+    #[case("(((0:1,1:2)4:3,2:4)5:5,3:6)6;", vec![1], "((0:4,1:4)3:5,2:6)4;")]
+    #[case("(((0:1,1:2)4:3,2:4)5:5,3:6)6;", vec![0, 1], "(0:9,1:6)2;")]
+    #[case("(((0:1,1:2)4:3,2:4)5:5,3:6)6;", vec![3, 0, 2], "0;")]
+    #[case("((a:1,b:2):3,(c:4,d:5):6);", vec![2, 0], "(b:5,d:11);")]
+    #[case("((a:1,b:2):3,(c:4,d:5):6);", vec![], "((a:1,b:2):3,(c:4,d:5):6);")]
+    fn test_remove_leaves(
+        #[case] newick: &str,
+        #[case] leaves: Vec<usize>,
+        #[case] expected: &str,
+    ) {
+        let mut tree = TreeVec::from_newick(newick).unwrap();
+        tree.remove_leaves(&leaves);
+        assert!(tree.validate().is_ok());
+        assert_eq!(tree.to_newick(), expected);
+    }
+
+    /// Test that removing leaves at once matches removing them one by one
+    #[rstest]
+    #[case(10, 2)]
+    #[case(10, 3)]
+    #[case(50, 2)]
+    #[case(50, 7)]
+    fn test_remove_leaves_sequential(#[case] n_leaves: usize, #[case] step: usize) {
+        let v = sample_vector(n_leaves, false);
+        // Branch lengths are exact in binary, so sums do not depend on their order
+        let bls: Vec<(f64, f64)> = (0..n_leaves - 1)
+            .map(|i| ((i % 7) as f64 + 0.5, (i % 5) as f64 + 0.25))
+            .collect();
+        let taxa: Vec<String> = (0..n_leaves).map(|i| format!("t{}", i)).collect();
+        let tree = TreeVec::new(v, Some(bls), Some(taxa));
+
+        for offset in 0..step {
+            let leaves: Vec<usize> = (offset..n_leaves).step_by(step).collect();
+
+            let mut bulk = tree.clone();
+            bulk.remove_leaves(&leaves);
+
+            let mut sequential = tree.clone();
+            for &leaf in leaves.iter().rev() {
+                sequential.remove_leaf(leaf);
+            }
+
+            assert_eq!(bulk, sequential);
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], vec![1, 1])]
+    #[case(vec![0, 0, 1], vec![4])]
+    #[case(vec![0, 0, 1], vec![0, 1, 2, 3])]
+    #[should_panic]
+    fn test_remove_leaves_invalid(#[case] v: Vec<usize>, #[case] leaves: Vec<usize>) {
+        TreeVec::new(v, None, None).remove_leaves(&leaves);
+    }
+
+    #[rstest]
+    #[case(
+        "((a:0.3,c:0.4):0.5,(b:0.1,d:0.2):0.6);",
+        "((a:0.3,c:0.4):0.5,(b:0.1,d:0.2):0.6);"
+    )]
+    #[case(
+        "(('Homo sapiens',Gorilla),'O''Brien');",
+        "(('Homo sapiens',Gorilla),'O''Brien');"
+    )]
+    #[case("((0,2)5,(1,3)4)6;", "((0,2)5,(1,3)4)6;")]
+    fn test_to_newick_taxa(#[case] newick: &str, #[case] expected: &str) {
+        let tree = TreeVec::from_newick(newick).unwrap();
+        assert_eq!(tree.to_newick(), expected);
+    }
+
+    #[rstest]
+    #[case("((0,2),(1,3);")]
+    #[case("((0:0.1,2),(1,3)4);")]
+    #[case("((0,2),(1,3))")]
+    fn test_from_newick_invalid(#[case] newick: &str) {
+        assert!(TreeVec::from_newick(newick).is_err());
+    }
+
+    #[rstest]
+    #[case("(0,0)2;", "0")]
+    #[case("((a,a),c);", "a")]
+    #[case("((a:0.1,b:0.2):0.3,a:0.4);", "a")]
+    fn test_from_newick_duplicate_leaves(#[case] newick: &str, #[case] duplicate: &str) {
+        assert_eq!(
+            TreeVec::from_newick(newick),
+            Err(NewickError::DuplicateTaxon(duplicate.to_string()))
+        );
+    }
+
     /// Test the conversion of a tree to Newick format
     ///
     /// Tests are using 5 or less leaf tree with different structures
@@ -195,11 +924,14 @@ mod tests {
 
     /// Test the removal of a leaf from the tree
     ///
-    /// Tests are using 6 leaf tree with different leaf and sister branch indices
+    /// Tests are using 6 leaf tree with different leaf and sister branch indices,
+    /// and a 10 leaf tree where a middle leaf is removed:
+    /// ((0,(2,(3,(5,(6,9))))),(1,((4,7),8))); becomes ((0,(2,(3,(4,(5,8))))),(1,(6,7)));
     #[rstest]
     #[case(vec![0, 1, 2, 5, 4, 2], 5, 4, vec![0, 1, 2, 5, 2])]
     #[case(vec![0, 1, 2, 5, 4, 2], 6, 2, vec![0, 1, 2, 5, 4])]
     #[case(vec![0, 1, 2, 5, 4, 2], 0, 11, vec![0, 1, 4, 3, 1])]
+    #[case(vec![0, 0, 2, 1, 3, 5, 4, 8, 6], 4, 7, vec![0, 0, 2, 3, 4, 1, 6, 5])]
     fn test_remove_leaf(
         #[case] v: Vec<usize>,
         #[case] leaf: usize,
@@ -211,4 +943,306 @@ mod tests {
         assert_eq!(tree.data, expected);
         assert_eq!(sister, branch);
     }
+
+    /// Test the validation of a consistent tree
+    ///
+    /// Tests are using 4 leaf trees with and without branch lengths and taxa
+    #[rstest]
+    #[case(vec![0, 0, 1], None, None)]
+    #[case(vec![0, 2, 4], Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), None)]
+    #[case(vec![0, 0, 1], Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), Some(vec!["a".to_string(), "b".to_string(), "c".to_string(), "d".to_string()]))]
+    fn test_validate(
+        #[case] v: Vec<usize>,
+        #[case] branch_lengths: Option<Vec<(f64, f64)>>,
+        #[case] taxa: Option<Vec<String>>,
+    ) {
+        let tree = TreeVec::new(v, branch_lengths, taxa);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    /// Test the validation of inconsistent trees
+    ///
+    /// Tests are using 4 leaf trees with out-of-bounds data or mismatched metadata
+    #[rstest]
+    #[case(vec![0, 3, 1], None, None, Phylo2VecError::OutOfBounds { index: 1, value: 3, max: 2 })]
+    #[case(vec![0, 0, 1], Some(vec![(0.1, 0.2), (0.3, 0.4)]), None, Phylo2VecError::BranchLengthsMismatch { expected: 3, found: 2 })]
+    #[case(vec![0, 0, 1], None, Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]), Phylo2VecError::TaxaMismatch { expected: 4, found: 3 })]
+    fn test_validate_invalid(
+        #[case] v: Vec<usize>,
+        #[case] branch_lengths: Option<Vec<(f64, f64)>>,
+        #[case] taxa: Option<Vec<String>>,
+        #[case] expected: Phylo2VecError,
+    ) {
+        let tree = TreeVec::new(v, branch_lengths, taxa);
+        assert_eq!(tree.validate(), Err(expected));
+    }
+
+    /// Test topological equality versus full equality
+    ///
+    /// Tests are using 4 leaf trees with differing metadata or topology
+    #[rstest]
+    #[case(vec![0, 0, 1], None, vec![0, 0, 1], Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), true, false)]
+    #[case(vec![0, 0, 1], Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), vec![0, 0, 1], Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), true, true)]
+    #[case(vec![0, 0, 1], None, vec![0, 1, 2], None, false, false)]
+    fn test_eq_topology(
+        #[case] v1: Vec<usize>,
+        #[case] bls1: Option<Vec<(f64, f64)>>,
+        #[case] v2: Vec<usize>,
+        #[case] bls2: Option<Vec<(f64, f64)>>,
+        #[case] expected_topology: bool,
+        #[case] expected_eq: bool,
+    ) {
+        let tree1 = TreeVec::new(v1, bls1, None);
+        let tree2 = TreeVec::new(v2, bls2, None);
+        assert_eq!(tree1.eq_topology(&tree2), expected_topology);
+        assert_eq!(tree1 == tree2, expected_eq);
+    }
+
+    /// Test that taxa are ignored by topological equality only
+    #[rstest]
+    fn test_eq_topology_taxa() {
+        let taxa = vec!["a", "b", "c", "d"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let tree1 = TreeVec::new(vec![0, 0, 1], None, Some(taxa));
+        let tree2 = TreeVec::new(vec![0, 0, 1], None, None);
+        assert!(tree1.eq_topology(&tree2));
+        assert_ne!(tree1, tree2);
+    }
+
+    /// Test contracting edges into multifurcations
+    ///
+    /// Tests are using ((0,2)5,(1,3)4)6 and (((0,(3,5)6)8,2)9,(1,4)7)10
+    #[rstest]
+    #[case(vec![0, 0, 1], vec![5], "(0,2,(1,3)4)6;")]
+    #[case(vec![0, 0, 1], vec![4], "((0,2)5,1,3)6;")]
+    #[case(vec![0, 0, 1], vec![4, 5], "(0,2,1,3)6;")]
+    #[case(vec![0, 0, 1], vec![5, 5], "(0,2,(1,3)4)6;")]
+    #[case(vec![0, 0, 0, 1, 3], vec![6, 8], "((0,3,5,2)9,(1,4)7)10;")]
+    #[case(vec![0, 0, 0, 1, 3], vec![8, 6], "((0,3,5,2)9,(1,4)7)10;")]
+    fn test_contract_edge(
+        #[case] v: Vec<usize>,
+        #[case] nodes: Vec<usize>,
+        #[case] expected: &str,
+    ) {
+        let mut tree = TreeVec::new(v.clone(), None, None);
+        for node in nodes {
+            tree.contract_edge(node);
+        }
+        assert_eq!(tree.to_newick(), expected);
+        assert_eq!(tree.data, v);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    /// Test that contracted branch lengths are moved to the edges below
+    ///
+    /// ((0:0.75,2:1)5:1.5,(1:0.25,3:0.5)4:2)6
+    #[rstest]
+    #[case(vec![5], vec![(0.25, 0.5), (2.25, 2.5), (0.0, 2.0)])]
+    #[case(vec![4], vec![(2.25, 2.5), (0.75, 1.0), (1.5, 0.0)])]
+    #[case(vec![5, 4], vec![(2.25, 2.5), (2.25, 2.5), (0.0, 0.0)])]
+    fn test_contract_edge_branch_lengths(
+        #[case] nodes: Vec<usize>,
+        #[case] expected: Vec<(f64, f64)>,
+    ) {
+        let bls = vec![(0.25, 0.5), (0.75, 1.0), (1.5, 2.0)];
+        let mut tree = TreeVec::new(vec![0, 0, 1], Some(bls), None);
+        for node in nodes {
+            tree.contract_edge(node);
+        }
+        assert_eq!(tree.branch_lengths, Some(expected));
+    }
+
+    /// Test that contracting an edge above a leaf or the root panics
+    #[rstest]
+    #[case(3)]
+    #[case(6)]
+    #[should_panic]
+    fn test_contract_edge_invalid(#[case] node: usize) {
+        let mut tree = TreeVec::new(vec![0, 0, 1], None, None);
+        tree.contract_edge(node);
+    }
+
+    /// Test topological equality of multifurcating trees
+    ///
+    /// ((0,2)5,(1,3)4)6 and ((0,(1,3)4)5,2)6 are both resolutions of (0,2,(1,3))
+    #[rstest]
+    #[case(vec![5], vec![5], true)]
+    #[case(vec![5], vec![], false)]
+    #[case(vec![], vec![5], false)]
+    #[case(vec![4, 5], vec![4, 5], true)]
+    #[case(vec![4], vec![4], false)]
+    fn test_eq_topology_contracted(
+        #[case] nodes1: Vec<usize>,
+        #[case] nodes2: Vec<usize>,
+        #[case] expected: bool,
+    ) {
+        let mut tree1 = TreeVec::new(vec![0, 0, 1], None, None);
+        let mut tree2 = TreeVec::new(vec![0, 2, 1], None, None);
+        for node in nodes1 {
+            tree1.contract_edge(node);
+        }
+        for node in nodes2 {
+            tree2.contract_edge(node);
+        }
+        assert_eq!(tree1.eq_topology(&tree2), expected);
+    }
+
+    /// Test that trees built from vectors are binary
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec![0])]
+    #[case(vec![0, 0, 1])]
+    #[case(crate::utils::sample_vector(100, false))]
+    fn test_is_binary(#[case] v: Vec<usize>) {
+        assert!(TreeVec::new(v, None, None).is_binary());
+    }
+
+    /// Test that trees with a contracted edge are not binary
+    #[rstest]
+    #[case(vec![0, 0, 1], 4)]
+    #[case(vec![0, 0, 0, 1, 3], 8)]
+    fn test_is_binary_contracted(#[case] v: Vec<usize>, #[case] node: usize) {
+        let mut tree = TreeVec::new(v, None, None);
+        tree.contract_edge(node);
+        assert!(!tree.is_binary());
+    }
+
+    /// Test that operations editing the vector reject multifurcating trees
+    #[rstest]
+    #[should_panic(expected = "Tree must be binary")]
+    fn test_multifurcating_add_leaf() {
+        let mut tree = TreeVec::new(vec![0, 0, 1], None, None);
+        tree.contract_edge(5);
+        tree.add_leaf(4, 0);
+    }
+
+    #[rstest]
+    #[should_panic(expected = "Tree must be binary")]
+    fn test_multifurcating_remove_leaves() {
+        let mut tree = TreeVec::new(vec![0, 0, 1], None, None);
+        tree.contract_edge(5);
+        tree.remove_leaves(&[0]);
+    }
+
+    /// Test swapping two leaves
+    ///
+    /// Tests are using 4 leaf trees, ((0,2)5,(1,3)4)6 and (((0,3)4,2)5,1)6
+    #[rstest]
+    #[case(vec![0, 0, 1], 0, 3, "((3,2),(1,0));")]
+    #[case(vec![0, 0, 1], 1, 2, "((0,1),(2,3));")]
+    #[case(vec![0, 0, 1], 0, 2, "((2,0),(1,3));")]
+    #[case(vec![0, 0, 0], 3, 1, "(((0,1),2),3);")]
+    #[case(vec![0, 0, 0], 2, 2, "(((0,3),2),1);")]
+    fn test_swap_leaves(
+        #[case] v: Vec<usize>,
+        #[case] a: usize,
+        #[case] b: usize,
+        #[case] expected_newick: &str,
+    ) {
+        let original = TreeVec::new(v, None, None);
+        let mut tree = original.clone();
+        tree.swap_leaves(a, b);
+        assert_eq!(tree.data, ops::to_vector(expected_newick));
+
+        // Swapping the same leaves again restores the original tree
+        tree.swap_leaves(a, b);
+        assert_eq!(tree, original);
+    }
+
+    #[rstest]
+    #[case(vec![0])]
+    #[case(vec![0, 0, 1])]
+    #[case(vec![0, 1, 2, 3, 4])]
+    #[case(sample_vector(50, false))]
+    fn test_reverse_leaf_order(#[case] v: Vec<usize>) {
+        let n_leaves = v.len() + 1;
+        let taxa: Vec<String> = (0..n_leaves).map(|i| format!("t{}", i)).collect();
+        let bls: Vec<(f64, f64)> = (0..n_leaves - 1)
+            .map(|i| (i as f64, (i + n_leaves) as f64))
+            .collect();
+        let tree = TreeVec::new(v.clone(), Some(bls), Some(taxa.clone()));
+
+        let mut reversed = tree.clone();
+        reversed.reverse_leaf_order();
+        assert!(reversed.validate().is_ok());
+
+        // The Newick leaf sequence is mirrored: each leaf i is replaced by n - 1 - i
+        let newick = ops::newick::remove_parent_labels(&ops::to_newick_from_vector(&tree.data));
+        let mut mirrored = String::new();
+        let mut label = String::new();
+        for ch in newick.chars() {
+            if ch.is_ascii_digit() {
+                label.push(ch);
+                continue;
+            }
+            if !label.is_empty() {
+                let leaf: usize = label.parse().unwrap();
+                mirrored.push_str(&(n_leaves - 1 - leaf).to_string());
+                label.clear();
+            }
+            mirrored.push(ch);
+        }
+        assert_eq!(reversed.data, ops::to_vector(&mirrored));
+
+        let reversed_taxa: Vec<String> = taxa.into_iter().rev().collect();
+        assert_eq!(reversed.taxa, Some(reversed_taxa));
+
+        // Reversing twice restores the original tree
+        reversed.reverse_leaf_order();
+        assert_eq!(reversed, tree);
+    }
+
+    /// Test scaling the branch lengths by a constant factor
+    #[rstest]
+    #[case(Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), 2.0, Some(vec![(0.2, 0.4), (0.6, 0.8), (1.0, 1.2)]))]
+    #[case(Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), 0.0, Some(vec![(0.0, 0.0), (0.0, 0.0), (0.0, 0.0)]))]
+    #[case(None, 2.0, None)]
+    fn test_scale_branch_lengths(
+        #[case] branch_lengths: Option<Vec<(f64, f64)>>,
+        #[case] factor: f64,
+        #[case] expected: Option<Vec<(f64, f64)>>,
+    ) {
+        let mut tree = TreeVec::new(vec![0, 0, 1], branch_lengths, None);
+        tree.scale_branch_lengths(factor);
+
+        assert_eq!(tree.branch_lengths, expected);
+        assert_eq!(tree.data, vec![0, 0, 1]);
+    }
+
+    /// Test that branch lengths and taxa follow the swapped leaves
+    #[rstest]
+    fn test_swap_leaves_metadata() {
+        // ((0:0.3,2:0.4)5:0.5,(1:0.1,3:0.2)4:0.6)6;
+        let taxa = vec!["a", "b", "c", "d"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let bls = vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)];
+        let mut tree = TreeVec::new(vec![0, 0, 1], Some(bls), Some(taxa));
+
+        tree.swap_leaves(0, 3);
+
+        // ((3:0.3,2:0.4):0.5,(1:0.1,0:0.2):0.6);
+        let new_ancestry = tree.get_ancestry();
+        let new_bls = tree.branch_lengths.clone().unwrap();
+        let mut pendant = vec![0.0; 4];
+        for (&[c1, c2, _], &(bl1, bl2)) in new_ancestry.iter().zip(new_bls.iter()) {
+            if c1 < 4 {
+                pendant[c1] = bl1;
+            }
+            if c2 < 4 {
+                pendant[c2] = bl2;
+            }
+        }
+        assert_eq!(pendant, vec![0.2, 0.1, 0.4, 0.3]);
+
+        // The names stay at their positions in the tree
+        let taxa: Vec<String> = vec!["d", "b", "c", "a"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(tree.taxa, Some(taxa));
+    }
 }
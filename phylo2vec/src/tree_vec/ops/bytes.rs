@@ -0,0 +1,158 @@
+use crate::errors::Phylo2VecError;
+
+/// Encode a Phylo2Vec vector as a compact sequence of bytes
+///
+/// # Format
+///
+/// The output is a sequence of unsigned LEB128 variable-length integers:
+/// the length of the vector (n - 1 for n leaves) followed by each v[i].
+/// Each integer is split into groups of 7 bits, least significant first;
+/// every byte holds one group, with the high bit set if more bytes follow.
+///
+/// As v[i] <= 2i, values below 64 (the first 32 entries) use a single byte
+/// and values below 8192 use two bytes, whereas a Newick string needs
+/// several characters per leaf.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::bytes::{from_bytes, to_bytes};
+///
+/// let v = vec![0, 1, 2, 3];
+/// let bytes = to_bytes(&v);
+/// assert_eq!(bytes, vec![4, 0, 1, 2, 3]);
+/// assert_eq!(from_bytes(&bytes).unwrap(), v);
+/// ```
+pub fn to_bytes(v: &[usize]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(v.len() + 1);
+    _write_varint(&mut bytes, v.len());
+    for &vi in v {
+        _write_varint(&mut bytes, vi);
+    }
+    bytes
+}
+
+/// Decode a Phylo2Vec vector encoded with `to_bytes`
+///
+/// # Errors
+///
+/// Returns a `Phylo2VecError` if the bytes are truncated, contain trailing
+/// data or an integer overflowing `usize`, or if the decoded vector violates
+/// the Phylo2Vec constraints (v[i] <= 2i)
+pub fn from_bytes(bytes: &[u8]) -> Result<Vec<usize>, Phylo2VecError> {
+    let mut pos = 0;
+    let len = _read_varint(bytes, &mut pos)?;
+
+    // Each entry takes at least one byte
+    if len > bytes.len() - pos {
+        return Err(Phylo2VecError::InvalidEncoding(format!(
+            "expected {} entries, found at most {}",
+            len,
+            bytes.len() - pos
+        )));
+    }
+
+    let mut v = Vec::with_capacity(len);
+    for index in 0..len {
+        let value = _read_varint(bytes, &mut pos)?;
+        let max = 2 * index;
+        if value > max {
+            return Err(Phylo2VecError::OutOfBounds { index, value, max });
+        }
+        v.push(value);
+    }
+
+    if pos != bytes.len() {
+        return Err(Phylo2VecError::InvalidEncoding(format!(
+            "{} trailing bytes",
+            bytes.len() - pos
+        )));
+    }
+
+    Ok(v)
+}
+
+// Append an unsigned LEB128 integer
+fn _write_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+// Read an unsigned LEB128 integer starting at `pos`, and move `pos` past it
+fn _read_varint(bytes: &[u8], pos: &mut usize) -> Result<usize, Phylo2VecError> {
+    let mut value: usize = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos).ok_or_else(|| {
+            Phylo2VecError::InvalidEncoding("unexpected end of input".to_string())
+        })?;
+        *pos += 1;
+
+        let group = (byte & 0x7F) as usize;
+        if shift >= usize::BITS || (group << shift) >> shift != group {
+            return Err(Phylo2VecError::InvalidEncoding(
+                "integer overflow".to_string(),
+            ));
+        }
+        value |= group << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_vec::ops::to_newick_from_vector;
+    use crate::utils::sample_vector;
+    use rstest::*;
+
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec![0])]
+    #[case(vec![0, 0, 1])]
+    #[case(vec![0, 2, 4, 6, 8, 10, 12, 14, 16, 18, 20, 22, 24, 26, 28, 30, 32, 34, 36, 38, 40, 42, 44, 46, 48, 50, 52, 54, 56, 58, 60, 62, 64, 66])]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(1000, false))]
+    #[case(sample_vector(100000, true))]
+    fn test_bytes_round_trip(#[case] v: Vec<usize>) {
+        assert_eq!(from_bytes(&to_bytes(&v)).unwrap(), v);
+    }
+
+    #[rstest]
+    #[case(vec![0], vec![1, 0])]
+    #[case(vec![0, 2], vec![2, 0, 2])]
+    #[case(vec![0, 1, 2, 3, 200], vec![5, 0, 1, 2, 3, 0xC8, 0x01])]
+    fn test_to_bytes(#[case] v: Vec<usize>, #[case] expected: Vec<u8>) {
+        assert_eq!(to_bytes(&v), expected);
+    }
+
+    /// Test that the encoding is smaller than the Newick string
+    #[rstest]
+    #[case(100)]
+    #[case(10000)]
+    fn test_bytes_size(#[case] n_leaves: usize) {
+        let v = sample_vector(n_leaves, false);
+        let n_bytes = to_bytes(&v).len();
+        let newick = to_newick_from_vector(&v);
+
+        assert!(n_bytes <= 2 * n_leaves);
+        assert!(n_bytes < newick.len() / 4);
+    }
+
+    #[rstest]
+    #[case(vec![], Phylo2VecError::InvalidEncoding("unexpected end of input".to_string()))]
+    #[case(vec![2, 0], Phylo2VecError::InvalidEncoding("expected 2 entries, found at most 1".to_string()))]
+    #[case(vec![2, 0, 0x81], Phylo2VecError::InvalidEncoding("unexpected end of input".to_string()))]
+    #[case(vec![1, 0, 0], Phylo2VecError::InvalidEncoding("1 trailing bytes".to_string()))]
+    #[case(vec![2, 0, 3], Phylo2VecError::OutOfBounds { index: 1, value: 3, max: 2 })]
+    #[case(vec![1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01], Phylo2VecError::InvalidEncoding("integer overflow".to_string()))]
+    fn test_from_bytes_invalid(#[case] bytes: Vec<u8>, #[case] expected: Phylo2VecError) {
+        assert_eq!(from_bytes(&bytes), Err(expected));
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use crate::tree_vec::ops::vector::{descendant_bitsets, get_ancestry};
+
+/// A difference between the ancestries of two trees
+///
+/// Nodes are identified by their clade, i.e. the sorted list of their
+/// descendant leaves, so that the internal node labels of both trees need
+/// not match.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AncestryChange {
+    /// A clade of the first tree that is not in the second tree
+    Removed { leaves: Vec<usize> },
+    /// A clade of the second tree that is not in the first tree
+    Added { leaves: Vec<usize> },
+    /// A clade of both trees whose parent clade differs
+    Moved {
+        leaves: Vec<usize>,
+        from: Vec<usize>,
+        to: Vec<usize>,
+    },
+}
+
+/// Get the changes between the ancestries of two trees with the same leaves
+///
+/// Clades of one tree only are reported as `Removed` or `Added`. Clades of
+/// both trees (including single leaves) whose parent clade differs are
+/// reported as `Moved`. The changes are sorted, with all `Removed` changes
+/// first, then `Added` and `Moved` changes. Identical trees yield no change.
+///
+/// This is meant as a debugging tool to understand the effect of tree edits
+/// such as `add_leaf` or `nni`.
+///
+/// # Panics
+///
+/// Panics if the trees do not have the same number of leaves
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::diff::{ancestry_diff, AncestryChange};
+/// use phylo2vec::tree_vec::ops::to_vector;
+///
+/// let v1 = to_vector("(((0,1),2),3);");
+/// let v2 = to_vector("(((0,2),1),3);");
+/// let changes = ancestry_diff(&v1, &v2);
+/// assert_eq!(changes[0], AncestryChange::Removed { leaves: vec![0, 1] });
+/// assert_eq!(changes[1], AncestryChange::Added { leaves: vec![0, 2] });
+/// ```
+pub fn ancestry_diff(v1: &[usize], v2: &[usize]) -> Vec<AncestryChange> {
+    assert_eq!(
+        v1.len(),
+        v2.len(),
+        "Both trees must have the same number of leaves"
+    );
+    let n_leaves = v1.len() + 1;
+
+    let parents1 = _get_parent_clades(v1);
+    let parents2 = _get_parent_clades(v2);
+
+    let mut changes: Vec<AncestryChange> = Vec::new();
+    for (clade, parent1) in parents1.iter() {
+        match parents2.get(clade) {
+            None => changes.push(AncestryChange::Removed {
+                leaves: _to_leaves(clade, n_leaves),
+            }),
+            Some(parent2) if parent2 != parent1 => changes.push(AncestryChange::Moved {
+                leaves: _to_leaves(clade, n_leaves),
+                from: _to_leaves(parent1, n_leaves),
+                to: _to_leaves(parent2, n_leaves),
+            }),
+            Some(_) => {}
+        }
+    }
+    for clade in parents2.keys() {
+        if !parents1.contains_key(clade) {
+            changes.push(AncestryChange::Added {
+                leaves: _to_leaves(clade, n_leaves),
+            });
+        }
+    }
+
+    // HashMap iteration order is arbitrary
+    changes.sort();
+    changes
+}
+
+// Map the clade (as a bitset) of each non-root node to the clade of its parent
+fn _get_parent_clades(v: &[usize]) -> HashMap<Vec<u64>, Vec<u64>> {
+    let n_leaves = v.len() + 1;
+    let n_blocks = n_leaves.div_ceil(64);
+
+    let mut clades: Vec<Vec<u64>> = (0..n_leaves)
+        .map(|leaf| {
+            let mut bitset = vec![0u64; n_blocks];
+            bitset[leaf / 64] |= 1 << (leaf % 64);
+            bitset
+        })
+        .collect();
+    clades.extend(descendant_bitsets(v));
+
+    let mut parents = HashMap::with_capacity(2 * n_leaves - 2);
+    for [c1, c2, p] in get_ancestry(v) {
+        parents.insert(clades[c1].clone(), clades[p].clone());
+        parents.insert(clades[c2].clone(), clades[p].clone());
+    }
+    parents
+}
+
+// Decode a bitset into the sorted list of its leaves
+fn _to_leaves(bitset: &[u64], n_leaves: usize) -> Vec<usize> {
+    (0..n_leaves)
+        .filter(|&leaf| bitset[leaf / 64] & (1 << (leaf % 64)) != 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_vec::ops::to_vector;
+    use crate::tree_vec::ops::vector::nni;
+    use crate::utils::sample_vector;
+    use rstest::*;
+
+    #[rstest]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(100, true))]
+    fn test_ancestry_diff_identical(#[case] v: Vec<usize>) {
+        assert!(ancestry_diff(&v, &v).is_empty());
+    }
+
+    /// Test the changes made by an NNI on ((0,2),(1,3)): the cherry (1,3) is
+    /// replaced by ((0,2),3), and the three swapped subtrees move
+    #[rstest]
+    fn test_ancestry_diff_nni() {
+        let v1 = vec![0, 0, 1];
+        let v2 = nni(&v1, 0);
+        assert_eq!(v2, to_vector("(((0,2),3),1);"));
+
+        let changes = ancestry_diff(&v1, &v2);
+        assert_eq!(
+            changes,
+            vec![
+                AncestryChange::Removed { leaves: vec![1, 3] },
+                AncestryChange::Added {
+                    leaves: vec![0, 2, 3]
+                },
+                AncestryChange::Moved {
+                    leaves: vec![0, 2],
+                    from: vec![0, 1, 2, 3],
+                    to: vec![0, 2, 3]
+                },
+                AncestryChange::Moved {
+                    leaves: vec![1],
+                    from: vec![1, 3],
+                    to: vec![0, 1, 2, 3]
+                },
+                AncestryChange::Moved {
+                    leaves: vec![3],
+                    from: vec![1, 3],
+                    to: vec![0, 2, 3]
+                },
+            ]
+        );
+    }
+
+    /// Test that as many clades are removed as added
+    #[rstest]
+    #[case(10)]
+    #[case(100)]
+    fn test_ancestry_diff_sampled(#[case] n_leaves: usize) {
+        let v1 = sample_vector(n_leaves, false);
+        let v2 = sample_vector(n_leaves, false);
+        let changes = ancestry_diff(&v1, &v2);
+
+        let n_removed = changes
+            .iter()
+            .filter(|c| matches!(c, AncestryChange::Removed { .. }))
+            .count();
+        let n_added = changes
+            .iter()
+            .filter(|c| matches!(c, AncestryChange::Added { .. }))
+            .count();
+        // Both trees have n - 2 non-root clades
+        assert_eq!(n_removed, n_added);
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_ancestry_diff_mismatched_leaves() {
+        ancestry_diff(&[0, 0, 1], &[0, 0]);
+    }
+}
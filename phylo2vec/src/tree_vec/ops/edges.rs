@@ -0,0 +1,228 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::errors::Phylo2VecError;
+use crate::tree_vec::ops::subtree::_ancestry_to_vector;
+use crate::tree_vec::ops::vector::get_ancestry;
+use crate::tree_vec::types::Ancestry;
+
+/// Get the (parent, child) edges of the tree described by a Phylo2Vec vector
+///
+/// Leaves are labelled `0..n_leaves` and internal nodes `n_leaves..2 * n_leaves - 1`,
+/// as in the ancestry. The two edges of each internal node are consecutive,
+/// and internal nodes appear in the order of the ancestry, ending with the root.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_edges;
+///
+/// // ((0,2)5,(1,3)4)6
+/// assert_eq!(
+///     to_edges(&vec![0, 0, 1]),
+///     vec![(4, 1), (4, 3), (5, 0), (5, 2), (6, 5), (6, 4)]
+/// );
+/// ```
+pub fn to_edges(v: &[usize]) -> Vec<(usize, usize)> {
+    get_ancestry(v)
+        .iter()
+        .flat_map(|&[c1, c2, p]| [(p, c1), (p, c2)])
+        .collect()
+}
+
+/// Recover a Phylo2Vec vector from a list of (parent, child) edges
+///
+/// Leaves must be labelled `0..n_leaves`. Internal nodes can have any other
+/// label, and the edges can be given in any order.
+///
+/// # Errors
+///
+/// Returns `Phylo2VecError::InvalidEdges` if the edges do not form a rooted
+/// binary tree with leaves `0..n_leaves`, e.g. if a node has several parents,
+/// an internal node does not have two children, or some nodes are not
+/// connected to the root
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::from_edges;
+///
+/// // ((0,2),(1,3)) with internal nodes labelled 10, 11 and 12 (the root)
+/// let edges = [(12, 10), (12, 11), (10, 0), (10, 2), (11, 1), (11, 3)];
+/// assert_eq!(from_edges(&edges, 4), Ok(vec![0, 0, 1]));
+/// assert!(from_edges(&edges[1..], 4).is_err());
+/// ```
+pub fn from_edges(edges: &[(usize, usize)], n_leaves: usize) -> Result<Vec<usize>, Phylo2VecError> {
+    let invalid = |reason: String| Err(Phylo2VecError::InvalidEdges(reason));
+
+    if n_leaves == 0 {
+        return invalid("a tree must have at least one leaf".to_string());
+    }
+    if edges.len() != 2 * n_leaves - 2 {
+        return invalid(format!(
+            "expected {} edges for {} leaves, found {}",
+            2 * n_leaves - 2,
+            n_leaves,
+            edges.len()
+        ));
+    }
+    if edges.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::with_capacity(n_leaves - 1);
+    let mut has_parent: HashSet<usize> = HashSet::with_capacity(edges.len());
+    for &(parent, child) in edges {
+        if parent < n_leaves {
+            return invalid(format!("leaf {} has a child", parent));
+        }
+        if !has_parent.insert(child) {
+            return invalid(format!("node {} has several parents", child));
+        }
+        children.entry(parent).or_default().push(child);
+    }
+
+    let roots: Vec<usize> = children
+        .keys()
+        .copied()
+        .filter(|node| !has_parent.contains(node))
+        .collect();
+    let root = match roots[..] {
+        [root] => root,
+        _ => return invalid(format!("expected a single root, found {}", roots.len())),
+    };
+
+    // Post-order traversal: row i of the ancestry describes internal node n + i
+    let mut ancestry: Ancestry = Vec::with_capacity(n_leaves - 1);
+    let mut labels: HashMap<usize, usize> = HashMap::with_capacity(n_leaves - 1);
+    let mut stack: Vec<(usize, bool)> = vec![(root, false)];
+    while let Some((node, expanded)) = stack.pop() {
+        let node_children = match children.get(&node) {
+            Some(node_children) => node_children,
+            None if node < n_leaves => continue,
+            None => return invalid(format!("node {} has no children", node)),
+        };
+        let &[c1, c2] = node_children.as_slice() else {
+            return invalid(format!(
+                "node {} has {} children, expected 2",
+                node,
+                node_children.len()
+            ));
+        };
+
+        if expanded {
+            let label = |child: usize| labels.get(&child).copied().unwrap_or(child);
+            ancestry.push([label(c1), label(c2), n_leaves + ancestry.len()]);
+            labels.insert(node, n_leaves + ancestry.len() - 1);
+        } else {
+            stack.push((node, true));
+            stack.push((c2, false));
+            stack.push((c1, false));
+        }
+    }
+
+    // Each node has at most one parent, so nodes missed by the traversal
+    // belong to cycles disconnected from the root
+    if ancestry.len() != n_leaves - 1 {
+        return invalid("some nodes are not connected to the root".to_string());
+    }
+
+    Ok(_ancestry_to_vector(&ancestry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::sample_vector;
+    use rand::seq::SliceRandom;
+    use rstest::*;
+
+    #[rstest]
+    #[case(vec![], vec![])]
+    #[case(vec![0], vec![(2, 0), (2, 1)])]
+    #[case(vec![0, 0, 1], vec![(4, 1), (4, 3), (5, 0), (5, 2), (6, 5), (6, 4)])]
+    fn test_to_edges(#[case] v: Vec<usize>, #[case] expected: Vec<(usize, usize)>) {
+        assert_eq!(to_edges(&v), expected);
+    }
+
+    #[rstest]
+    #[case(10)]
+    #[case(100)]
+    fn test_to_edges_tree(#[case] n_leaves: usize) {
+        let v = sample_vector(n_leaves, false);
+        let edges = to_edges(&v);
+        assert_eq!(edges.len(), 2 * n_leaves - 2);
+
+        // Every node except the root has exactly one parent
+        let mut children: Vec<usize> = edges.iter().map(|&(_, c)| c).collect();
+        children.sort_unstable();
+        assert_eq!(children, (0..2 * n_leaves - 2).collect::<Vec<usize>>());
+
+        // Every internal node has exactly two children
+        let mut parents: Vec<usize> = edges.iter().map(|&(p, _)| p).collect();
+        parents.sort_unstable();
+        parents.dedup();
+        assert_eq!(
+            parents,
+            (n_leaves..2 * n_leaves - 1).collect::<Vec<usize>>()
+        );
+    }
+
+    #[rstest]
+    #[case(vec![], 1, vec![])]
+    #[case(vec![(2, 0), (2, 1)], 2, vec![0])]
+    // (((0,(3,5)),2),(1,4)) with arbitrary internal labels
+    #[case(vec![(7, 3), (7, 5), (9, 0), (9, 7), (8, 9), (8, 2), (100, 8), (100, 6), (6, 1), (6, 4)], 6, vec![0, 0, 0, 1, 3])]
+    fn test_from_edges(
+        #[case] edges: Vec<(usize, usize)>,
+        #[case] n_leaves: usize,
+        #[case] expected: Vec<usize>,
+    ) {
+        assert_eq!(from_edges(&edges, n_leaves), Ok(expected));
+    }
+
+    #[rstest]
+    #[case(10)]
+    #[case(100)]
+    fn test_from_edges_sampled(#[case] n_leaves: usize) {
+        let mut rng = rand::thread_rng();
+        let v = sample_vector(n_leaves, false);
+        let mut edges = to_edges(&v);
+        assert_eq!(from_edges(&edges, n_leaves), Ok(v.clone()));
+
+        // Shuffle the edges and relabel the internal nodes
+        let mut internal_labels: Vec<usize> = (n_leaves..2 * n_leaves - 1).collect();
+        internal_labels.shuffle(&mut rng);
+        let relabel = |node: usize| {
+            if node < n_leaves {
+                node
+            } else {
+                1000 + internal_labels[node - n_leaves]
+            }
+        };
+        edges = edges
+            .into_iter()
+            .map(|(p, c)| (relabel(p), relabel(c)))
+            .collect();
+        edges.shuffle(&mut rng);
+        assert_eq!(from_edges(&edges, n_leaves), Ok(v));
+    }
+
+    #[rstest]
+    #[case(vec![(2, 0), (2, 1)], 0, "a tree must have at least one leaf")]
+    #[case(vec![(2, 0)], 2, "expected 2 edges for 2 leaves, found 1")]
+    #[case(vec![(2, 0), (1, 2)], 2, "leaf 1 has a child")]
+    #[case(vec![(2, 0), (2, 0)], 2, "node 0 has several parents")]
+    #[case(vec![(3, 0), (3, 4), (4, 1), (4, 5)], 3, "node 5 has no children")]
+    #[case(vec![(4, 0), (4, 1), (4, 2), (5, 4)], 3, "node 5 has 1 children, expected 2")]
+    #[case(vec![(3, 0), (3, 1), (4, 2), (4, 5)], 3, "expected a single root, found 2")]
+    #[case(vec![(3, 0), (3, 4), (4, 1), (4, 3)], 3, "expected a single root, found 0")]
+    #[case(vec![(6, 0), (6, 1), (4, 2), (4, 5), (5, 4), (5, 3)], 4, "some nodes are not connected to the root")]
+    fn test_from_edges_invalid(
+        #[case] edges: Vec<(usize, usize)>,
+        #[case] n_leaves: usize,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            from_edges(&edges, n_leaves),
+            Err(Phylo2VecError::InvalidEdges(expected.to_string()))
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::tree_vec::ops::vector::get_ancestry;
+
+/// Precomputed structure for lowest common ancestor (LCA) queries
+///
+/// Built from an Euler tour of the tree and a sparse table of range minima
+/// over the depths along the tour. Construction takes O(n log n) time and
+/// memory; each `lca` query then runs in O(1). This is worthwhile when
+/// many MRCA queries are made on the same tree.
+///
+/// Nodes follow the Phylo2Vec numbering: leaves are 0..n-1,
+/// internal nodes are n..2n-2 and the root is 2n-2.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::lca::LcaIndex;
+///
+/// // ((0,2)5,(1,3)4)6;
+/// let index = LcaIndex::new(&vec![0, 0, 1]);
+/// assert_eq!(index.lca(0, 2), 5);
+/// assert_eq!(index.lca(0, 1), 6);
+/// assert_eq!(index.lca(3, 4), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LcaIndex {
+    /// Depth of each node (number of edges from the root)
+    depths: Vec<usize>,
+    /// Position of the first occurrence of each node in the Euler tour
+    first: Vec<usize>,
+    /// sparse[k][i] = shallowest node of the Euler tour in [i, i + 2^k)
+    sparse: Vec<Vec<usize>>,
+}
+
+impl LcaIndex {
+    /// Builds the LCA index of the tree described by a Phylo2Vec vector
+    ///
+    /// The Euler tour is computed iteratively, like `visit_postorder`.
+    pub fn new(v: &[usize]) -> Self {
+        let n_leaves = v.len() + 1;
+        let n_nodes = 2 * n_leaves - 1;
+        let root = n_nodes - 1;
+        let ancestry = get_ancestry(v);
+
+        let mut depths = vec![0; n_nodes];
+        let mut first = vec![usize::MAX; n_nodes];
+        let mut euler: Vec<usize> = Vec::with_capacity(2 * n_nodes - 1);
+
+        // (node, number of children visited so far)
+        let mut stack: Vec<(usize, usize)> = vec![(root, 0)];
+        first[root] = 0;
+        euler.push(root);
+
+        while let Some(&(node, n_visited)) = stack.last() {
+            if node < n_leaves || n_visited == 2 {
+                stack.pop();
+                if let Some(&(parent, _)) = stack.last() {
+                    euler.push(parent);
+                }
+                continue;
+            }
+
+            let child = ancestry[node - n_leaves][n_visited];
+            stack.last_mut().unwrap().1 += 1;
+
+            depths[child] = depths[node] + 1;
+            first[child] = euler.len();
+            euler.push(child);
+            stack.push((child, 0));
+        }
+
+        let mut sparse: Vec<Vec<usize>> = vec![euler];
+        let mut width = 1;
+        while 2 * width <= sparse[0].len() {
+            let prev = sparse.last().unwrap();
+            let level: Vec<usize> = (0..prev.len() - width)
+                .map(|i| {
+                    let (a, b) = (prev[i], prev[i + width]);
+                    if depths[a] <= depths[b] {
+                        a
+                    } else {
+                        b
+                    }
+                })
+                .collect();
+            sparse.push(level);
+            width *= 2;
+        }
+
+        LcaIndex {
+            depths,
+            first,
+            sparse,
+        }
+    }
+
+    /// Gets the lowest common ancestor of two nodes
+    ///
+    /// The LCA of a node with itself (or with one of its descendants)
+    /// is the node itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` is not a node of the tree
+    pub fn lca(&self, a: usize, b: usize) -> usize {
+        let max = self.first.len() - 1;
+        assert!(
+            a <= max && b <= max,
+            "Nodes ({}, {}) are out of bounds (max = {})",
+            a,
+            b,
+            max
+        );
+
+        let (l, r) = {
+            let (fa, fb) = (self.first[a], self.first[b]);
+            (fa.min(fb), fa.max(fb))
+        };
+
+        // Two (possibly overlapping) power-of-two windows cover [l, r]
+        let k = (r - l + 1).ilog2() as usize;
+        let x = self.sparse[k][l];
+        let y = self.sparse[k][r + 1 - (1 << k)];
+
+        if self.depths[x] <= self.depths[y] {
+            x
+        } else {
+            y
+        }
+    }
+
+    /// Gets the depth of a node, i.e. its number of edges from the root
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is not a node of the tree
+    pub fn depth(&self, node: usize) -> usize {
+        self.depths[node]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::sample_vector;
+    use rstest::*;
+
+    /// Naive MRCA: walk up from both nodes to the root
+    fn naive_mrca(v: &[usize], a: usize, b: usize) -> usize {
+        let n_leaves = v.len() + 1;
+        let mut parents = vec![usize::MAX; 2 * n_leaves - 1];
+        for [c1, c2, p] in get_ancestry(v) {
+            parents[c1] = p;
+            parents[c2] = p;
+        }
+
+        let mut ancestors_of_a = vec![false; 2 * n_leaves - 1];
+        let mut node = a;
+        while node != usize::MAX {
+            ancestors_of_a[node] = true;
+            node = parents[node];
+        }
+
+        let mut node = b;
+        while !ancestors_of_a[node] {
+            node = parents[node];
+        }
+        node
+    }
+
+    #[rstest]
+    #[case(vec![0], 0, 1, 2)]
+    #[case(vec![0, 0, 1], 0, 2, 5)]
+    #[case(vec![0, 0, 1], 1, 3, 4)]
+    #[case(vec![0, 0, 1], 2, 3, 6)]
+    #[case(vec![0, 0, 1], 2, 2, 2)]
+    #[case(vec![0, 0, 1], 5, 0, 5)]
+    #[case(vec![0, 0, 0, 1, 3], 3, 2, 9)]
+    fn test_lca(
+        #[case] v: Vec<usize>,
+        #[case] a: usize,
+        #[case] b: usize,
+        #[case] expected: usize,
+    ) {
+        let index = LcaIndex::new(&v);
+        assert_eq!(index.lca(a, b), expected);
+        assert_eq!(index.lca(b, a), expected);
+    }
+
+    /// Compare against the naive MRCA for all pairs of nodes
+    #[rstest]
+    #[case(sample_vector(2, false))]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(50, false))]
+    #[case(sample_vector(50, true))]
+    #[case(sample_vector(100, false))]
+    fn test_lca_all_pairs(#[case] v: Vec<usize>) {
+        let index = LcaIndex::new(&v);
+        let n_nodes = 2 * v.len() + 1;
+        for a in 0..n_nodes {
+            for b in 0..n_nodes {
+                assert_eq!(index.lca(a, b), naive_mrca(&v, a, b));
+            }
+        }
+    }
+
+    #[rstest]
+    fn test_lca_deep_caterpillar() {
+        let n_leaves = 10_000;
+        let v: Vec<usize> = (0..n_leaves - 1).collect();
+        let index = LcaIndex::new(&v);
+
+        // (0,(1,(2,...))): the MRCA of leaves i < j is the parent of leaf i
+        assert_eq!(index.lca(0, n_leaves - 1), 2 * n_leaves - 2);
+        assert_eq!(index.lca(n_leaves - 2, n_leaves - 1), n_leaves);
+        assert_eq!(index.depth(n_leaves - 1), n_leaves - 1);
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_lca_out_of_bounds() {
+        LcaIndex::new(&[0, 0, 1]).lca(0, 7);
+    }
+}
@@ -1,6 +1,13 @@
-use crate::tree_vec::ops::newick::{get_cherries_no_parents_with_bls, get_cherries_with_bls};
-use crate::tree_vec::ops::vector::{build_vector, order_cherries, order_cherries_no_parents};
+use crate::errors::Phylo2VecError;
+use crate::tree_vec::ops::newick::{
+    create_label_mapping, get_cherries_no_parents_with_bls, get_cherries_with_bls, NewickError,
+};
+use crate::tree_vec::ops::vector::{
+    _get_clade_keys, get_ancestry, to_vector_from_cherries, CladeKey,
+};
 use crate::tree_vec::types::Ancestry;
+use crate::utils::round_to_usize;
+use std::collections::HashMap;
 
 /// Converts a Newick string to a matrix representation.
 ///
@@ -12,75 +19,177 @@ use crate::tree_vec::types::Ancestry;
 ///
 /// A `Vec<Vec<f32>>` where each row contains the tree's vector representation value and associated branch lengths.
 ///
+/// # Errors
+///
+/// Returns a `NewickError` if the Newick string or one of its branch
+/// lengths cannot be parsed, or if a node label is out of range or duplicated
+///
 /// # Example
 ///
 /// ```
 /// use phylo2vec::tree_vec::ops::matrix::to_matrix;
-/// let newick = "(0:0.1,1:0.2):0.3;";
-/// let matrix = to_matrix(newick);
-/// ```
+/// use phylo2vec::tree_vec::ops::newick::NewickError;
 ///
-/// # Notes
+/// let matrix = to_matrix("(0:0.1,1:0.2)2:0.3;").unwrap();
+/// assert_eq!(matrix, vec![vec![0.0, 0.1, 0.2]]);
 ///
-/// Assumes a valid Newick string. Relies on helper functions for processing.
-pub fn to_matrix(newick: &str) -> Vec<Vec<f32>> {
+/// let matrix = to_matrix("(0:1.2e-3,1:2E+1)2;").unwrap();
+/// assert_eq!(matrix, vec![vec![0.0, 0.0012, 20.0]]);
+///
+/// assert!(matches!(
+///     to_matrix("(0:0.1,1:abc)2;"),
+///     Err(NewickError::ParseFloatError(_))
+/// ));
+/// ```
+pub fn to_matrix(newick: &str) -> Result<Vec<Vec<f32>>, NewickError> {
     // Get the ancestry and branch lengths
-    let (mut ancestry, bls) = get_cherries_with_bls(newick);
-    let indices = _get_sorted_indices(&ancestry);
+    let (ancestry, bls) = get_cherries_with_bls(newick)?;
+    let bl_map = _get_branch_length_map(&ancestry, &bls, true);
 
-    order_cherries(&mut ancestry); // Order the cherries in the ancestry matrix based on parent values
-    let vector = build_vector(&ancestry); // Build the ordered  vector
+    // Order the cherries in the ancestry matrix based on parent values and build the vector
+    let vector = to_vector_from_cherries(ancestry, true);
 
-    let reordered_bls: Vec<[f32; 2]> = indices
-        .iter()
-        .map(|&idx| bls[idx]) // Access each element of `bls` using the index from `indices`
-        .collect();
+    Ok(_build_matrix(&vector, &bl_map))
+}
 
-    // Combine the vector with the branch lengths into a matrix
-    let mut matrix: Vec<Vec<f32>> = Vec::new();
+/// Converts a batch of Newick strings to matrices (see `to_matrix`)
+///
+/// With the `parallel` feature enabled, the trees are processed in parallel
+/// using rayon. The trees may have different numbers of leaves.
+///
+/// # Errors
+///
+/// Returns `Phylo2VecError::InvalidBatchItem` with the index of the first
+/// Newick string that cannot be parsed
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::matrix::to_matrix_batch;
+/// let newicks = vec![
+///     "(0:0.1,1:0.2)2;".to_string(),
+///     "((0:0.1,2:0.2)3:0.3,1:0.4)4;".to_string(),
+/// ];
+/// let matrices = to_matrix_batch(&newicks).unwrap();
+/// assert_eq!(matrices[0], vec![vec![0.0, 0.1, 0.2]]);
+/// assert_eq!(matrices[1].len(), 2);
+/// ```
+pub fn to_matrix_batch(newicks: &[String]) -> Result<Vec<Vec<Vec<f32>>>, Phylo2VecError> {
+    #[cfg(feature = "parallel")]
+    let results: Vec<Result<Vec<Vec<f32>>, NewickError>> = {
+        use rayon::prelude::*;
+        newicks.par_iter().map(|newick| to_matrix(newick)).collect()
+    };
 
-    for i in 0..vector.len() {
-        let row = vec![vector[i] as f32, reordered_bls[i][0], reordered_bls[i][1]];
-        matrix.push(row);
-    }
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<Result<Vec<Vec<f32>>, NewickError>> =
+        newicks.iter().map(|newick| to_matrix(newick)).collect();
 
-    matrix
+    // Report the first invalid input, regardless of the processing order
+    results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| {
+            result.map_err(|source| Phylo2VecError::InvalidBatchItem { index, source })
+        })
+        .collect()
 }
 
-// Matrix construction for the "no parents" case
+/// Converts a Newick string without parent labels to a matrix representation.
+///
+/// Same as `to_matrix`, but internal nodes need not be labelled. Leaves must
+/// be labelled with integers from 0 to n-1.
+///
+/// # Arguments
+///
+/// * `newick` - A string representing a phylogenetic tree in Newick format.
+///
+/// # Returns
+///
+/// A `Vec<Vec<f32>>` with the same row layout as `to_matrix`: the vector
+/// value followed by the branch lengths of the two children.
+/// An empty Newick string yields an empty matrix.
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::matrix::to_matrix_no_parents;
+/// let newick = "((0:0.5,1:0.6):0.7,(2:0.8,3:0.9):1.0);";
+/// let matrix = to_matrix_no_parents(newick);
+/// assert_eq!(
+///     matrix,
+///     vec![vec![0.0, 0.8, 0.9], vec![2.0, 0.5, 0.6], vec![2.0, 0.7, 1.0]]
+/// );
+/// ```
 pub fn to_matrix_no_parents(newick: &str) -> Vec<Vec<f32>> {
-    let (mut ancestry, bls) = get_cherries_no_parents_with_bls(newick); // Using the `get_cherries_no_parents` function directly
-    let indices = _get_sorted_indices(&ancestry);
-    order_cherries_no_parents(&mut ancestry);
+    // Internal nodes are identified by their minimum descendant leaf
+    let (ancestry, bls) = get_cherries_no_parents_with_bls(newick).expect("Invalid Newick string");
+    let bl_map = _get_branch_length_map(&ancestry, &bls, false);
 
-    // Extract branch lengths from the Newick string or ensure they are included in get_cherries
-    let reordered_bls: Vec<[f32; 2]> = indices
-        .iter()
-        .map(|&idx| bls[idx]) // Access each element of `bls` using the index from `indices`
-        .collect();
-    let vector = build_vector(&ancestry);
-
-    let mut matrix: Vec<Vec<f32>> = Vec::new();
-
-    for i in 0..vector.len() {
-        let row = vec![vector[i] as f32, reordered_bls[i][0], reordered_bls[i][1]];
-        matrix.push(row);
-    }
+    let vector = to_vector_from_cherries(ancestry, false);
 
-    matrix
+    _build_matrix(&vector, &bl_map)
 }
 
-// Helper function that takes an ancestry array, and returns an array of indices,
-//sorted by the parent values in the ancestry array.
-fn _get_sorted_indices(ancestry: &Ancestry) -> Vec<usize> {
-    let num_cherries = ancestry.len();
+/// Converts a Newick string with named leaves to a matrix representation.
+///
+/// Leaves are relabelled with integers in their order of appearance in the
+/// Newick string (see `create_label_mapping`), and internal node labels are ignored.
+///
+/// # Arguments
+///
+/// * `newick` - A string representing a phylogenetic tree in Newick format.
+///
+/// # Returns
+///
+/// A tuple of the matrix (as in `to_matrix`) and the leaf names, such that
+/// `taxa[i]` is the name of leaf `i` in the matrix.
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::matrix::to_matrix_with_taxa;
+/// let newick = "(a:0.1,b:0.2);";
+/// let (matrix, taxa) = to_matrix_with_taxa(newick);
+/// assert_eq!(matrix, vec![vec![0.0, 0.1, 0.2]]);
+/// assert_eq!(taxa, vec!["a", "b"]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the Newick string is invalid, e.g. if two leaves have the same name
+pub fn to_matrix_with_taxa(newick: &str) -> (Vec<Vec<f32>>, Vec<String>) {
+    let (newick_int, taxa) = create_label_mapping(newick).expect("Invalid Newick string");
+    (to_matrix_no_parents(&newick_int), taxa)
+}
 
-    // Create a vector of indices from 0 to num_cherries - 1
-    let mut indices: Vec<usize> = (0..num_cherries).collect();
+// Helper function that maps each node to the length of the branch above it.
+// The map is only used for keyed lookups (never iterated), so the matrix
+// does not depend on the HashMap iteration order.
+fn _get_branch_length_map(
+    ancestry: &Ancestry,
+    bls: &[[f32; 2]],
+    has_parents: bool,
+) -> HashMap<CladeKey, f32> {
+    let mut bl_map = HashMap::with_capacity(2 * ancestry.len());
+    let keys = _get_clade_keys(ancestry, has_parents, |leaf| (leaf, 1));
+    for (keys, bl) in keys.iter().zip(bls) {
+        bl_map.insert(keys[0], bl[0]);
+        bl_map.insert(keys[1], bl[1]);
+    }
+    bl_map
+}
 
-    // Sort the indices based on the parent value (ancestry[i][2])
-    indices.sort_by_key(|&i| ancestry[i][2]);
-    indices
+// Helper function that combines a vector with its branch lengths into a matrix.
+// Row i of the matrix holds the branch lengths of the children of
+// the i-th cherry in `get_ancestry(vector)`.
+fn _build_matrix(vector: &[usize], bl_map: &HashMap<CladeKey, f32>) -> Vec<Vec<f32>> {
+    let ancestry = get_ancestry(vector);
+    _get_clade_keys(&ancestry, true, |leaf| (leaf, 1))
+        .iter()
+        .zip(vector)
+        .map(|(keys, &v_i)| vec![v_i as f32, bl_map[&keys[0]], bl_map[&keys[1]]])
+        .collect()
 }
 
 /// Parses a matrix into its vector and branch length components.
@@ -100,7 +209,7 @@ pub fn parse_matrix(matrix: &Vec<Vec<f32>>) -> (Vec<usize>, Vec<[f32; 2]>) {
 
     for row in matrix.iter() {
         // Extract vector (ancestry) value and convert it to usize
-        vector.push(row[0] as usize);
+        vector.push(round_to_usize(row[0]));
 
         // Extract branch lengths
         branch_lengths.push([row[1], row[2]]);
@@ -109,9 +218,60 @@ pub fn parse_matrix(matrix: &Vec<Vec<f32>>) -> (Vec<usize>, Vec<[f32; 2]>) {
     (vector, branch_lengths)
 }
 
+/// Get the number of leaves of a tree in Phylo2Vec matrix format
+///
+/// A matrix with n - 1 rows (one per cherry) describes a tree with n leaves,
+/// so the tree does not need to be reconstructed.
+///
+/// # Errors
+///
+/// Returns `Phylo2VecError::InvalidMatrixRow` if a row does not have exactly 3 columns
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::matrix::matrix_num_leaves;
+///
+/// let m = vec![vec![0.0, 0.1, 0.2], vec![1.0, 0.3, 0.4]];
+/// assert_eq!(matrix_num_leaves(&m), Ok(3));
+/// ```
+pub fn matrix_num_leaves(matrix: &[Vec<f32>]) -> Result<usize, Phylo2VecError> {
+    if let Some((row, columns)) = matrix
+        .iter()
+        .map(|row| row.len())
+        .enumerate()
+        .find(|&(_, columns)| columns != 3)
+    {
+        return Err(Phylo2VecError::InvalidMatrixRow { row, columns });
+    }
+
+    Ok(matrix.len() + 1)
+}
+
+/// Multiply all branch lengths of a Phylo2Vec matrix by a constant factor
+///
+/// Only the branch length columns (1 and 2) are scaled:
+/// the vector part (column 0) is left intact.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::matrix::scale_matrix_branch_lengths;
+///
+/// let mut m = vec![vec![0.0, 0.5, 1.0], vec![1.0, 1.5, 2.0]];
+/// scale_matrix_branch_lengths(&mut m, 2.0);
+/// assert_eq!(m, vec![vec![0.0, 1.0, 2.0], vec![1.0, 3.0, 4.0]]);
+/// ```
+pub fn scale_matrix_branch_lengths(m: &mut [Vec<f32>], factor: f32) {
+    for row in m.iter_mut() {
+        row[1] *= factor;
+        row[2] *= factor;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tree_vec::ops::to_newick_from_matrix;
+    use crate::utils::sample_matrix;
     use rstest::rstest;
 
     // Test for the `to_matrix` function
@@ -132,13 +292,53 @@ mod tests {
         vec![0.0, 0.5, 0.8],
         vec![1.0, 0.7, 0.6],
     ])]
+    #[case("(0:1.2e-3,(1:5E-1,2:2e+1)3:1e2)4", vec![
+        vec![0.0, 0.5, 20.0],
+        vec![1.0, 0.0012, 100.0],
+    ])]
+    // An unlabelled root is labelled by its largest child
+    #[case("(0:0.1,1:0.2):0.3;", vec![
+        vec![0.0, 0.1, 0.2],
+    ])]
     fn test_to_matrix(#[case] newick: String, #[case] expected_matrix: Vec<Vec<f32>>) {
-        let matrix = to_matrix(&newick);
+        let matrix = to_matrix(&newick).unwrap();
 
         // Check if the matrix matches the expected matrix
         assert_eq!(matrix, expected_matrix);
     }
 
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec![
+        "(0:0.1,1:0.2)2:0.5".to_string(),
+        "(((0:0.9,2:0.4)4:0.8,3:3.0)5:0.4,1:0.5)6:0.2".to_string(),
+        "(0:0.7,(1:0.5,2:0.8)3:0.6)4:0.9".to_string(),
+    ])]
+    #[case((2..50).map(|n| to_newick_from_matrix(&sample_matrix(n, false))).collect())]
+    fn test_to_matrix_batch(#[case] newicks: Vec<String>) {
+        let batch = to_matrix_batch(&newicks).unwrap();
+        assert_eq!(batch.len(), newicks.len());
+        for (newick, matrix) in newicks.iter().zip(batch.iter()) {
+            assert_eq!(*matrix, to_matrix(newick).unwrap());
+        }
+    }
+
+    #[rstest]
+    fn test_to_matrix_batch_invalid() {
+        let newicks = vec![
+            "(0:0.1,1:0.2)2;".to_string(),
+            "((0:0.1,2:abc)3:0.3,1:0.4)4;".to_string(),
+            "((0:0.1)3:0.3,1:0.4)4;".to_string(),
+        ];
+        match to_matrix_batch(&newicks) {
+            Err(Phylo2VecError::InvalidBatchItem { index, source }) => {
+                assert_eq!(index, 1);
+                assert!(matches!(source, NewickError::ParseFloatError(_)));
+            }
+            other => panic!("Expected an InvalidBatchItem error, got {:?}", other),
+        }
+    }
+
     // Test for the `to_matrix_no_parents` function
     // Verifies correct matrix generation from a Newick string without parent nodes.
     #[rstest]
@@ -146,9 +346,14 @@ mod tests {
         vec![0.0, 0.5, 0.6],
     ])]
     #[case("((0:0.1,2:0.2),(1:0.5,3:0.7));", vec![
-        vec![0.0, 0.0, 0.0],
+        vec![0.0, 0.5, 0.7],
         vec![0.0, 0.1, 0.2],
-        vec![1.0, 0.5, 0.7],
+        vec![1.0, 0.0, 0.0],
+    ])]
+    #[case("((0:0.5,1:0.6):0.7,(2:0.8,3:0.9):1.0);", vec![
+        vec![0.0, 0.8, 0.9],
+        vec![2.0, 0.5, 0.6],
+        vec![2.0, 0.7, 1.0],
     ])]
     fn test_to_matrix_no_parents(
         #[case] newick_no_parents: String,
@@ -160,12 +365,49 @@ mod tests {
         assert_eq!(matrix, expected_matrix);
     }
 
+    // Test that `to_matrix` reports invalid branch lengths and Newick strings
+    #[rstest]
+    #[case("(0:0.1,1:1.2e)2;")]
+    #[case("(0:0.1,1:abc)2;")]
+    #[case("(0:0.1,1:inf)2;")]
+    #[case("((0:0.1,1:0.2)2;")]
+    fn test_to_matrix_invalid(#[case] newick: &str) {
+        assert!(to_matrix(newick).is_err());
+    }
+
+    // Test that `to_matrix` reports invalid node labels instead of panicking
+    #[rstest]
+    #[case("(0,0)2;", NewickError::DuplicateNode(0))]
+    #[case("(0,5)2;", NewickError::ParentBelowChild { parent: 2, child: 5 })]
+    #[case("((0,1)2,3)4;", NewickError::NodeOutOfRange { node: 3, min: 0, max: 2 })]
+    #[case("((0,1)1,2)4;", NewickError::ParentBelowChild { parent: 1, child: 1 })]
+    #[case("((0,1)3,2)3;", NewickError::ParentBelowChild { parent: 3, child: 3 })]
+    fn test_to_matrix_invalid_labels(#[case] newick: &str, #[case] expected: NewickError) {
+        assert_eq!(to_matrix(newick), Err(expected));
+    }
+
+    // Test that branch lengths in exponent notation survive a round trip
+    // (`to_newick_from_matrix` writes branch lengths with one decimal)
+    #[rstest]
+    #[case("((0:5.0e-1,2:3.4E+1)3:2e-1,1:1e0)4;", vec![
+        vec![0.0, 0.5, 34.0],
+        vec![0.0, 0.2, 1.0],
+    ])]
+    fn test_to_matrix_exponent_round_trip(
+        #[case] newick: &str,
+        #[case] expected_matrix: Vec<Vec<f32>>,
+    ) {
+        let matrix = to_matrix(newick).unwrap();
+        assert_eq!(matrix, expected_matrix);
+        assert_eq!(to_matrix(&to_newick_from_matrix(&matrix)), Ok(matrix));
+    }
+
     // Test for an empty Newick string in the `to_matrix` function
     // Ensures that an empty Newick string results in an empty matrix.
     #[rstest]
     #[case("".to_string(), vec![])]
     fn test_empty_newick_to_matrix(#[case] newick: String, #[case] expected_matrix: Vec<Vec<f32>>) {
-        let matrix = to_matrix(&newick);
+        let matrix = to_matrix(&newick).unwrap();
 
         // Empty Newick should result in an empty matrix
         assert_eq!(matrix, expected_matrix);
@@ -184,4 +426,120 @@ mod tests {
         // Empty Newick should result in an empty matrix
         assert_eq!(matrix, expected_matrix);
     }
+
+    // Test for the `to_matrix_with_taxa` function
+    // Verifies that named leaves are mapped to integers in order of appearance.
+    #[rstest]
+    #[case("((a:0.5,b:0.6):0.7,(c:0.8,d:0.9):1.0);", vec![
+        vec![0.0, 0.8, 0.9],
+        vec![2.0, 0.5, 0.6],
+        vec![2.0, 0.7, 1.0],
+    ], vec!["a", "b", "c", "d"], "((0:0.5,1:0.6)5:0.7,(2:0.8,3:0.9)4:1.0)6;")]
+    fn test_to_matrix_with_taxa(
+        #[case] newick: &str,
+        #[case] expected_matrix: Vec<Vec<f32>>,
+        #[case] expected_taxa: Vec<&str>,
+        #[case] expected_newick: &str,
+    ) {
+        let (matrix, taxa) = to_matrix_with_taxa(newick);
+
+        assert_eq!(matrix, expected_matrix);
+        assert_eq!(taxa, expected_taxa);
+
+        // Each branch length must stay attached to its node
+        assert_eq!(to_newick_from_matrix(&matrix), expected_newick);
+    }
+
+    // Test for the `parse_matrix` function
+    // Verifies that float-encoded integers in the first column are rounded.
+    #[rstest]
+    #[case(vec![
+        vec![0e0, 0.1, 0.2],
+        vec![1.9999999, 0.3, 0.4],
+        vec![2.0000001, 0.5, 0.6],
+    ], vec![0, 2, 2])]
+    #[case(vec![
+        vec!["0e0".parse().unwrap(), 0.1, 0.2],
+        vec!["1e0".parse().unwrap(), 0.3, 0.4],
+    ], vec![0, 1])]
+    fn test_parse_matrix(#[case] matrix: Vec<Vec<f32>>, #[case] expected_vector: Vec<usize>) {
+        let (vector, bls) = parse_matrix(&matrix);
+
+        assert_eq!(vector, expected_vector);
+        assert_eq!(bls.len(), matrix.len());
+    }
+
+    // Test for the `matrix_num_leaves` function
+    #[rstest]
+    #[case(vec![
+        vec![0.0, 0.1, 0.2],
+        vec![0.0, 0.3, 0.4],
+        vec![3.0, 0.5, 0.6],
+        vec![1.0, 0.7, 0.8],
+    ], 5)]
+    #[case(vec![vec![0.0, 0.1, 0.2]], 2)]
+    #[case(vec![], 1)]
+    fn test_matrix_num_leaves(#[case] matrix: Vec<Vec<f32>>, #[case] expected: usize) {
+        assert_eq!(matrix_num_leaves(&matrix), Ok(expected));
+    }
+
+    #[rstest]
+    #[case(vec![vec![0.0, 0.1, 0.2], vec![0.0, 0.3]], 1, 2)]
+    #[case(vec![vec![0.0, 0.1, 0.2, 0.3]], 0, 4)]
+    fn test_matrix_num_leaves_ragged(
+        #[case] matrix: Vec<Vec<f32>>,
+        #[case] row: usize,
+        #[case] columns: usize,
+    ) {
+        assert_eq!(
+            matrix_num_leaves(&matrix),
+            Err(Phylo2VecError::InvalidMatrixRow { row, columns })
+        );
+    }
+
+    // Test for the `scale_matrix_branch_lengths` function
+    // Verifies that branch lengths are scaled and that the vector is untouched.
+    #[rstest]
+    #[case(vec![
+        vec![0.0, 0.9, 0.4],
+        vec![0.0, 0.8, 3.0],
+        vec![3.0, 0.4, 0.5],
+    ], 0.5)]
+    #[case(vec![vec![0.0, 0.1, 0.2]], 10.0)]
+    #[case(vec![], 2.0)]
+    fn test_scale_matrix_branch_lengths(#[case] matrix: Vec<Vec<f32>>, #[case] factor: f32) {
+        let mut scaled = matrix.clone();
+        scale_matrix_branch_lengths(&mut scaled, factor);
+
+        assert_eq!(scaled.len(), matrix.len());
+        for (row, scaled_row) in matrix.iter().zip(scaled.iter()) {
+            assert_eq!(scaled_row[0], row[0]);
+            assert_eq!(scaled_row[1], row[1] * factor);
+            assert_eq!(scaled_row[2], row[2] * factor);
+        }
+    }
+
+    // Test that `to_matrix` and `to_matrix_no_parents` are deterministic
+    // Runs the conversion many times and checks that all results are identical.
+    #[rstest]
+    #[case("(((0:0.9,2:0.4)4:0.8,3:3.0)5:0.4,1:0.5)6:0.2;", true)]
+    #[case("((0:0.1,2:0.2),(1:0.5,3:0.7));", false)]
+    #[case(
+        "(((0:0.1,4:0.2):0.3,(2:0.4,5:0.5):0.6):0.7,(1:0.8,3:0.9):1.0);",
+        false
+    )]
+    fn test_to_matrix_deterministic(#[case] newick: &str, #[case] with_parents: bool) {
+        let convert = |newick: &str| {
+            if with_parents {
+                to_matrix(newick).unwrap()
+            } else {
+                to_matrix_no_parents(newick)
+            }
+        };
+
+        let expected = convert(newick);
+        for _ in 0..100 {
+            assert_eq!(convert(newick), expected);
+        }
+    }
 }
@@ -0,0 +1,761 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::tree_vec::ops::newick::{
+    get_cherries_no_parents_with_bls, get_cherries_with_bls, has_parents,
+};
+use crate::tree_vec::ops::subtree::_ancestry_to_vector;
+use crate::tree_vec::ops::vector::{cophenetic_distances, descendant_bitsets, leaf_depths};
+use crate::tree_vec::types::Ancestry;
+
+/// Get the non-trivial bipartitions (splits) of a tree, seen as unrooted
+///
+/// Each split is represented by the bitset of the side that does not
+/// contain leaf 0, so that both clades below the root map to the same split.
+/// Trivial splits (a single leaf versus the rest) are excluded.
+fn _get_splits(v: &[usize]) -> HashSet<Vec<u64>> {
+    let n_leaves = v.len() + 1;
+    let n_blocks = n_leaves.div_ceil(64);
+
+    // Mask of the valid bits in the last block
+    let last_mask = match n_leaves % 64 {
+        0 => u64::MAX,
+        r => (1 << r) - 1,
+    };
+
+    let mut splits = HashSet::with_capacity(n_leaves);
+
+    for mut bitset in descendant_bitsets(v) {
+        if bitset[0] & 1 != 0 {
+            // Take the complement to get the side without leaf 0
+            for block in bitset.iter_mut() {
+                *block = !*block;
+            }
+            bitset[n_blocks - 1] &= last_mask;
+        }
+
+        let size: u32 = bitset.iter().map(|block| block.count_ones()).sum();
+        if size >= 2 && (size as usize) <= n_leaves - 2 {
+            splits.insert(bitset);
+        }
+    }
+
+    splits
+}
+
+/// Compute the Robinson-Foulds distance between two trees
+///
+/// The trees are compared as unrooted trees: the distance is the number of
+/// non-trivial bipartitions found in one tree but not in the other.
+/// It ranges from 0 (same unrooted topology) to 2(n - 3).
+///
+/// Bipartitions are stored as bitsets (see `descendant_bitsets`),
+/// so each comparison costs O(n / 64).
+///
+/// # Panics
+///
+/// Panics if the trees do not have the same number of leaves
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::metrics::robinson_foulds;
+///
+/// // ((0,2),(1,3)) versus (((0,1),2),3)
+/// assert_eq!(robinson_foulds(&vec![0, 0, 1], &vec![0, 1, 0]), 2);
+/// assert_eq!(robinson_foulds(&vec![0, 0, 1], &vec![0, 0, 1]), 0);
+/// ```
+pub fn robinson_foulds(v1: &[usize], v2: &[usize]) -> usize {
+    assert_eq!(
+        v1.len(),
+        v2.len(),
+        "Both trees must have the same number of leaves"
+    );
+
+    let splits1 = _get_splits(v1);
+    let splits2 = _get_splits(v2);
+
+    splits1.symmetric_difference(&splits2).count()
+}
+
+/// Check whether two trees have the same unrooted topology
+///
+/// The trees are compared through their sets of non-trivial bipartitions,
+/// so the result does not depend on the rooting or on the order of the
+/// children of each node. Trees with different numbers of leaves are never
+/// equal. Equivalent to `robinson_foulds(v1, v2) == 0`.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::metrics::topologically_equal;
+/// use phylo2vec::tree_vec::ops::to_vector;
+///
+/// let v1 = to_vector("(((0,1),(2,3)),4);");
+/// let v2 = to_vector("((4,(3,2)),(1,0));");
+/// assert!(topologically_equal(&v1, &v2));
+/// assert!(!topologically_equal(&v1, &to_vector("(((0,2),(1,3)),4);")));
+/// ```
+pub fn topologically_equal(v1: &[usize], v2: &[usize]) -> bool {
+    v1.len() == v2.len() && _get_splits(v1) == _get_splits(v2)
+}
+
+/// Compute the Robinson-Foulds distance as a fraction of its maximum
+///
+/// The distance is divided by its maximum value 2(n - 3) for unrooted trees,
+/// so it ranges from 0.0 (same unrooted topology) to 1.0 (no shared split).
+/// Trees with 3 leaves or fewer have no non-trivial split, so their
+/// normalized distance is always 0.0.
+///
+/// # Panics
+///
+/// Panics if the trees do not have the same number of leaves
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::metrics::robinson_foulds_normalized;
+///
+/// // ((0,2),(1,3)) versus (((0,1),2),3)
+/// assert_eq!(robinson_foulds_normalized(&vec![0, 0, 1], &vec![0, 1, 0]), 1.0);
+/// assert_eq!(robinson_foulds_normalized(&vec![0, 0, 1], &vec![0, 0, 1]), 0.0);
+/// ```
+pub fn robinson_foulds_normalized(v1: &[usize], v2: &[usize]) -> f64 {
+    _normalize_rf(robinson_foulds(v1, v2), v1.len() + 1)
+}
+
+// Divide a Robinson-Foulds distance by its maximum value, 2(n - 3)
+fn _normalize_rf(rf: usize, n_leaves: usize) -> f64 {
+    if n_leaves > 3 {
+        rf as f64 / (2 * (n_leaves - 3)) as f64
+    } else {
+        0.0
+    }
+}
+
+/// Find the tree of a library closest to a query tree by Robinson-Foulds distance
+///
+/// Returns the index of the closest library tree and its distance to the
+/// query (see `robinson_foulds`). Ties are broken by the smallest index.
+/// The scan stops early once a tree with distance 0 is found (with the
+/// `parallel` feature, trees after the first exact match are skipped).
+///
+/// The splits of the query are computed only once, so this is faster than
+/// calling `robinson_foulds` on each library tree.
+///
+/// # Panics
+///
+/// Panics if the library is empty or if a library tree does not have the
+/// same number of leaves as the query
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::metrics::nearest_by_rf;
+///
+/// let library = vec![vec![0, 1, 0, 3], vec![0, 0, 1, 2], vec![0, 1, 2, 3]];
+/// assert_eq!(nearest_by_rf(&[0, 1, 2, 3], &library), (2, 0));
+/// ```
+pub fn nearest_by_rf(query: &[usize], library: &[Vec<usize>]) -> (usize, usize) {
+    assert!(
+        !library.is_empty(),
+        "The library must contain at least one tree"
+    );
+
+    let query_splits = _get_splits(query);
+    let distance = |v: &Vec<usize>| {
+        assert_eq!(
+            v.len(),
+            query.len(),
+            "Library trees must have the same number of leaves as the query"
+        );
+        _get_splits(v).symmetric_difference(&query_splits).count()
+    };
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Index of the first exact match found so far
+        let first_match = AtomicUsize::new(usize::MAX);
+        library
+            .par_iter()
+            .enumerate()
+            .filter_map(|(i, v)| {
+                if i > first_match.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let rf = distance(v);
+                if rf == 0 {
+                    first_match.fetch_min(i, Ordering::Relaxed);
+                }
+                Some((rf, i))
+            })
+            .min()
+            .map(|(rf, i)| (i, rf))
+            .unwrap()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut nearest = (0, usize::MAX);
+        for (i, v) in library.iter().enumerate() {
+            let rf = distance(v);
+            if rf < nearest.1 {
+                nearest = (i, rf);
+            }
+            if rf == 0 {
+                break;
+            }
+        }
+        nearest
+    }
+}
+
+/// Parse a Newick string (with or without parent labels) into an ancestry
+/// and its branch lengths, where internal nodes are numbered by row.
+///
+/// Leaves keep their label and the i-th cherry gets id n_leaves + i, so that
+/// the parent of a row is always found in a later row.
+/// Missing branch lengths are set to 0.
+///
+/// # Panics
+///
+/// Panics if the Newick string cannot be parsed or if its leaves are not 0..n-1
+fn _parse_tree(newick: &str) -> (Ancestry, Vec<[f32; 2]>) {
+    let with_parents = has_parents(newick);
+    let (cherries, bls) = if with_parents {
+        get_cherries_with_bls(newick)
+    } else {
+        get_cherries_no_parents_with_bls(newick)
+    }
+    .expect("Invalid Newick string");
+
+    let n_leaves = cherries.len() + 1;
+
+    // `labels` maps the label of an internal node in the Newick to its id
+    // (without parent labels, a cherry is labelled by its minimum leaf).
+    let mut labels: HashMap<usize, usize> = HashMap::with_capacity(n_leaves);
+    let mut is_leaf_seen = vec![false; n_leaves];
+    let mut ancestry: Ancestry = Vec::with_capacity(cherries.len());
+
+    for (i, &[c1, c2, p]) in cherries.iter().enumerate() {
+        let node = n_leaves + i;
+        let mut children = [0; 2];
+        for (child, label) in children.iter_mut().zip([c1, c2]) {
+            *child = match labels.get(&label) {
+                Some(&id) => id,
+                None => {
+                    assert!(
+                        label < n_leaves && !is_leaf_seen[label],
+                        "Invalid leaf {}: leaves must be 0..{} and appear once",
+                        label,
+                        n_leaves - 1
+                    );
+                    is_leaf_seen[label] = true;
+                    label
+                }
+            };
+        }
+        ancestry.push([children[0], children[1], node]);
+        labels.insert(if with_parents { p } else { c1.min(c2) }, node);
+    }
+
+    (ancestry, bls)
+}
+
+/// Compute Faith's phylogenetic diversity (PD) of a subset of leaves
+///
+/// PD is the total branch length of the minimal subtree connecting the
+/// given leaves. The root is not included unless it lies on a path between
+/// two of the leaves. Missing branch lengths count as 0.
+///
+/// # Panics
+///
+/// Panics if the Newick string cannot be parsed or if a leaf is not in the tree
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::metrics::phylogenetic_diversity;
+///
+/// let newick = "((0:1.0,2:2.0)3:0.5,1:1.5)4;";
+/// assert_eq!(phylogenetic_diversity(newick, &[0, 1, 2]), 5.0);
+/// assert_eq!(phylogenetic_diversity(newick, &[0, 2]), 3.0);
+/// ```
+pub fn phylogenetic_diversity(newick: &str, leaves: &[usize]) -> f32 {
+    let (ancestry, bls) = _parse_tree(newick);
+
+    let n_leaves = ancestry.len() + 1;
+    let n_nodes = 2 * n_leaves - 1;
+
+    // Number of selected leaves below each node
+    let mut counts = vec![0; n_nodes];
+    for &leaf in leaves {
+        assert!(
+            leaf < n_leaves,
+            "Leaf {} is not in the tree (n_leaves = {})",
+            leaf,
+            n_leaves
+        );
+        counts[leaf] = 1;
+    }
+    let n_selected: usize = counts.iter().sum();
+
+    let mut pd = 0.0;
+    for (&[c1, c2, p], &[bl1, bl2]) in ancestry.iter().zip(bls.iter()) {
+        for (child, bl) in [(c1, bl1), (c2, bl2)] {
+            // The edge above `child` separates selected leaves
+            if counts[child] > 0 && counts[child] < n_selected {
+                pd += bl;
+            }
+        }
+        counts[p] = counts[c1] + counts[c2];
+    }
+
+    pd
+}
+
+/// Summary of the differences between two trees (see `compare_trees`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeComparison {
+    /// Robinson-Foulds distance (number of non-shared non-trivial splits)
+    pub rf: usize,
+    /// Robinson-Foulds distance divided by its maximum, 2(n - 3)
+    pub normalized_rf: f64,
+    /// Quartet distance (number of 4-leaf subsets with different topologies)
+    pub quartet: usize,
+    /// Branch score distance (Kuhner & Felsenstein, 1994)
+    pub branch_score: f32,
+}
+
+/// Map each split of a parsed tree, seen as unrooted, to the length of its edge
+///
+/// Splits are encoded as in `_get_splits`, but trivial splits are included.
+/// The two edges below the root form a single split, whose length is their sum.
+fn _get_split_lengths(ancestry: &Ancestry, bls: &[[f32; 2]]) -> HashMap<Vec<u64>, f32> {
+    let n_leaves = ancestry.len() + 1;
+    let n_blocks = n_leaves.div_ceil(64);
+    let last_mask = match n_leaves % 64 {
+        0 => u64::MAX,
+        r => (1 << r) - 1,
+    };
+
+    let mut bitsets: Vec<Vec<u64>> = (0..n_leaves)
+        .map(|leaf| {
+            let mut bitset = vec![0u64; n_blocks];
+            bitset[leaf / 64] |= 1 << (leaf % 64);
+            bitset
+        })
+        .collect();
+    bitsets.resize(2 * n_leaves - 1, Vec::new());
+
+    let mut split_lengths: HashMap<Vec<u64>, f32> = HashMap::with_capacity(2 * n_leaves);
+    for (&[c1, c2, p], &[bl1, bl2]) in ancestry.iter().zip(bls.iter()) {
+        let mut parent_bitset = vec![0u64; n_blocks];
+        for (child, bl) in [(c1, bl1), (c2, bl2)] {
+            let mut split = bitsets[child].clone();
+            for (block, child_block) in parent_bitset.iter_mut().zip(split.iter()) {
+                *block |= child_block;
+            }
+            if split[0] & 1 != 0 {
+                for block in split.iter_mut() {
+                    *block = !*block;
+                }
+                split[n_blocks - 1] &= last_mask;
+            }
+            *split_lengths.entry(split).or_insert(0.0) += bl;
+        }
+        bitsets[p] = parent_bitset;
+    }
+
+    split_lengths
+}
+
+/// Compare two trees with several distances at once
+///
+/// The trees are compared as unrooted trees, and are parsed only once.
+/// Both Newick strings must use the same integer leaf labels 0..n-1.
+///
+/// * `rf` and `normalized_rf`: see `robinson_foulds`
+/// * `quartet`: number of quartets of leaves whose (unrooted) topologies
+///   differ. All O(n^4) quartets are checked, so this is meant for small
+///   to moderate trees.
+/// * `branch_score`: square root of the sum, over all splits (including
+///   trivial ones), of the squared differences between the branch lengths
+///   of the two trees. A split missing from a tree has length 0.
+///
+/// # Panics
+///
+/// Panics if a Newick string cannot be parsed or if the trees do not
+/// have the same leaf set
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::metrics::compare_trees;
+///
+/// let cmp = compare_trees("((0:1.0,1:1.0):1.0,(2:1.0,3:1.0):1.0);", "((0:1.0,2:1.0):1.0,(1:1.0,3:1.0):1.0);");
+/// assert_eq!(cmp.rf, 2);
+/// assert_eq!(cmp.normalized_rf, 1.0);
+/// assert_eq!(cmp.quartet, 1);
+/// assert_eq!(cmp.branch_score, 8.0_f32.sqrt());
+/// ```
+pub fn compare_trees(n1: &str, n2: &str) -> TreeComparison {
+    let (ancestry1, bls1) = _parse_tree(n1);
+    let (ancestry2, bls2) = _parse_tree(n2);
+
+    let n_leaves = ancestry1.len() + 1;
+    assert_eq!(
+        n_leaves,
+        ancestry2.len() + 1,
+        "Both trees must have the same leaves"
+    );
+
+    let split_lengths1 = _get_split_lengths(&ancestry1, &bls1);
+    let split_lengths2 = _get_split_lengths(&ancestry2, &bls2);
+
+    // Robinson-Foulds: non-trivial splits only
+    let is_non_trivial = |split: &&Vec<u64>| {
+        let size: u32 = split.iter().map(|block| block.count_ones()).sum();
+        size >= 2 && (size as usize) <= n_leaves - 2
+    };
+    let rf = split_lengths1
+        .keys()
+        .filter(is_non_trivial)
+        .filter(|split| !split_lengths2.contains_key(*split))
+        .count()
+        + split_lengths2
+            .keys()
+            .filter(is_non_trivial)
+            .filter(|split| !split_lengths1.contains_key(*split))
+            .count();
+    let normalized_rf = _normalize_rf(rf, n_leaves);
+
+    // Branch score: all splits, missing splits have length 0
+    let mut squared_sum = 0.0;
+    for (split, &bl1) in split_lengths1.iter() {
+        let bl2 = split_lengths2.get(split).copied().unwrap_or(0.0);
+        squared_sum += (bl1 - bl2).powi(2);
+    }
+    for (split, &bl2) in split_lengths2.iter() {
+        if !split_lengths1.contains_key(split) {
+            squared_sum += bl2.powi(2);
+        }
+    }
+    let branch_score = squared_sum.sqrt();
+
+    // Quartets: compare the topologies given by the four-point condition
+    let dist1 = cophenetic_distances(&_ancestry_to_vector(&ancestry1), true);
+    let dist2 = cophenetic_distances(&_ancestry_to_vector(&ancestry2), true);
+    let mut quartet = 0;
+    for a in 0..n_leaves {
+        for b in a + 1..n_leaves {
+            for c in b + 1..n_leaves {
+                for d in c + 1..n_leaves {
+                    if _quartet_topology(&dist1, a, b, c, d)
+                        != _quartet_topology(&dist2, a, b, c, d)
+                    {
+                        quartet += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    TreeComparison {
+        rf,
+        normalized_rf,
+        quartet,
+        branch_score,
+    }
+}
+
+// Get the topology of the quartet {a, b, c, d} in a tree given its (unrooted)
+// path lengths: 0 for ab|cd, 1 for ac|bd and 2 for ad|bc.
+// In a binary tree, the pairing with the smallest sum of distances is unique.
+fn _quartet_topology(dist: &[Vec<usize>], a: usize, b: usize, c: usize, d: usize) -> usize {
+    let sums = [
+        dist[a][b] + dist[c][d],
+        dist[a][c] + dist[b][d],
+        dist[a][d] + dist[b][c],
+    ];
+    (0..3).min_by_key(|&i| sums[i]).unwrap()
+}
+
+/// Compute the variance of the leaf depths of a tree
+///
+/// A simple and fast imbalance measure: it is 0 for a perfectly balanced
+/// tree and grows as the tree gets closer to a caterpillar.
+/// The population variance (normalized by n) is returned.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::metrics::leaf_depth_variance;
+///
+/// // ((0,2),(1,3)): all leaves at depth 2
+/// assert_eq!(leaf_depth_variance(&vec![0, 0, 1]), 0.0);
+/// // (0,(1,(2,3))): depths 1, 2, 3, 3
+/// assert_eq!(leaf_depth_variance(&vec![0, 1, 2]), 0.6875);
+/// ```
+pub fn leaf_depth_variance(v: &[usize]) -> f64 {
+    let depths = leaf_depths(v);
+    let n_leaves = depths.len() as f64;
+
+    let mean = depths.iter().sum::<usize>() as f64 / n_leaves;
+    depths
+        .iter()
+        .map(|&depth| (depth as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n_leaves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_vec::ops::{get_ancestry, to_newick_from_vector, to_vector};
+    use crate::utils::sample_vector;
+    use rstest::*;
+    use std::collections::BTreeSet;
+
+    /// Unrooted splits computed with sorted leaf vectors
+    fn splits_vec(v: &[usize]) -> HashSet<Vec<usize>> {
+        let n_leaves = v.len() + 1;
+        let mut descendants: Vec<BTreeSet<usize>> = Vec::new();
+        for [c1, c2, _] in get_ancestry(v) {
+            let mut leaves = BTreeSet::new();
+            for child in [c1, c2] {
+                if child < n_leaves {
+                    leaves.insert(child);
+                } else {
+                    leaves.extend(&descendants[child - n_leaves]);
+                }
+            }
+            descendants.push(leaves);
+        }
+        descendants
+            .into_iter()
+            .map(|leaves| {
+                if leaves.contains(&0) {
+                    (0..n_leaves).filter(|j| !leaves.contains(j)).collect()
+                } else {
+                    leaves.into_iter().collect::<Vec<usize>>()
+                }
+            })
+            .filter(|split| split.len() >= 2 && split.len() <= n_leaves - 2)
+            .collect()
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], vec![0, 0, 1], 0)]
+    #[case(vec![0, 0, 1], vec![0, 1, 0], 2)]
+    #[case(vec![0, 1, 2], vec![0, 0, 0], 2)]
+    #[case(vec![0, 1, 2], vec![0, 2, 2], 0)] // Same unrooted topology, different root
+    #[case(vec![0], vec![0], 0)]
+    #[case(vec![0, 1], vec![0, 0], 0)]
+    #[case(vec![0, 1, 2, 3, 4], vec![0, 0, 1, 3, 2], 6)]
+    fn test_robinson_foulds(
+        #[case] v1: Vec<usize>,
+        #[case] v2: Vec<usize>,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(robinson_foulds(&v1, &v2), expected);
+        assert_eq!(robinson_foulds(&v2, &v1), expected);
+    }
+
+    #[rstest]
+    #[case("(((0,1),(2,3)),4);", "((4,(3,2)),(1,0));", true)]
+    #[case("(((0,1),(2,3)),4);", "((0,1),((2,3),4));", true)]
+    #[case("(((0,1),(2,3)),4);", "(((0,2),(1,3)),4);", false)]
+    #[case("((0,1),2);", "((0,2),1);", true)]
+    #[case("((0,1),2);", "(((0,1),2),3);", false)]
+    fn test_topologically_equal(#[case] n1: &str, #[case] n2: &str, #[case] expected: bool) {
+        let (v1, v2) = (to_vector(n1), to_vector(n2));
+        assert_eq!(topologically_equal(&v1, &v2), expected);
+        assert_eq!(topologically_equal(&v2, &v1), expected);
+    }
+
+    #[rstest]
+    #[case(10)]
+    #[case(100)]
+    fn test_topologically_equal_sampled(#[case] n_leaves: usize) {
+        for _ in 0..10 {
+            let v1 = sample_vector(n_leaves, false);
+            let v2 = sample_vector(n_leaves, false);
+            assert!(topologically_equal(&v1, &v1));
+            assert_eq!(
+                topologically_equal(&v1, &v2),
+                robinson_foulds(&v1, &v2) == 0
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(10, 0)]
+    #[case(10, 7)]
+    #[case(100, 19)]
+    fn test_nearest_by_rf_exact_match(#[case] n_leaves: usize, #[case] index: usize) {
+        let library: Vec<Vec<usize>> = (0..20).map(|_| sample_vector(n_leaves, false)).collect();
+        let query = library[index].clone();
+
+        let (nearest, rf) = nearest_by_rf(&query, &library);
+        assert_eq!(rf, 0);
+        // Sampled trees may share an unrooted topology with the query
+        assert!(nearest <= index);
+        assert_eq!(robinson_foulds(&query, &library[nearest]), 0);
+    }
+
+    #[rstest]
+    fn test_nearest_by_rf_sampled() {
+        let query = sample_vector(50, false);
+        let library: Vec<Vec<usize>> = (0..20).map(|_| sample_vector(50, false)).collect();
+
+        let distances: Vec<usize> = library.iter().map(|v| robinson_foulds(&query, v)).collect();
+        let min = *distances.iter().min().unwrap();
+        let expected = distances.iter().position(|&d| d == min).unwrap();
+        assert_eq!(nearest_by_rf(&query, &library), (expected, min));
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_nearest_by_rf_empty_library() {
+        nearest_by_rf(&[0, 1, 2], &[]);
+    }
+
+    #[rstest]
+    #[case(10)]
+    #[case(65)]
+    #[case(200)]
+    fn test_robinson_foulds_against_vec_splits(#[case] n_leaves: usize) {
+        for _ in 0..10 {
+            let v1 = sample_vector(n_leaves, false);
+            let v2 = sample_vector(n_leaves, false);
+
+            // Bitset-based splits decode to the Vec-based splits
+            let decoded: HashSet<Vec<usize>> = _get_splits(&v1)
+                .iter()
+                .map(|bitset| {
+                    (0..n_leaves)
+                        .filter(|&j| bitset[j / 64] & (1 << (j % 64)) != 0)
+                        .collect()
+                })
+                .collect();
+            assert_eq!(decoded, splits_vec(&v1));
+
+            let expected = splits_vec(&v1)
+                .symmetric_difference(&splits_vec(&v2))
+                .count();
+            let rf = robinson_foulds(&v1, &v2);
+            assert_eq!(rf, expected);
+            assert!(rf <= 2 * (n_leaves - 3));
+            assert_eq!(robinson_foulds(&v1, &v1), 0);
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], vec![0, 0, 1], 0.0)]
+    #[case(vec![0, 0, 1], vec![0, 1, 0], 1.0)]
+    #[case(vec![0, 1, 2, 3, 4], vec![0, 0, 1, 3, 2], 1.0)]
+    #[case(vec![0, 1, 2, 3], to_vector("(0,(1,(3,(2,4))));"), 0.5)]
+    #[case(vec![0], vec![0], 0.0)]
+    #[case(vec![0, 1], vec![0, 0], 0.0)]
+    fn test_robinson_foulds_normalized(
+        #[case] v1: Vec<usize>,
+        #[case] v2: Vec<usize>,
+        #[case] expected: f64,
+    ) {
+        assert_eq!(robinson_foulds_normalized(&v1, &v2), expected);
+        assert_eq!(robinson_foulds_normalized(&v2, &v1), expected);
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_robinson_foulds_mismatched_leaves() {
+        robinson_foulds(&[0, 0, 1], &[0, 0]);
+    }
+
+    #[rstest]
+    #[case("((0:1.0,2:2.0)3:0.5,1:1.5)4;", vec![0, 1, 2], 5.0)]
+    #[case("((0:1.0,2:2.0)3:0.5,1:1.5)4;", vec![0, 2], 3.0)]
+    #[case("((0:1.0,2:2.0)3:0.5,1:1.5)4;", vec![0, 1], 3.0)]
+    #[case("((0:1.0,2:2.0)3:0.5,1:1.5)4;", vec![2], 0.0)]
+    #[case("((0:1.0,2:2.0)3:0.5,1:1.5)4;", vec![], 0.0)]
+    #[case("((0:0.5,2:0.25):1.0,(1:2.0,3:4.0):0.125);", vec![0, 1, 2, 3], 7.875)]
+    #[case("((0:0.5,2:0.25):1.0,(1:2.0,3:4.0):0.125);", vec![2, 3], 5.375)]
+    #[case("((0:0.5,2:0.25):1.0,(1:2.0,3:4.0):0.125);", vec![1, 3, 3], 6.0)]
+    fn test_phylogenetic_diversity(
+        #[case] newick: &str,
+        #[case] leaves: Vec<usize>,
+        #[case] expected: f32,
+    ) {
+        assert_eq!(phylogenetic_diversity(newick, &leaves), expected);
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_phylogenetic_diversity_invalid_leaf() {
+        phylogenetic_diversity("((0:1.0,2:2.0)3:0.5,1:1.5)4;", &[0, 3]);
+    }
+
+    /// Test that a caterpillar is more imbalanced than a balanced tree
+    #[rstest]
+    #[case(8, "(((0,1),(2,3)),((4,5),(6,7)));")]
+    #[case(
+        16,
+        "((((0,1),(2,3)),((4,5),(6,7))),(((8,9),(10,11)),((12,13),(14,15))));"
+    )]
+    fn test_leaf_depth_variance(#[case] n_leaves: usize, #[case] balanced_newick: &str) {
+        let caterpillar: Vec<usize> = (0..n_leaves - 1).collect();
+        let balanced = to_vector(balanced_newick);
+
+        assert_eq!(leaf_depth_variance(&balanced), 0.0);
+        assert!(leaf_depth_variance(&caterpillar) > leaf_depth_variance(&balanced));
+    }
+
+    #[rstest]
+    #[case(
+        "(((0:1.0,1:1.0):1.0,2:1.0):1.0,(3:1.0,4:1.0):1.0);",
+        "(((0:1.0,2:1.0):1.0,1:1.0):1.0,(3:1.0,4:1.0):1.0);",
+        TreeComparison { rf: 2, normalized_rf: 0.5, quartet: 2, branch_score: 2.0_f32.sqrt() }
+    )]
+    #[case(
+        "((0:1.0,1:1.0)4:1.0,(2:1.0,3:1.0)5:1.0)6;",
+        "((0:1.0,2:1.0)4:1.0,(1:1.0,3:1.0)5:1.0)6;",
+        TreeComparison { rf: 2, normalized_rf: 1.0, quartet: 1, branch_score: 8.0_f32.sqrt() }
+    )]
+    #[case(
+        "((0:1.0,1:2.0):0.5,(2:1.0,3:1.0):1.5);",
+        "(((0:1.0,1:1.0):1.0,2:1.0):1.0,3:1.0);",
+        TreeComparison { rf: 0, normalized_rf: 0.0, quartet: 0, branch_score: 3.0_f32.sqrt() }
+    )]
+    #[case(
+        "((0,1),2);",
+        "((0,2),1);",
+        TreeComparison { rf: 0, normalized_rf: 0.0, quartet: 0, branch_score: 0.0 }
+    )]
+    fn test_compare_trees(#[case] n1: &str, #[case] n2: &str, #[case] expected: TreeComparison) {
+        assert_eq!(compare_trees(n1, n2), expected);
+        assert_eq!(compare_trees(n2, n1), expected);
+    }
+
+    /// Test the distances of a tree to itself and the RF distance against `robinson_foulds`
+    #[rstest]
+    #[case(10)]
+    #[case(30)]
+    fn test_compare_trees_sampled(#[case] n_leaves: usize) {
+        for _ in 0..5 {
+            let v1 = sample_vector(n_leaves, false);
+            let v2 = sample_vector(n_leaves, false);
+            let (n1, n2) = (to_newick_from_vector(&v1), to_newick_from_vector(&v2));
+
+            let cmp_self = compare_trees(&n1, &n1);
+            assert_eq!((cmp_self.rf, cmp_self.quartet), (0, 0));
+
+            let cmp = compare_trees(&n1, &n2);
+            assert_eq!(cmp.rf, robinson_foulds(&v1, &v2));
+            assert_eq!(cmp.quartet == 0, cmp.rf == 0);
+        }
+    }
+
+    #[rstest]
+    #[case("((0,1),2);", "((0,1),(2,3));")]
+    #[case("((0,1),2);", "((0,1),3);")]
+    #[should_panic]
+    fn test_compare_trees_different_leaves(#[case] n1: &str, #[case] n2: &str) {
+        compare_trees(n1, n2);
+    }
+}
@@ -1,26 +1,355 @@
 pub mod avl;
+pub mod bytes;
+pub mod diff;
+pub mod edges;
+pub mod lca;
+#[cfg(feature = "matrix")]
 pub mod matrix;
+pub mod metrics;
 pub mod newick;
+pub mod nexml;
+pub mod nexus;
+pub mod stats;
+pub mod subtree;
 pub mod vector;
 
-use crate::{tree_vec::types::Ancestry, utils::check_m};
+use crate::errors::Phylo2VecError;
+use crate::tree_vec::types::{Ancestry, LeafIndex};
+#[cfg(feature = "matrix")]
+use crate::utils::check_m;
+#[cfg(feature = "matrix")]
 use matrix::parse_matrix;
+#[cfg(feature = "matrix")]
 use newick::build_newick_with_bls;
 
 pub use vector::{
-    build_vector, cophenetic_distances, find_coords_of_first_leaf, get_ancestry, get_pairs,
-    get_pairs_avl, order_cherries, order_cherries_no_parents,
+    build_vector, cophenetic_after_nni, cophenetic_distances, cophenetic_distances_batch,
+    count_cherries, descendant_bitsets, find_coords_of_first_leaf, get_ancestry, get_pairs,
+    get_pairs_avl, is_ancestor, is_monophyletic, ladderize, leaf_depths, mrca, mrca_depth_matrix,
+    nni, nni_neighbors, order_cherries, order_cherries_no_parents, parent_of, path_length,
+    postorder_sequence, rotate_node, to_vector_from_cherries, unrooted_canonical, visit_postorder,
+    NodeVisit,
 };
 
 pub use newick::{build_newick, get_cherries, get_cherries_no_parents, has_parents};
 
+pub use edges::{from_edges, to_edges};
+pub use metrics::topologically_equal;
+pub use subtree::graft;
+
+use newick::{
+    get_cherries_no_parents_with_bls, relabel_with_table, NewickError, NewickParseOptions,
+    ParseWarning, TaxonTable,
+};
+
+/// Recover a rooted tree (in Newick format) from a Phylo2Vec vector
+///
+/// The vector can use any `LeafIndex` element type, e.g. `u32`.
+pub fn to_newick_from_vector<T: LeafIndex>(v: &[T]) -> String {
+    let ancestry: Ancestry = get_ancestry(v);
+    build_newick(&ancestry)
+}
+
+/// Recover rooted trees (in Newick format) from a batch of Phylo2Vec vectors
+///
+/// With the `parallel` feature enabled, the trees are processed in parallel
+/// using rayon. The trees may have different numbers of leaves.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_newick_batch;
+///
+/// let newicks = to_newick_batch(&[vec![0], vec![0, 0, 1]]);
+/// assert_eq!(newicks, vec!["(0,1)2;", "((0,2)5,(1,3)4)6;"]);
+/// ```
+pub fn to_newick_batch(vectors: &[Vec<usize>]) -> Vec<String> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        vectors
+            .par_iter()
+            .map(|v| to_newick_from_vector(v))
+            .collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        vectors.iter().map(|v| to_newick_from_vector(v)).collect()
+    }
+}
+
+/// Recover a rooted tree (in Newick format) from a Phylo2Vec vector,
+/// with 0-based or 1-based node labels
+///
+/// With `one_based`, all node labels are shifted by one: leaves are labelled
+/// 1..n and internal nodes n+1..2n-1, as in R's `ape`. Otherwise, this is
+/// the same as `to_newick_from_vector`.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_newick_from_vector_with_base;
+///
+/// let v = vec![0, 0, 1];
+/// assert_eq!(to_newick_from_vector_with_base(&v, false), "((0,2)5,(1,3)4)6;");
+/// assert_eq!(to_newick_from_vector_with_base(&v, true), "((1,3)6,(2,4)5)7;");
+/// ```
+pub fn to_newick_from_vector_with_base(v: &[usize], one_based: bool) -> String {
+    let newick = build_newick(&get_ancestry(v));
+    if one_based {
+        newick::shift_labels(&newick, 1)
+    } else {
+        newick
+    }
+}
+
+/// Recover a rooted tree (in Newick format) from a Phylo2Vec vector,
+/// without internal node labels
+///
+/// Equivalent to `remove_parent_labels(&to_newick_from_vector(v))`, but the
+/// label-free string is written directly while traversing the tree.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_newick_no_labels;
+///
+/// let v = vec![0, 0, 0, 1, 3];
+/// assert_eq!(to_newick_no_labels(&v), "(((0,(3,5)),2),(1,4));");
+/// ```
+pub fn to_newick_no_labels(v: &[usize]) -> String {
+    newick::_write_newick(&get_ancestry(v), newick::_write_integer, |_, _| {})
+}
+
+/// Recover an unrooted tree (in Newick format) from a Phylo2Vec vector
+///
+/// The root is collapsed into a trifurcation: the two children of an
+/// internal child of the root become children of the top-level node, which
+/// is the usual Newick convention for unrooted trees. Only leaves are
+/// labelled. Trees with fewer than three leaves are returned as rooted.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_newick_unrooted;
+///
+/// // Rooted: (((0,(3,5)),2),(1,4));
+/// assert_eq!(to_newick_unrooted(&[0, 0, 0, 1, 3]), "((0,(3,5)),2,(1,4));");
+/// // Rooted: (0,(1,2));
+/// assert_eq!(to_newick_unrooted(&[0, 1]), "(0,1,2);");
+/// ```
+pub fn to_newick_unrooted(v: &[usize]) -> String {
+    let n_leaves = v.len() + 1;
+    let ancestry = get_ancestry(v);
+    let newick = newick::_write_newick(&ancestry, newick::_write_integer, |_, _| {});
+    if n_leaves < 3 {
+        return newick;
+    }
+
+    // Remove the parentheses around an internal child of the root
+    let [c1, _, _] = ancestry[n_leaves - 2];
+    let (open, close) = if c1 >= n_leaves {
+        // "((...)c1,c2);": find the parenthesis closing the first child
+        let mut depth = 0;
+        let close = newick::_tokenize(&newick)
+            .skip(1)
+            .find(|token| {
+                match token.kind {
+                    newick::TokenKind::Open => depth += 1,
+                    newick::TokenKind::Close => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })
+            .unwrap()
+            .start;
+        (1, close)
+    } else {
+        // "(c1,(...));": the second child spans the rest of the string
+        (newick.find(',').unwrap() + 1, newick.len() - 3)
+    };
+
+    let mut unrooted = String::with_capacity(newick.len() - 2);
+    unrooted.push_str(&newick[..open]);
+    unrooted.push_str(&newick[open + 1..close]);
+    unrooted.push_str(&newick[close + 1..]);
+    unrooted
+}
+
+/// Recover a rooted tree (in Newick format) from a Phylo2Vec vector,
+/// with support values as internal node labels
+///
+/// `support[k]` is the label of internal node `n + k`, i.e. the parent of
+/// row `k` of the ancestry (see `get_ancestry`), so the last value is the
+/// support of the root.
+///
+/// # Panics
+///
+/// Panics if `support` does not have one value per internal node
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_newick_with_support;
+///
+/// let v = vec![0, 0, 1];
+/// let support = vec![0.5, 0.9, 1.0];
+/// assert_eq!(to_newick_with_support(&v, &support), "((0,2)0.9,(1,3)0.5)1;");
+/// ```
+pub fn to_newick_with_support(v: &[usize], support: &[f32]) -> String {
+    assert_eq!(
+        support.len(),
+        v.len(),
+        "Expected one support value per internal node"
+    );
+    let n_leaves = v.len() + 1;
+    newick::_write_newick(&get_ancestry(v), newick::_write_integer, |newick, node| {
+        newick.push_str(&support[node - n_leaves].to_string())
+    })
+}
+
+/// Recover a rooted tree (in Newick format) from a Phylo2Vec vector,
+/// with taxon names as leaf labels
+///
+/// Leaf `i` is labelled `taxa[i]`, quoted if needed (see `quote_label`).
+/// Internal nodes are not labelled.
+///
+/// # Panics
+///
+/// Panics if `taxa` does not have one name per leaf
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_newick_with_taxa;
+///
+/// let v = vec![0, 0, 1];
+/// let taxa = ["a", "b", "Homo sapiens", "O'Brien"].map(String::from);
+/// assert_eq!(
+///     to_newick_with_taxa(&v, &taxa),
+///     "((a,'Homo sapiens'),(b,'O''Brien'));"
+/// );
+/// ```
+pub fn to_newick_with_taxa(v: &[usize], taxa: &[String]) -> String {
+    assert_eq!(
+        taxa.len(),
+        v.len() + 1,
+        "Expected one taxon per leaf: got {} taxa for {} leaves",
+        taxa.len(),
+        v.len() + 1
+    );
+    _to_newick_with_taxa(&get_ancestry(v), &[], taxa, |_, _| {})
+}
+
+// Write a tree with leaves labelled by `taxa` and unlabelled internal nodes,
+// calling `write_bl` after each node. Edges above the internal nodes of
+// `contracted` are not written (see `newick::_write_newick_contracted`).
+pub(crate) fn _to_newick_with_taxa(
+    ancestry: &Ancestry,
+    contracted: &[usize],
+    taxa: &[String],
+    write_bl: impl Fn(&mut String, usize),
+) -> String {
+    newick::_write_newick_contracted(
+        ancestry,
+        contracted,
+        |newick, leaf| {
+            newick.push_str(&newick::quote_label(&taxa[leaf]));
+            write_bl(newick, leaf);
+        },
+        &write_bl,
+    )
+}
+
 /// Recover a rooted tree (in Newick format) from a Phylo2Vec vector
-pub fn to_newick_from_vector(v: &Vec<usize>) -> String {
-    let ancestry: Ancestry = get_ancestry(&v);
+/// and its branch lengths, written with a fixed number of decimals
+///
+/// `bls[k]` holds the branch lengths of the two children of ancestry row `k`
+/// (see `get_ancestry`). Every branch length is written with exactly
+/// `precision` decimal places, so the output is stable across runs.
+/// Nodes keep their integer labels, so the output can be parsed back
+/// with `get_cherries_with_bls`.
+///
+/// # Panics
+///
+/// Panics if `bls` does not have one pair of branch lengths per internal node
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_newick_with_bls;
+///
+/// let newick = to_newick_with_bls(&[0], &[(0.5, 1.0 / 3.0)], 5);
+/// assert_eq!(newick, "(0:0.50000,1:0.33333)2;");
+/// ```
+pub fn to_newick_with_bls(v: &[usize], bls: &[(f64, f64)], precision: usize) -> String {
+    assert_eq!(
+        bls.len(),
+        v.len(),
+        "Expected one pair of branch lengths per internal node: got {} pairs for {} nodes",
+        bls.len(),
+        v.len()
+    );
+
+    let ancestry = get_ancestry(v);
+    let write_bl = newick::_branch_length_writer(&ancestry, bls, Some(precision));
+    let write_node = |newick: &mut String, node: usize| {
+        newick::_write_integer(newick, node);
+        write_bl(newick, node);
+    };
+
+    newick::_write_newick(&ancestry, write_node, write_node)
+}
+
+/// Recover a rooted tree (in Newick format) from an ordered Phylo2Vec vector
+///
+/// Fast path for vectors satisfying v[i] <= i. In that case, each leaf i + 1
+/// is simply paired with v[i], so the ancestry can be built in a single pass
+/// without the general (AVL-based) pair construction.
+///
+/// # Panics
+///
+/// In debug builds, panics if the vector is not ordered
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::{to_newick_from_vector, to_newick_ordered};
+///
+/// let v = vec![0, 1, 2, 3, 4];
+/// assert_eq!(to_newick_ordered(&v), to_newick_from_vector(&v));
+/// ```
+pub fn to_newick_ordered(v: &[usize]) -> String {
+    debug_assert!(
+        v.iter().enumerate().all(|(i, &vi)| vi <= i),
+        "to_newick_ordered requires an ordered vector (v[i] <= i)"
+    );
+
+    let k = v.len();
+    let mut ancestry: Ancestry = Vec::with_capacity(k);
+    // Keep track of child->highest parent relationship
+    let mut parents: Vec<usize> = vec![usize::MAX; 2 * k + 1];
+
+    // Pairs are processed in the same order as `get_pairs` (reverse iteration)
+    for (row, i) in (0..k).rev().enumerate() {
+        let (c1, c2) = (v[i], i + 1);
+
+        let parent_of_child1 = if parents[c1] != usize::MAX {
+            parents[c1]
+        } else {
+            c1
+        };
+        let parent_of_child2 = if parents[c2] != usize::MAX {
+            parents[c2]
+        } else {
+            c2
+        };
+
+        let next_parent = k + row + 1;
+        ancestry.push([parent_of_child1, parent_of_child2, next_parent]);
+
+        parents[c1] = next_parent;
+        parents[c2] = next_parent;
+    }
+
     build_newick(&ancestry)
 }
 
 /// Recover a rooted tree (in Newick format) from a Phylo2Vec matrix
+#[cfg(feature = "matrix")]
 pub fn to_newick_from_matrix(m: &Vec<Vec<f32>>) -> String {
     // First, check the matrix structure for validity
     check_m(m);
@@ -31,18 +360,281 @@ pub fn to_newick_from_matrix(m: &Vec<Vec<f32>>) -> String {
 }
 
 /// Recover a Phylo2Vec vector from a rooted tree (in Newick format)
+///
+/// # Panics
+///
+/// Panics if the Newick string cannot be parsed (see `try_to_vector`)
 pub fn to_vector(newick: &str) -> Vec<usize> {
-    let mut ancestry: Ancestry;
+    try_to_vector(newick).expect("Invalid Newick string")
+}
+
+/// Recover a Phylo2Vec vector from a rooted tree (in Newick format),
+/// returning an error if the Newick string cannot be parsed
+///
+/// Use this instead of `to_vector` for user-supplied trees.
+///
+/// # Errors
+///
+/// Returns a `NewickError` if the Newick string does not end with a
+/// semicolon, if parentheses are unbalanced, or if node labels are missing,
+/// out of range or duplicated
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::NewickError;
+/// use phylo2vec::tree_vec::ops::try_to_vector;
+///
+/// assert_eq!(try_to_vector("((0,2),(1,3));"), Ok(vec![0, 0, 1]));
+/// assert_eq!(try_to_vector("((0,2),(1,3);"), Err(NewickError::UnbalancedParentheses));
+/// ```
+pub fn try_to_vector(newick: &str) -> Result<Vec<usize>, NewickError> {
+    _try_to_vector_as(newick)
+}
+
+// Helper function that parses a Newick string into a vector of any element type
+fn _try_to_vector_as<T: LeafIndex>(newick: &str) -> Result<Vec<T>, NewickError> {
+    // The cherry parsers expect the trailing semicolon
+    if !newick.is_empty() && !newick.ends_with(';') {
+        return Err(NewickError::MissingSemicolon);
+    }
+
+    if has_parents(newick) {
+        Ok(to_vector_from_cherries(
+            newick::try_get_cherries(newick)?,
+            true,
+        ))
+    } else {
+        Ok(to_vector_from_cherries(
+            newick::try_get_cherries_no_parents(newick)?,
+            false,
+        ))
+    }
+}
+
+/// Recover Phylo2Vec vectors from a batch of rooted trees (in Newick format)
+///
+/// With the `parallel` feature enabled, the trees are processed in parallel
+/// using rayon. The trees may have different numbers of leaves.
+///
+/// # Errors
+///
+/// Returns `Phylo2VecError::InvalidBatchItem` with the index of the first
+/// Newick string that cannot be parsed (see `try_to_vector`)
+///
+/// # Example
+/// ```
+/// use phylo2vec::errors::Phylo2VecError;
+/// use phylo2vec::tree_vec::ops::to_vector_batch;
+///
+/// let vectors = to_vector_batch(&["(0,1)2;", "((0,2)5,(1,3)4)6;"]).unwrap();
+/// assert_eq!(vectors, vec![vec![0], vec![0, 0, 1]]);
+///
+/// let result = to_vector_batch(&["(0,1)2;", "((0,2)5,(1,3)4)6"]);
+/// assert!(matches!(result, Err(Phylo2VecError::InvalidBatchItem { index: 1, .. })));
+/// ```
+pub fn to_vector_batch<S: AsRef<str> + Sync>(
+    newicks: &[S],
+) -> Result<Vec<Vec<usize>>, Phylo2VecError> {
+    #[cfg(feature = "parallel")]
+    let results: Vec<Result<Vec<usize>, NewickError>> = {
+        use rayon::prelude::*;
+        newicks
+            .par_iter()
+            .map(|newick| try_to_vector(newick.as_ref()))
+            .collect()
+    };
+
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<Result<Vec<usize>, NewickError>> = newicks
+        .iter()
+        .map(|newick| try_to_vector(newick.as_ref()))
+        .collect();
+
+    // Report the first invalid input, regardless of the processing order
+    results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| {
+            result.map_err(|source| Phylo2VecError::InvalidBatchItem { index, source })
+        })
+        .collect()
+}
+
+/// Recover a Phylo2Vec vector with elements of type `T` from a rooted tree
+/// (in Newick format)
+///
+/// Use this to store vectors with a smaller element type such as `u32`.
+/// The elements are written as `T` directly, without an intermediate
+/// `Vec<usize>`.
+///
+/// # Panics
+///
+/// Panics if the Newick string cannot be parsed (see `try_to_vector`),
+/// or if an element of the vector does not fit in `T`
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_vector_as;
+///
+/// let v: Vec<u32> = to_vector_as("((0,2)5,(1,3)4)6;");
+/// assert_eq!(v, vec![0, 0, 1]);
+/// ```
+pub fn to_vector_as<T: LeafIndex>(newick: &str) -> Vec<T> {
+    _try_to_vector_as(newick).expect("Invalid Newick string")
+}
 
-    if has_parents(&newick) {
-        ancestry = get_cherries(newick);
-        order_cherries(&mut ancestry);
+/// Recover a Phylo2Vec vector from a rooted tree (in Newick format)
+/// with 0-based or 1-based node labels
+///
+/// With `one_based`, all node labels are shifted down by one before
+/// conversion, so leaves must be labelled 1..n. The output vector always
+/// uses 0-based leaves. Otherwise, this is the same as `to_vector`.
+///
+/// # Panics
+///
+/// Panics if `one_based` is true and a node is labelled 0
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::{to_vector, to_vector_with_base};
+///
+/// assert_eq!(to_vector_with_base("((1,2),3);", true), to_vector("((0,1),2);"));
+/// ```
+pub fn to_vector_with_base(newick: &str, one_based: bool) -> Vec<usize> {
+    if one_based {
+        to_vector(&newick::shift_labels(newick, -1))
     } else {
-        ancestry = get_cherries_no_parents(newick);
-        order_cherries_no_parents(&mut ancestry);
+        to_vector(newick)
+    }
+}
+
+/// Recover a Phylo2Vec vector from a rooted tree (in Newick format),
+/// reporting the transformations applied to parse it
+///
+/// The Newick string is first normalized according to `options` (see
+/// `newick::normalize_newick`), e.g. by stripping comments and whitespace.
+/// Branch lengths, if any, are ignored. Polytomies are not supported and
+/// are reported as errors.
+///
+/// # Returns
+///
+/// The vector and the list of non-fatal warnings, one per transformation
+/// that changed the Newick string
+///
+/// # Errors
+///
+/// Returns a `NewickError` if the normalized Newick string cannot be parsed,
+/// including if it does not end with a semicolon
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::{NewickParseOptions, ParseWarning};
+/// use phylo2vec::tree_vec::ops::to_vector_diagnostic;
+///
+/// let options = NewickParseOptions::default();
+/// let (v, warnings) = to_vector_diagnostic("((0,2), (1,3))", &options).unwrap();
+/// assert_eq!(v, vec![0, 0, 1]);
+/// assert_eq!(
+///     warnings,
+///     vec![ParseWarning::StrippedWhitespace { count: 1 }, ParseWarning::AddedSemicolon]
+/// );
+/// ```
+pub fn to_vector_diagnostic(
+    newick: &str,
+    options: &NewickParseOptions,
+) -> Result<(Vec<usize>, Vec<ParseWarning>), NewickError> {
+    let (newick, warnings) = newick::normalize_newick(newick, options)?;
+    // The cherry parsers expect the trailing semicolon
+    if !newick.ends_with(';') {
+        return Err(NewickError::MissingSemicolon);
+    }
+
+    // Comments are only removed if `options` says so, so parse the string as is
+    let with_parents = has_parents(&newick);
+    let (ancestry, _) = newick::_get_cherries_with_bls_inner(&newick, with_parents)?;
+
+    Ok((to_vector_from_cherries(ancestry, with_parents), warnings))
+}
+
+/// Recover a Phylo2Vec vector and support values from a rooted tree
+/// (in Newick format) with support values as internal node labels
+///
+/// This is the inverse of `to_newick_with_support`: `support[k]` is the
+/// label of internal node `n + k` of the output vector. Internal nodes
+/// without a label get a support of `NaN`. Branch lengths are ignored.
+///
+/// # Errors
+///
+/// Returns a `NewickError` if the Newick string cannot be parsed
+/// or if a support value is not a number
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_vector_with_support;
+///
+/// let (v, support) = to_vector_with_support("((0,2)0.9,(1,3)0.5)1;").unwrap();
+/// assert_eq!(v, vec![0, 0, 1]);
+/// assert_eq!(support, vec![0.5, 0.9, 1.0]);
+/// ```
+pub fn to_vector_with_support(newick: &str) -> Result<(Vec<usize>, Vec<f32>), NewickError> {
+    let (newick_no_labels, labels) = newick::split_internal_labels(newick)?;
+    let (ancestry, _) = get_cherries_no_parents_with_bls(&newick_no_labels)?;
+    let v = to_vector_from_cherries(ancestry, false);
+
+    // Identify each node of the vector by (smallest leaf, number of leaves),
+    // as in `split_internal_labels`
+    let n_leaves = v.len() + 1;
+    let mut clades: Vec<(usize, usize)> = (0..n_leaves).map(|leaf| (leaf, 1)).collect();
+    for [c1, c2, _] in get_ancestry(&v) {
+        let (min1, size1) = clades[c1];
+        let (min2, size2) = clades[c2];
+        clades.push((min1.min(min2), size1 + size2));
     }
 
-    return build_vector(&ancestry);
+    let support = clades[n_leaves..]
+        .iter()
+        .map(|clade| match labels.get(clade) {
+            Some(label) => Ok(label.parse::<f32>()?),
+            None => Ok(f32::NAN),
+        })
+        .collect::<Result<Vec<f32>, NewickError>>()?;
+
+    Ok((v, support))
+}
+
+/// Recover a Phylo2Vec vector from a rooted tree with named leaves,
+/// using a taxon table shared across trees
+///
+/// New taxa are added to `table`, so the same taxon gets the same id in all
+/// trees vectorized with the same table. Leaf `i` of the output vector is
+/// the i-th taxon of the tree by increasing table id (see
+/// `newick::relabel_with_table`): if all trees have the same taxa, leaf `i`
+/// is simply the taxon with id `i`.
+///
+/// # Errors
+///
+/// Returns a `NewickError` if the Newick string cannot be parsed
+/// or if a taxon appears more than once in the tree
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::TaxonTable;
+/// use phylo2vec::tree_vec::ops::{to_vector, to_vector_with_table};
+///
+/// let mut table = TaxonTable::new();
+/// let v1 = to_vector_with_table("((a,b),(c,d));", &mut table).unwrap();
+/// let v2 = to_vector_with_table("((d,a),(b,c));", &mut table).unwrap();
+/// assert_eq!(v1, to_vector("((0,1),(2,3));"));
+/// assert_eq!(v2, to_vector("((3,0),(1,2));"));
+/// ```
+pub fn to_vector_with_table(
+    newick: &str,
+    table: &mut TaxonTable,
+) -> Result<Vec<usize>, NewickError> {
+    let newick_int = relabel_with_table(newick, table)?;
+    let (ancestry, _) = get_cherries_no_parents_with_bls(&newick_int)?;
+
+    Ok(to_vector_from_cherries(ancestry, false))
 }
 
 /// Adds a new leaf to the tree
@@ -59,7 +651,6 @@ pub fn add_leaf(v: &mut Vec<usize>, leaf: usize, branch: usize) -> Vec<usize> {
 
     let mut ancestry_add = get_ancestry(v);
 
-    println!("{:?}", ancestry_add);
     let mut found_first_leaf = false;
     for r in 0..ancestry_add.len() {
         for c in 0..3 {
@@ -74,13 +665,9 @@ pub fn add_leaf(v: &mut Vec<usize>, leaf: usize, branch: usize) -> Vec<usize> {
         }
     }
 
-    // ancestry_add[leaf_coords][leaf_col] = leaf as isize;
-    // let ancestry_add_ref = &mut ancestry_add;
-    order_cherries(&mut ancestry_add);
-    order_cherries_no_parents(&mut ancestry_add);
-    let new_vec = build_vector(&ancestry_add);
-
-    new_vec
+    // The ancestry still has (shifted) parent labels, which are not the
+    // canonical labels of the new tree, so both ordering passes are needed
+    to_vector_from_cherries(ancestry_add, true)
 }
 
 /// Removes a leaf from the tree
@@ -136,9 +723,8 @@ pub fn remove_leaf(v: &mut Vec<usize>, leaf: usize) -> (Vec<usize>, usize) {
         ancestry_rm.push(new_row);
     }
 
-    order_cherries(&mut ancestry_rm);
-    order_cherries_no_parents(&mut ancestry_rm);
-    let new_vec = build_vector(&ancestry_rm);
+    // The ancestry still has (shifted) parent labels
+    let new_vec = to_vector_from_cherries(ancestry_rm, true);
 
     return (new_vec, sister);
 }
@@ -146,6 +732,8 @@ pub fn remove_leaf(v: &mut Vec<usize>, leaf: usize) -> (Vec<usize>, usize) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::{check_v, sample_vector};
+    use rand::Rng;
     use rstest::*;
 
     /// Test the conversion of vector to Newick format
@@ -160,6 +748,100 @@ mod tests {
         assert_eq!(newick, expected);
     }
 
+    /// Test that smaller element types give the same trees as usize
+    #[rstest]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(100, false))]
+    #[case(sample_vector(100, true))]
+    fn test_leaf_index_types(#[case] v: Vec<usize>) {
+        let newick = to_newick_from_vector(&v);
+
+        let v_u32: Vec<u32> = to_vector_as(&newick);
+        assert_eq!(to_newick_from_vector(&v_u32), newick);
+        assert_eq!(get_ancestry(&v_u32), get_ancestry(&v));
+
+        let v_u16: Vec<u16> = to_vector_as(&newick);
+        assert_eq!(to_newick_from_vector(&v_u16), newick);
+        assert_eq!(v_u16.iter().map(|&x| x as usize).collect::<Vec<_>>(), v);
+    }
+
+    /// Test that batched conversion matches per-vector conversion
+    #[rstest]
+    fn test_to_newick_batch() {
+        let vectors: Vec<Vec<usize>> = (2..50)
+            .map(|n_leaves| sample_vector(n_leaves, n_leaves % 2 == 0))
+            .collect();
+        let expected: Vec<String> = vectors.iter().map(|v| to_newick_from_vector(v)).collect();
+
+        assert_eq!(to_newick_batch(&vectors), expected);
+        assert!(to_newick_batch(&[]).is_empty());
+    }
+
+    /// Test that batched parsing matches per-string parsing
+    #[rstest]
+    fn test_to_vector_batch() {
+        let vectors: Vec<Vec<usize>> = (2..50)
+            .map(|n_leaves| sample_vector(n_leaves, n_leaves % 2 == 0))
+            .collect();
+        let newicks = to_newick_batch(&vectors);
+
+        assert_eq!(to_vector_batch(&newicks), Ok(vectors));
+        assert_eq!(to_vector_batch::<&str>(&[]), Ok(vec![]));
+    }
+
+    #[rstest]
+    #[case(vec!["(0,1)2;", "((0,2)5,(1,3)4)6;", "((0,2),(1,3);"], 2)]
+    #[case(vec!["(0,1)2", "((0,2)5,(1,3)4)6"], 0)]
+    fn test_to_vector_batch_invalid(#[case] newicks: Vec<&str>, #[case] index: usize) {
+        let source = try_to_vector(newicks[index]).unwrap_err();
+        assert_eq!(
+            to_vector_batch(&newicks),
+            Err(Phylo2VecError::InvalidBatchItem { index, source })
+        );
+    }
+
+    /// Test that deep (caterpillar) trees do not overflow the call stack
+    #[rstest]
+    fn test_to_newick_from_vector_deep() {
+        let n_leaves = 1 << 16;
+        let v: Vec<usize> = (0..n_leaves - 1).collect();
+        let newick = to_newick_from_vector(&v);
+
+        assert!(newick.starts_with("(0,(1,(2,"));
+        assert!(newick.ends_with(&format!("){};", 2 * n_leaves - 2)));
+        assert_eq!(to_vector(&newick), v);
+    }
+
+    /// Test that the ordered fast path matches the general conversion
+    #[rstest]
+    #[case(vec![0])]
+    #[case(vec![0, 1, 2, 3, 4])]
+    #[case(vec![0, 0, 0, 0, 0])]
+    #[case(vec![0, 1, 0, 3, 2, 5, 1])]
+    fn test_to_newick_ordered(#[case] v: Vec<usize>) {
+        assert_eq!(to_newick_ordered(&v), to_newick_from_vector(&v));
+    }
+
+    /// Test the ordered fast path on random ordered vectors
+    #[rstest]
+    #[case(10)]
+    #[case(100)]
+    #[case(1000)]
+    fn test_to_newick_ordered_sampled(#[case] n_leaves: usize) {
+        for _ in 0..10 {
+            let v = sample_vector(n_leaves, true);
+            assert_eq!(to_newick_ordered(&v), to_newick_from_vector(&v));
+        }
+    }
+
+    /// Test that unordered vectors are rejected in debug builds
+    #[rstest]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_to_newick_ordered_unordered_input() {
+        to_newick_ordered(&[0, 2, 1]);
+    }
+
     /// Test the conversion of a matrix to a Newick string
     #[rstest]
     #[case(vec![
@@ -175,6 +857,7 @@ mod tests {
         vec![0.0, 0.1, 0.2],
         vec![1.0, 0.5, 0.7],
     ], "((0:0.1,2:0.2)5:0.5,(1:0.0,3:0.0)4:0.7)6;")]
+    #[cfg(feature = "matrix")]
     fn test_to_newick_from_matrix(#[case] m: Vec<Vec<f32>>, #[case] expected: &str) {
         let newick = to_newick_from_matrix(&m);
         assert_eq!(newick, expected);
@@ -207,6 +890,45 @@ mod tests {
         assert_eq!(cophenetic_distances(&v, unrooted), expected);
     }
 
+    /// Test the vectorization of two trees sharing some taxa with one table
+    #[rstest]
+    fn test_to_vector_with_table() {
+        let mut table = TaxonTable::new();
+
+        let v1 =
+            to_vector_with_table("((human:0.1,chimp:0.2):0.3,gorilla:0.4);", &mut table).unwrap();
+        assert_eq!(v1, to_vector("((0,1),2);"));
+        assert_eq!(table.names(), &["human", "chimp", "gorilla"]);
+
+        // Same taxa as the first tree (plus orangutan): ids are reused
+        let v2 = to_vector_with_table("((gorilla,orangutan),(chimp,human));", &mut table).unwrap();
+        assert_eq!(v2, to_vector("((2,3),(1,0));"));
+        assert_eq!(table.names(), &["human", "chimp", "gorilla", "orangutan"]);
+        assert_eq!(table.id("orangutan"), Some(3));
+
+        // Subset of the taxa: leaves are numbered by increasing id
+        // (human -> 0, gorilla -> 1, orangutan -> 2)
+        let v3 = to_vector_with_table("(orangutan,(gorilla,human));", &mut table).unwrap();
+        assert_eq!(v3, to_vector("(2,(1,0));"));
+        assert_eq!(table.len(), 4);
+    }
+
+    #[rstest]
+    fn test_to_vector_with_table_invalid() {
+        let mut table = TaxonTable::new();
+        table.get_or_insert("z");
+
+        // Duplicates are reported without modifying the table
+        assert_eq!(
+            to_vector_with_table("((a,b),a);", &mut table),
+            Err(NewickError::DuplicateTaxon("a".to_string()))
+        );
+        assert_eq!(table.names(), &["z"]);
+
+        // Polytomies are not binary trees
+        assert!(to_vector_with_table("((a,b,c),d);", &mut table).is_err());
+    }
+
     /// Test the conversion of a Newick string without parents to a vector
     ///
     /// Tests are using 5 or less leaf tree with different structures
@@ -219,6 +941,304 @@ mod tests {
         assert_eq!(vector, expected);
     }
 
+    /// Test that cherries parsed beforehand give the same vector as `to_vector`
+    #[rstest]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(100, false))]
+    #[case(sample_vector(100, true))]
+    fn test_to_vector_from_cherries(#[case] v: Vec<usize>) {
+        let newick = to_newick_from_vector(&v);
+        assert_eq!(to_vector(&newick), v);
+        assert_eq!(
+            to_vector_from_cherries::<usize>(get_cherries(&newick), true),
+            v
+        );
+
+        let newick_no_parents = newick::remove_parent_labels(&newick);
+        assert_eq!(
+            to_vector_from_cherries::<usize>(get_cherries_no_parents(&newick_no_parents), false),
+            v
+        );
+    }
+
+    #[rstest]
+    #[case("((0,2),(1,3);", NewickError::UnbalancedParentheses)]
+    #[case("((0,2)),(1,3));", NewickError::StackUnderflow(6))]
+    #[case("((0,2),(1,3))", NewickError::MissingSemicolon)]
+    #[case("((0,2)5,(1,3)4)6", NewickError::MissingSemicolon)]
+    #[case("((0,2)5,(1,3))6;", NewickError::ParseIntError("".parse::<usize>().unwrap_err()))]
+    #[case("((0,2),(1,4));", NewickError::NodeOutOfRange { node: 4, min: 0, max: 3 })]
+    #[case("((0,2),(1,2));", NewickError::DuplicateNode(2))]
+    #[case("((0,2)5,(1,3)5)6;", NewickError::DuplicateNode(5))]
+    #[case("((0,2)5,(1,3)4)3;", NewickError::ParentBelowChild { parent: 3, child: 5 })]
+    fn test_try_to_vector_invalid(#[case] newick: &str, #[case] expected: NewickError) {
+        assert_eq!(try_to_vector(newick), Err(expected));
+    }
+
+    #[rstest]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(100, true))]
+    fn test_try_to_vector_valid(#[case] v: Vec<usize>) {
+        let newick = to_newick_from_vector(&v);
+        assert_eq!(try_to_vector(&newick), Ok(v.clone()));
+        assert_eq!(try_to_vector(&newick::remove_parent_labels(&newick)), Ok(v));
+    }
+
+    #[rstest]
+    #[case(vec![], "0;")]
+    #[case(vec![0], "(0,1);")]
+    #[case(vec![0, 0, 0, 1, 3], "(((0,(3,5)),2),(1,4));")]
+    #[case(sample_vector(100, false), "")]
+    #[case(sample_vector(100, true), "")]
+    fn test_to_newick_no_labels(#[case] v: Vec<usize>, #[case] expected: &str) {
+        let newick = to_newick_no_labels(&v);
+        let expected = if expected.is_empty() {
+            newick::remove_parent_labels(&to_newick_from_vector(&v))
+        } else {
+            expected.to_string()
+        };
+        assert_eq!(newick, expected);
+        assert_eq!(to_vector(&newick), v);
+    }
+
+    #[rstest]
+    #[case(vec![], "0;")]
+    #[case(vec![0], "(0,1);")]
+    #[case(vec![0, 1], "(0,1,2);")]
+    #[case(vec![0, 0], "(0,2,1);")]
+    #[case(vec![0, 0, 1], "(0,2,(1,3));")]
+    #[case(vec![0, 1, 2, 3, 4], "(0,1,(2,(3,(4,5))));")]
+    #[case(vec![0, 0, 0, 1, 3], "((0,(3,5)),2,(1,4));")]
+    fn test_to_newick_unrooted(#[case] v: Vec<usize>, #[case] expected: &str) {
+        assert_eq!(to_newick_unrooted(&v), expected);
+    }
+
+    #[rstest]
+    #[case(3)]
+    #[case(10)]
+    #[case(100)]
+    fn test_to_newick_unrooted_sampled(#[case] n_leaves: usize) {
+        let v = sample_vector(n_leaves, false);
+        let newick = to_newick_unrooted(&v);
+
+        // The leaf set is preserved
+        let mut leaves: Vec<usize> = newick::get_leaf_labels(&newick)
+            .iter()
+            .map(|label| label.parse().unwrap())
+            .collect();
+        leaves.sort_unstable();
+        assert_eq!(leaves, (0..n_leaves).collect::<Vec<usize>>());
+
+        // The top-level node has three children
+        let mut depth = 0;
+        let mut root_degree = 1;
+        for ch in newick.chars() {
+            match ch {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 1 => root_degree += 1,
+                _ => {}
+            }
+        }
+        assert_eq!(root_degree, 3);
+
+        // Other nodes are unchanged
+        assert_eq!(newick.matches('(').count(), n_leaves - 2);
+    }
+
+    #[rstest]
+    #[case(2)]
+    #[case(10)]
+    #[case(100)]
+    fn test_to_newick_with_taxa(#[case] n_leaves: usize) {
+        let v = sample_vector(n_leaves, false);
+        let taxa: Vec<String> = (0..n_leaves)
+            .map(|i| match i % 3 {
+                0 => format!("taxon_{}", i),
+                1 => format!("taxon {}", i),
+                _ => format!("taxon's ({})", i),
+            })
+            .collect();
+
+        let newick = to_newick_with_taxa(&v, &taxa);
+        let expected_labels: Vec<String> = newick::get_leaf_labels(&to_newick_no_labels(&v))
+            .iter()
+            .map(|leaf| taxa[leaf.parse::<usize>().unwrap()].clone())
+            .collect();
+        assert_eq!(newick::get_leaf_labels(&newick), expected_labels);
+        assert_eq!(newick::remove_parent_labels(&newick), newick);
+    }
+
+    #[rstest]
+    #[should_panic(expected = "Expected one taxon per leaf")]
+    fn test_to_newick_with_taxa_wrong_length() {
+        to_newick_with_taxa(&[0, 0, 1], &["a".to_string(), "b".to_string()]);
+    }
+
+    #[rstest]
+    #[case(vec![0], vec![(0.5, 1.0 / 3.0)], 0, "(0:0,1:0)2;")]
+    #[case(vec![0], vec![(0.5, 1.0 / 3.0)], 2, "(0:0.50,1:0.33)2;")]
+    #[case(vec![0, 0, 1], vec![(0.1, 0.2), (0.3, 0.4), (1.0, 2.0 / 3.0)], 3, "((0:0.300,2:0.400)5:1.000,(1:0.100,3:0.200)4:0.667)6;")]
+    fn test_to_newick_with_bls(
+        #[case] v: Vec<usize>,
+        #[case] bls: Vec<(f64, f64)>,
+        #[case] precision: usize,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(to_newick_with_bls(&v, &bls, precision), expected);
+    }
+
+    /// Test that the tree and branch lengths can be parsed back
+    #[rstest]
+    #[case(10, 4)]
+    #[case(100, 6)]
+    fn test_to_newick_with_bls_round_trip(#[case] n_leaves: usize, #[case] precision: usize) {
+        let v = sample_vector(n_leaves, false);
+        let mut rng = rand::thread_rng();
+        let bls: Vec<(f64, f64)> = (0..n_leaves - 1)
+            .map(|_| (rng.gen::<f64>(), rng.gen::<f64>()))
+            .collect();
+
+        let newick = to_newick_with_bls(&v, &bls, precision);
+        assert_eq!(to_vector(&newick::remove_branch_lengths(&newick)), v);
+
+        let tolerance = 10f64.powi(-(precision as i32));
+        let (cherries, parsed_bls) = newick::get_cherries_with_bls(&newick).unwrap();
+        for ([c1, c2, p], [bl1, bl2]) in cherries.into_iter().zip(parsed_bls) {
+            let (expected1, expected2) = bls[p - n_leaves];
+            assert_eq!(get_ancestry(&v)[p - n_leaves], [c1, c2, p]);
+            assert!((bl1 as f64 - expected1).abs() <= tolerance);
+            assert!((bl2 as f64 - expected2).abs() <= tolerance);
+        }
+    }
+
+    #[rstest]
+    #[should_panic(expected = "Expected one pair of branch lengths per internal node")]
+    fn test_to_newick_with_bls_wrong_length() {
+        to_newick_with_bls(&[0, 0, 1], &[(0.1, 0.2)], 2);
+    }
+
+    #[rstest]
+    #[case(2)]
+    #[case(10)]
+    #[case(100)]
+    fn test_support_round_trip(#[case] n_leaves: usize) {
+        let v = sample_vector(n_leaves, false);
+        let mut rng = rand::thread_rng();
+        let support: Vec<f32> = (0..n_leaves - 1).map(|_| rng.gen::<f32>()).collect();
+
+        let newick = to_newick_with_support(&v, &support);
+        let (newick_no_labels, labels) = newick::split_internal_labels(&newick).unwrap();
+        assert_eq!(newick_no_labels, to_newick_no_labels(&v));
+        assert_eq!(labels.len(), n_leaves - 1);
+
+        let (v2, support2) = to_vector_with_support(&newick).unwrap();
+        assert_eq!(v2, v);
+        assert_eq!(support2, support);
+    }
+
+    #[rstest]
+    fn test_to_vector_with_support_relabelled() {
+        // Same tree as ((0,2)0.9,(1,3)0.5)1; with other internal node labels
+        let (v, support) = to_vector_with_support("((3:0.1,1:0.2)0.5,(2,0)90)100;").unwrap();
+        assert_eq!(v, vec![0, 0, 1]);
+        assert_eq!(support, vec![0.5, 90.0, 100.0]);
+
+        let (_, support) = to_vector_with_support("((0,2),(1,3)0.5);").unwrap();
+        assert_eq!(support[0], 0.5);
+        assert!(support[1].is_nan() && support[2].is_nan());
+
+        assert!(to_vector_with_support("((0,2)high,(1,3));").is_err());
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_to_newick_with_support_wrong_length() {
+        to_newick_with_support(&[0, 0, 1], &[0.5, 0.9]);
+    }
+
+    #[rstest]
+    #[case(sample_vector(2, false), false)]
+    #[case(sample_vector(2, false), true)]
+    #[case(sample_vector(50, false), false)]
+    #[case(sample_vector(50, true), true)]
+    fn test_to_newick_with_base_round_trip(#[case] v: Vec<usize>, #[case] one_based: bool) {
+        let newick = to_newick_from_vector_with_base(&v, one_based);
+        assert_eq!(to_vector_with_base(&newick, one_based), v);
+        assert_eq!(
+            to_vector_with_base(&newick::remove_parent_labels(&newick), one_based),
+            v
+        );
+
+        // Labels are 1..2n-1 in one-based mode, 0..2n-2 otherwise
+        let n_nodes = 2 * v.len() + 1;
+        let offset = usize::from(one_based);
+        assert!(newick.contains(&format!("){};", n_nodes - 1 + offset)));
+        assert_eq!(newick.contains("(0,") || newick.contains(",0)"), !one_based);
+    }
+
+    #[rstest]
+    #[case("((1,2),3);", "((0,1),2);")]
+    #[case("((1,3)6,(2,4)5)7;", "((0,2)5,(1,3)4)6;")]
+    #[case("(((1,(4,6)),3),(2,5));", "(((0,(3,5)),2),(1,4));")]
+    fn test_to_vector_one_based(#[case] newick: &str, #[case] expected_zero_based: &str) {
+        assert_eq!(
+            to_vector_with_base(newick, true),
+            to_vector(expected_zero_based)
+        );
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_to_vector_one_based_zero_label() {
+        to_vector_with_base("((0,1),2);", true);
+    }
+
+    #[rstest]
+    #[case("[&R] ((0:0.1, 2:0.2)[x]5, (1,3)4)6;", vec![0, 0, 1])]
+    #[case("[&R]((0,2)[x]5,(1,3)4)6;", vec![0, 0, 1])]
+    #[case("(((0,(3,5)),2),\n(1,4))", vec![0, 0, 0, 1, 3])]
+    fn test_to_vector_diagnostic(#[case] newick: &str, #[case] expected: Vec<usize>) {
+        let (v, warnings) = to_vector_diagnostic(newick, &NewickParseOptions::default()).unwrap();
+        assert_eq!(v, expected);
+        assert!(!warnings.is_empty());
+
+        // Without normalization, the messy input is rejected
+        assert!(to_vector_diagnostic(newick, &NewickParseOptions::strict()).is_err());
+    }
+
+    #[rstest]
+    fn test_to_vector_diagnostic_warnings() {
+        let newick = "[&R] ((0,2)[comment], (1, 3))";
+        let (v, warnings) = to_vector_diagnostic(newick, &NewickParseOptions::default()).unwrap();
+        assert_eq!(v, vec![0, 0, 1]);
+        assert_eq!(
+            warnings,
+            vec![
+                ParseWarning::StrippedComments { count: 2 },
+                ParseWarning::StrippedWhitespace { count: 3 },
+                ParseWarning::AddedSemicolon,
+            ]
+        );
+
+        // A clean input yields no warning
+        let (_, warnings) =
+            to_vector_diagnostic("((0,2),(1,3));", &NewickParseOptions::default()).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[rstest]
+    #[case("((0,2),(1,3)[abc);")]
+    #[case("((0,1,2),3);")]
+    #[case("((0,2),(1,3))")]
+    fn test_to_vector_diagnostic_invalid(#[case] newick: &str) {
+        let options = NewickParseOptions {
+            add_semicolon: false,
+            ..NewickParseOptions::default()
+        };
+        assert!(to_vector_diagnostic(newick, &options).is_err());
+    }
+
     /// Test the addition of a new leaf to the tree
     ///
     /// Tests are using 6 leaf tree with different leaf and branch indices
@@ -238,11 +1258,14 @@ mod tests {
 
     /// Test the removal of a leaf from the tree
     ///
-    /// Tests are using 6 leaf tree with different leaf and sister branch indices
+    /// Tests are using 6 leaf tree with different leaf and sister branch indices,
+    /// and a 10 leaf tree where a middle leaf is removed:
+    /// ((0,(2,(3,(5,(6,9))))),(1,((4,7),8))); becomes ((0,(2,(3,(4,(5,8))))),(1,(6,7)));
     #[rstest]
     #[case(vec![0, 1, 2, 5, 4, 2], 5, 4, vec![0, 1, 2, 5, 2])]
     #[case(vec![0, 1, 2, 5, 4, 2], 6, 2, vec![0, 1, 2, 5, 4])]
     #[case(vec![0, 1, 2, 5, 4, 2], 0, 11, vec![0, 1, 4, 3, 1])]
+    #[case(vec![0, 0, 2, 1, 3, 5, 4, 8, 6], 4, 7, vec![0, 0, 2, 3, 4, 1, 6, 5])]
     fn test_remove_leaf(
         #[case] mut v: Vec<usize>,
         #[case] leaf: usize,
@@ -253,4 +1276,64 @@ mod tests {
         assert_eq!(new_vec, expected);
         assert_eq!(sister, branch);
     }
+
+    /// Test that removing a leaf then adding it back restores the vector
+    #[rstest]
+    fn test_remove_then_add_leaf() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..5000 {
+            let n_leaves = rng.gen_range(3..50);
+            let v = sample_vector(n_leaves, rng.gen_bool(0.5));
+            let leaf = rng.gen_range(0..n_leaves);
+
+            let (mut v_sub, mut sister) = remove_leaf(&mut v.clone(), leaf);
+            // The sister is labelled in the original tree
+            if sister >= leaf {
+                sister -= 1;
+            }
+            let v_add = add_leaf(&mut v_sub, leaf, sister);
+
+            assert_eq!(v_add, v, "leaf = {}", leaf);
+        }
+    }
+
+    /// Test that adding a leaf then removing it restores the vector
+    #[rstest]
+    fn test_add_then_remove_leaf() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..5000 {
+            let n_leaves = rng.gen_range(2..50);
+            let v = sample_vector(n_leaves, rng.gen_bool(0.5));
+            let leaf = rng.gen_range(0..=n_leaves);
+            let branch = rng.gen_range(0..2 * n_leaves - 1);
+
+            let mut v_add = add_leaf(&mut v.clone(), leaf, branch);
+            check_v(&v_add);
+            let (v_rm, _) = remove_leaf(&mut v_add, leaf);
+
+            assert_eq!(v_rm, v, "leaf = {}, branch = {}", leaf, branch);
+        }
+    }
+}
+
+/// Tests of the minimal feature set (without the `newick` and `matrix` features)
+#[cfg(all(test, not(any(feature = "newick", feature = "matrix"))))]
+mod tests_minimal {
+    use super::*;
+    use crate::utils::sample_vector;
+    use rstest::*;
+
+    /// Test the vector <-> Newick round trip, with and without parent labels
+    #[rstest]
+    #[case(10, true)]
+    #[case(10, false)]
+    #[case(1000, true)]
+    #[case(1000, false)]
+    fn test_core_round_trip(#[case] n_leaves: usize, #[case] ordering: bool) {
+        let v = sample_vector(n_leaves, ordering);
+        let newick = to_newick_from_vector(&v);
+
+        assert_eq!(to_vector(&newick), v);
+        assert_eq!(to_vector(&newick::remove_parent_labels(&newick)), v);
+    }
 }
@@ -1,12 +1,70 @@
-use std::num::IntErrorKind;
+use std::borrow::Cow;
+use std::num::{IntErrorKind, ParseFloatError, ParseIntError};
 
-use crate::tree_vec::types::Ancestry;
-use std::collections::HashMap;
+use crate::tree_vec::types::{Ancestry, OptionalBranchLengths};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
 
+#[cfg(feature = "newick")]
 mod newick_patterns;
 
+#[cfg(feature = "newick")]
 pub use newick_patterns::NewickPatterns;
 
+mod parse_options;
+mod taxon_table;
+mod tokenizer;
+
+pub use parse_options::{normalize_newick, NewickParseOptions, ParseWarning};
+pub use taxon_table::TaxonTable;
+
+pub(crate) use tokenizer::{_tokenize, _unquote, TokenKind};
+
+/// Errors raised when parsing a Newick string
+#[derive(Error, Debug, PartialEq)]
+pub enum NewickError {
+    /// A node label is not a non-negative integer
+    #[error("Invalid node label: {0}")]
+    ParseIntError(#[from] ParseIntError),
+    /// A branch length is not a number
+    #[error("Invalid branch length: {0}")]
+    ParseFloatError(#[from] ParseFloatError),
+    /// A branch length is infinite or NaN
+    #[error("Invalid branch length: {0} is not finite")]
+    NonFiniteBranchLength(String),
+    /// A cherry does not contain two children
+    #[error("Invalid cherry: expected two children in '({0})'")]
+    InvalidCherry(String),
+    /// A taxon appears more than once in a tree
+    #[error("Duplicate taxon: {0}")]
+    DuplicateTaxon(String),
+    /// A comment opened with `[` is never closed
+    #[error("Unterminated comment starting at position {0}")]
+    UnterminatedComment(usize),
+    /// The Newick string does not end with a semicolon
+    #[error("Missing semicolon at the end of the Newick string")]
+    MissingSemicolon,
+    /// A closing parenthesis does not close a cherry of two nodes
+    #[error("Invalid cherry: ')' at position {0} closes fewer than two nodes")]
+    StackUnderflow(usize),
+    /// Opening and closing parentheses do not match
+    #[error("Unbalanced parentheses in the Newick string")]
+    UnbalancedParentheses,
+    /// A node label is outside the range allowed for its node type
+    /// (0..n-1 for leaves, n..2n-2 for internal nodes)
+    #[error("Invalid node label: {node} is out of bounds ({min}..={max})")]
+    NodeOutOfRange { node: usize, min: usize, max: usize },
+    /// A node label appears more than once in a tree
+    #[error("Duplicate node label: {0}")]
+    DuplicateNode(usize),
+    /// An internal node is labelled below one of its children
+    #[error("Invalid node label: parent {parent} must be greater than its child {child}")]
+    ParentBelowChild { parent: usize, child: usize },
+    /// A Nexus document has no tree or refers to unknown taxa
+    #[error("Invalid Nexus document: {0}")]
+    InvalidNexus(String),
+}
+
 fn _stoi_substr(s: &str, start: usize, end: &mut usize) -> Result<usize, IntErrorKind> {
     let s = &s[start..];
     let mut value = 0;
@@ -25,99 +83,194 @@ fn _stoi_substr(s: &str, start: usize, end: &mut usize) -> Result<usize, IntErro
     }
 }
 
-fn _get_cherries_inner(ancestry: &mut Ancestry, newick: &str) {
+// Parse a node label starting at position `start`, which may be quoted (e.g. `'3'`)
+fn _parse_node(newick: &str, start: usize, end: &mut usize) -> Result<usize, NewickError> {
+    let quoted = newick.as_bytes().get(start) == Some(&b'\'');
+    let digits_start = start + usize::from(quoted);
+    // `_stoi_substr` only fails if there is no digit, i.e. on an empty label
+    let node = _stoi_substr(newick, digits_start, end)
+        .map_err(|_| NewickError::from("".parse::<usize>().unwrap_err()))?;
+    if quoted {
+        if newick.as_bytes().get(*end) != Some(&b'\'') {
+            return Err("'".parse::<usize>().unwrap_err().into());
+        }
+        *end += 1;
+    }
+    Ok(node)
+}
+
+// Remove comments in square brackets (e.g. `[&NHX:S=human]`) before parsing
+fn _strip_comments(newick: &str) -> Result<Cow<'_, str>, NewickError> {
+    if !newick.contains('[') {
+        return Ok(Cow::Borrowed(newick));
+    }
+    let options = NewickParseOptions {
+        strip_comments: true,
+        ..NewickParseOptions::strict()
+    };
+    let (stripped, _) = normalize_newick(newick, &options)?;
+    Ok(Cow::Owned(stripped))
+}
+
+// Pop the two children of the cherry closed at position `i`
+fn _pop_cherry(stack: &mut Vec<usize>, i: usize) -> Result<(usize, usize), NewickError> {
+    match (stack.pop(), stack.pop()) {
+        (Some(c2), Some(c1)) => Ok((c1, c2)),
+        _ => Err(NewickError::StackUnderflow(i)),
+    }
+}
+
+// Check that the parsed string describes a single tree with leaves 0..n-1
+// and, if parent labels are given, internal nodes n..2n-2 labelled above
+// their children
+fn _check_tree(
+    stack: &[usize],
+    n_open: usize,
+    ancestry: &Ancestry,
+    leaves: &[usize],
+    has_parents: bool,
+) -> Result<(), NewickError> {
+    if stack.len() != 1 || n_open != ancestry.len() {
+        return Err(NewickError::UnbalancedParentheses);
+    }
+
+    let parents: &[[usize; 3]] = if has_parents { ancestry } else { &[] };
+    _check_labels(ancestry.len() + 1, leaves, parents.iter().copied())
+}
+
+// Check that the leaves are 0..n-1 and that the labelled internal nodes
+// (given with their children) are n..2n-2 and labelled above their children
+fn _check_labels(
+    n_leaves: usize,
+    leaves: &[usize],
+    parents: impl Iterator<Item = [usize; 3]> + Clone,
+) -> Result<(), NewickError> {
+    for [c1, c2, p] in parents.clone() {
+        let child = c1.max(c2);
+        if p <= child {
+            return Err(NewickError::ParentBelowChild { parent: p, child });
+        }
+    }
+
+    let nodes = leaves
+        .iter()
+        .map(|&leaf| (leaf, 0, n_leaves - 1))
+        .chain(parents.map(|[_, _, p]| (p, n_leaves, 2 * n_leaves - 2)));
+
+    let mut seen = vec![false; 2 * n_leaves - 1];
+    for (node, min, max) in nodes {
+        if node < min || node > max {
+            return Err(NewickError::NodeOutOfRange { node, min, max });
+        }
+        if seen[node] {
+            return Err(NewickError::DuplicateNode(node));
+        }
+        seen[node] = true;
+    }
+
+    Ok(())
+}
+
+fn _get_cherries_inner(ancestry: &mut Ancestry, newick: &str) -> Result<(), NewickError> {
     let mut stack = Vec::new();
+    let mut leaves = Vec::new();
+    let mut n_open = 0;
     let mut i = 0;
 
     while i < newick.len() {
         let c = newick.as_bytes()[i] as char;
-        if c == ')' {
+        if c == '(' {
+            n_open += 1;
+        } else if c == ')' {
+            let (c1, c2) = _pop_cherry(&mut stack, i)?;
             i += 1;
 
-            let c2 = stack.pop().unwrap();
-            let c1 = stack.pop().unwrap();
-
             let mut end = 0;
-            let p = _stoi_substr(newick, i, &mut end).expect("Bad input");
+            let p = _parse_node(newick, i, &mut end)?;
             i = end - 1;
 
             ancestry.push([c1, c2, p]);
             stack.push(p);
-        } else if c.is_ascii_digit() {
+        } else if c.is_ascii_digit() || c == '\'' {
             let mut end = 0;
-            let node = _stoi_substr(newick, i, &mut end).expect("Bad input");
+            let node = _parse_node(newick, i, &mut end)?;
             stack.push(node);
+            leaves.push(node);
             i = end - 1;
         }
         i += 1;
     }
+
+    _check_tree(&stack, n_open, ancestry, &leaves, true)
 }
 
-fn _get_cherries_no_parents_inner(ancestry: &mut Ancestry, newick: &str) {
+fn _get_cherries_no_parents_inner(
+    ancestry: &mut Ancestry,
+    newick: &str,
+) -> Result<(), NewickError> {
     let newick_length = newick.len();
     let mut stack = Vec::with_capacity(newick_length);
+    let mut leaves = Vec::new();
+    let mut n_open = 0;
     let mut i = 0;
 
     while i < newick_length {
         let c = newick.as_bytes()[i] as char;
 
-        if c == ')' {
-            let c2 = stack.pop().unwrap();
-            let c1 = stack.pop().unwrap();
+        if c == '(' {
+            n_open += 1;
+        } else if c == ')' {
+            let (c1, c2) = _pop_cherry(&mut stack, i)?;
 
             let c_max = std::cmp::max(c1, c2);
             ancestry.push([c1, c2, c_max]);
 
             let c_min = std::cmp::min(c1, c2);
             stack.push(c_min);
-        } else if c.is_ascii_digit() {
+        } else if c.is_ascii_digit() || c == '\'' {
             let mut end = 0;
-            let leaf = _stoi_substr(newick, i, &mut end).expect("Bad input");
+            let leaf = _parse_node(newick, i, &mut end)?;
             stack.push(leaf);
+            leaves.push(leaf);
             i = end - 1;
         }
         i += 1;
     }
+
+    _check_tree(&stack, n_open, ancestry, &leaves, false)
 }
 
 fn _get_cherries_recursive_inner_with_bls(
     ancestry: &mut Ancestry,
-    bls: &mut Vec<[f32; 2]>,
+    bls: &mut OptionalBranchLengths,
     newick: &str,
     newick_has_parents: bool,
-) {
+) -> Result<(), NewickError> {
     let mut open_idx: usize = 0;
 
-    for (i, ch) in newick.chars().enumerate() {
+    for (i, ch) in newick.char_indices() {
         if ch == '(' {
             open_idx = i + 1;
         } else if ch == ')' {
-            let parts: Vec<&str> = newick[open_idx..i].split(',').collect();
-            let part = parts[0];
-            let c1: usize;
-            let c2;
-            let mut bl1 = 0.0;
-            let mut bl2 = 0.0;
-            if part.contains(':') {
-                // have to account for base case in which newick string is just a leaf
-                let (child1_str, bl1_str) = part.split_once(':').unwrap();
-                let (child2_str, bl2_str) = parts[1].split_once(':').unwrap();
-
-                // Parse the children (c1, c2)
-                c1 = child1_str.parse::<usize>().unwrap();
-                c2 = child2_str.parse::<usize>().unwrap();
-
-                // Parse the branch lengths (bl1, bl2)
-                bl1 = bl1_str.parse::<f32>().unwrap_or(0.0);
-                bl2 = bl2_str.parse::<f32>().unwrap_or(0.0);
-            } else {
-                c1 = parts[0].parse::<usize>().unwrap();
-                c2 = parts[1].parse::<usize>().unwrap();
-            }
+            let cherry = &newick[open_idx..i];
+            let (part1, part2) = cherry
+                .split_once(',')
+                .ok_or_else(|| NewickError::InvalidCherry(cherry.to_string()))?;
+
+            // Split each child into its label and (optional) branch length
+            let (child1_str, bl1_str) = part1.split_once(':').unwrap_or((part1, ""));
+            let (child2_str, bl2_str) = part2.split_once(':').unwrap_or((part2, ""));
+
+            // Parse the children (c1, c2)
+            let c1 = _unquote(child1_str).parse::<usize>()?;
+            let c2 = _unquote(child2_str).parse::<usize>()?;
+
+            // Parse the branch lengths (bl1, bl2), which may be missing
+            let bl1 = _parse_branch_length(bl1_str)?;
+            let bl2 = _parse_branch_length(bl2_str)?;
 
             // The parent node (if present)
             let parent: usize;
-            // let _blp: f32;
             let new_newick: String;
 
             if newick_has_parents {
@@ -127,20 +280,14 @@ fn _get_cherries_recursive_inner_with_bls(
                     .unwrap_or("")
                     .split(')')
                     .next()
-                    .unwrap_or("")
-                    .to_string();
+                    .unwrap_or("");
 
-                // Attempt to split by ":" to extract the branch length if it exists
-                if parent_pair.is_empty() {
-                    continue;
-                } else {
-                    let (parent_str, blp_str) = parent_pair.split_once(':').unwrap();
-                    eprint!("parent_str: {}, blp_str: {}", parent_str, blp_str);
-                    parent = match parent_str.parse::<usize>() {
-                        Ok(parent_value) => parent_value, // Successfully parsed the parent node
-                        Err(_) => std::cmp::max(c1, c2),  // Fallback value if parsing fails
-                    };
-                }
+                // Discard the branch length of the parent (if any)
+                let parent_str = parent_pair.split(':').next().unwrap_or("");
+                // Unlabelled parents are labelled by their largest child
+                parent = _unquote(parent_str)
+                    .parse::<usize>()
+                    .unwrap_or(std::cmp::max(c1, c2));
                 new_newick = format!("{}{}", &newick[..open_idx - 1], &newick[i + 1..]);
             }
             // If the newick string does not have parents
@@ -167,78 +314,312 @@ fn _get_cherries_recursive_inner_with_bls(
             );
         }
     }
+
+    // All cherries have been replaced by their parent
+    if newick.contains('(') {
+        return Err(NewickError::UnbalancedParentheses);
+    }
+
+    Ok(())
 }
 
+// Parse a branch length annotation, returning None if it is missing
+fn _parse_branch_length(bl_str: &str) -> Result<Option<f32>, NewickError> {
+    let bl_str = bl_str.trim();
+    if bl_str.is_empty() {
+        return Ok(None);
+    }
+    let bl = bl_str.parse::<f32>()?;
+    // `parse` accepts "inf" and "nan", which would silently poison downstream computations
+    if !bl.is_finite() {
+        return Err(NewickError::NonFiniteBranchLength(bl_str.to_string()));
+    }
+    Ok(Some(bl))
+}
+
+// Replace missing branch lengths by 0
+fn _fill_missing_bls(bls: OptionalBranchLengths) -> Vec<[f32; 2]> {
+    bls.into_iter()
+        .map(|[bl1, bl2]| [bl1.unwrap_or(0.0), bl2.unwrap_or(0.0)])
+        .collect()
+}
+
+/// Get the cherries (with parent labels) of a Newick string
+///
+/// # Panics
+///
+/// Panics if the Newick string cannot be parsed (see `try_get_cherries`)
 pub fn get_cherries(newick: &str) -> Ancestry {
+    try_get_cherries(newick).expect("Invalid Newick string")
+}
+
+/// Get the cherries (with parent labels) of a Newick string
+///
+/// Branch lengths are not supported. Comments in square brackets are ignored
+/// and integer labels may be quoted (e.g. `'3'`).
+///
+/// # Errors
+///
+/// Returns a `NewickError` if a node label is missing, out of range or
+/// duplicated, if a closing parenthesis does not close two nodes, or if
+/// parentheses are unbalanced
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::{try_get_cherries, NewickError};
+///
+/// assert_eq!(try_get_cherries("((0,2)3,1)4;"), Ok(vec![[0, 2, 3], [3, 1, 4]]));
+/// assert_eq!(
+///     try_get_cherries("((0,2)3,1;"),
+///     Err(NewickError::UnbalancedParentheses)
+/// );
+/// ```
+pub fn try_get_cherries(newick: &str) -> Result<Ancestry, NewickError> {
     if newick.is_empty() {
-        return Vec::new(); // Return empty ancestry and branch length vectors
+        return Ok(Vec::new()); // Return empty ancestry and branch length vectors
     }
+    let newick = _strip_comments(newick)?;
     let mut ancestry: Ancestry = Vec::new();
-    _get_cherries_inner(&mut ancestry, &newick[..newick.len() - 1]);
-    ancestry
+    _get_cherries_inner(&mut ancestry, &newick[..newick.len() - 1])?;
+    Ok(ancestry)
+}
+
+/// Get the cherries (with parent labels) and branch lengths of a Newick string
+///
+/// Missing branch lengths are set to 0 (see `get_cherries_with_optional_bls`).
+/// Comments in square brackets (e.g., `[&NHX:...]`) are ignored.
+///
+/// # Errors
+///
+/// Returns a `NewickError` if a node label or a branch length cannot be parsed,
+/// if a node label is out of range or duplicated (as in `try_get_cherries`),
+/// if a cherry does not have exactly two children, or if parentheses are unbalanced.
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::get_cherries_with_bls;
+///
+/// let (ancestry, bls) = get_cherries_with_bls("((0:0.1,2:0.2)3:0.3,1:0.4)4;").unwrap();
+/// assert_eq!(ancestry, vec![[0, 2, 3], [3, 1, 4]]);
+/// assert_eq!(bls, vec![[0.1, 0.2], [0.3, 0.4]]);
+/// assert!(get_cherries_with_bls("((0:0.1,a:0.2)3:0.3,1:0.4)4;").is_err());
+/// // An unlabelled parent is labelled by its largest child
+/// let (ancestry, _) = get_cherries_with_bls("(0:0.1,1:0.2):0.3;").unwrap();
+/// assert_eq!(ancestry, vec![[0, 1, 1]]);
+/// ```
+pub fn get_cherries_with_bls(newick: &str) -> Result<(Ancestry, Vec<[f32; 2]>), NewickError> {
+    let (ancestry, bls) = get_cherries_with_optional_bls(newick)?;
+    Ok((ancestry, _fill_missing_bls(bls)))
 }
 
-pub fn get_cherries_with_bls(newick: &str) -> (Ancestry, Vec<[f32; 2]>) {
+/// Get the cherries (with parent labels) and branch lengths of a Newick string,
+/// where some edges may not be annotated with a branch length
+///
+/// Missing branch lengths are returned as `None`. Comments in square
+/// brackets are ignored.
+///
+/// # Errors
+///
+/// Returns a `NewickError` if a node label or a branch length cannot be parsed,
+/// if a node label is out of range or duplicated (as in `try_get_cherries`),
+/// if a cherry does not have exactly two children, or if parentheses are unbalanced.
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::get_cherries_with_optional_bls;
+///
+/// let (ancestry, bls) = get_cherries_with_optional_bls("(0:0.5,1)2;").unwrap();
+/// assert_eq!(ancestry, vec![[0, 1, 2]]);
+/// assert_eq!(bls, vec![[Some(0.5), None]]);
+/// ```
+pub fn get_cherries_with_optional_bls(
+    newick: &str,
+) -> Result<(Ancestry, OptionalBranchLengths), NewickError> {
     if newick.is_empty() {
-        return (Vec::new(), Vec::new()); // Return empty ancestry and branch length vectors
+        return Ok((Vec::new(), Vec::new())); // Return empty ancestry and branch length vectors
     }
+    _get_cherries_with_bls_inner(&_strip_comments(newick)?, true)
+}
+
+// Parse the cherries and branch lengths of a Newick string without comments
+pub(crate) fn _get_cherries_with_bls_inner(
+    newick: &str,
+    with_parents: bool,
+) -> Result<(Ancestry, OptionalBranchLengths), NewickError> {
     let mut ancestry: Ancestry = Vec::new();
-    let mut bls: Vec<[f32; 2]> = Vec::new();
-    _get_cherries_recursive_inner_with_bls(
-        &mut ancestry,
-        &mut bls,
-        &newick[..newick.len() - 1],
-        true,
-    );
-    (ancestry, bls)
+    let mut bls: OptionalBranchLengths = Vec::new();
+    let newick = newick.strip_suffix(';').unwrap_or(newick);
+    _get_cherries_recursive_inner_with_bls(&mut ancestry, &mut bls, newick, with_parents)?;
+
+    // The recursive parser cannot tell leaves from the parents it substituted,
+    // so leaves and parent labels are read from the tokens. The k-th closing
+    // parenthesis closes the k-th cherry of `ancestry`.
+    let mut leaves = Vec::with_capacity(ancestry.len() + 1);
+    let mut labelled = vec![false; ancestry.len()];
+    let mut n_closed = 0;
+    let mut previous = None;
+    for token in _tokenize(newick) {
+        if token.kind == TokenKind::Text && token.text.trim().is_empty() {
+            continue;
+        }
+        match (previous, token.kind) {
+            (Some(TokenKind::Open | TokenKind::Comma) | None, TokenKind::Text) => {
+                leaves.push(_unquote(token.text).parse::<usize>()?);
+            }
+            (Some(TokenKind::Close), TokenKind::Text) if with_parents => {
+                // Unlabelled parents were labelled by their largest child
+                labelled[n_closed - 1] = _unquote(token.text).parse::<usize>().is_ok();
+            }
+            (_, TokenKind::Close) => n_closed += 1,
+            _ => {}
+        }
+        previous = Some(token.kind);
+    }
+
+    let parents = ancestry
+        .iter()
+        .zip(&labelled)
+        .filter(|(_, &labelled)| labelled)
+        .map(|(&row, _)| row);
+    _check_labels(ancestry.len() + 1, &leaves, parents)?;
+
+    Ok((ancestry, bls))
 }
 
+/// Get the cherries (without parent labels) of a Newick string
+///
+/// # Panics
+///
+/// Panics if the Newick string cannot be parsed (see `try_get_cherries_no_parents`)
 pub fn get_cherries_no_parents(newick: &str) -> Ancestry {
+    try_get_cherries_no_parents(newick).expect("Invalid Newick string")
+}
+
+/// Get the cherries (without parent labels) of a Newick string
+///
+/// Each cherry is labelled by its largest child, as expected by
+/// `order_cherries_no_parents`. Branch lengths are not supported.
+/// Comments in square brackets are ignored and integer labels may be quoted.
+///
+/// # Errors
+///
+/// Returns a `NewickError` if a leaf label is out of range or duplicated,
+/// if a closing parenthesis does not close two nodes, or if parentheses are
+/// unbalanced
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::{try_get_cherries_no_parents, NewickError};
+///
+/// assert_eq!(try_get_cherries_no_parents("((0,2),1);"), Ok(vec![[0, 2, 2], [0, 1, 1]]));
+/// assert_eq!(
+///     try_get_cherries_no_parents("((0,),1);"),
+///     Err(NewickError::StackUnderflow(4))
+/// );
+/// ```
+pub fn try_get_cherries_no_parents(newick: &str) -> Result<Ancestry, NewickError> {
     if newick.is_empty() {
-        return Vec::new(); // Return empty ancestry and branch length vectors
+        return Ok(Vec::new()); // Return empty ancestry and branch length vectors
     }
+    let newick = _strip_comments(newick)?;
     let mut ancestry: Ancestry = Vec::new();
-    _get_cherries_no_parents_inner(&mut ancestry, &newick[..newick.len() - 1]);
-    ancestry
+    _get_cherries_no_parents_inner(&mut ancestry, &newick[..newick.len() - 1])?;
+    Ok(ancestry)
 }
 
-pub fn get_cherries_no_parents_with_bls(newick: &str) -> (Ancestry, Vec<[f32; 2]>) {
+/// Get the cherries (without parent labels) and branch lengths of a Newick string
+///
+/// Missing branch lengths are set to 0. Comments in square brackets are
+/// ignored.
+///
+/// # Errors
+///
+/// Returns a `NewickError` if a node label or a branch length cannot be parsed,
+/// if a node label is out of range or duplicated (as in `try_get_cherries_no_parents`),
+/// if a cherry does not have exactly two children, or if parentheses are unbalanced.
+pub fn get_cherries_no_parents_with_bls(
+    newick: &str,
+) -> Result<(Ancestry, Vec<[f32; 2]>), NewickError> {
     if newick.is_empty() {
-        return (Vec::new(), Vec::new()); // Return empty ancestry and branch length vectors
+        return Ok((Vec::new(), Vec::new())); // Return empty ancestry and branch length vectors
     }
-    let mut ancestry: Ancestry = Vec::new();
-    let mut bls: Vec<[f32; 2]> = Vec::new();
-    _get_cherries_recursive_inner_with_bls(
-        &mut ancestry,
-        &mut bls,
-        &newick[..newick.len() - 1],
-        false,
-    );
-    (ancestry, bls)
-}
-
-// The recursive function that builds the Newick string
-fn _build_newick_recursive_inner(p: usize, ancestry: &Ancestry) -> String {
-    let leaf_max = ancestry.len();
-
-    // Extract the children (c1, c2) and ignore the parent from the ancestry tuple
-    let [c1, c2, _] = ancestry[p - leaf_max - 1];
-
-    // Recursive calls for left and right children, checking if they are leaves or internal nodes
-    let left = if c1 > leaf_max {
-        _build_newick_recursive_inner(c1, ancestry)
-    } else {
-        c1.to_string() // It's a leaf node, just convert to string
-    };
+    let (ancestry, bls) = _get_cherries_with_bls_inner(&_strip_comments(newick)?, false)?;
+    Ok((ancestry, _fill_missing_bls(bls)))
+}
 
-    let right = if c2 > leaf_max {
-        _build_newick_recursive_inner(c2, ancestry)
-    } else {
-        c2.to_string() // It's a leaf node, just convert to string
-    };
+/// Write the Newick string of an ancestry matrix
+///
+/// Node `n + k` must be the parent of row `k`, as in `get_ancestry`.
+/// `write_leaf` is called with each leaf, and `write_label` with each
+/// internal node right after its closing parenthesis. Like `visit_postorder`,
+/// the traversal does not recurse, so it handles deep trees (e.g., caterpillars).
+pub(crate) fn _write_newick(
+    ancestry: &Ancestry,
+    write_leaf: impl FnMut(&mut String, usize),
+    write_label: impl FnMut(&mut String, usize),
+) -> String {
+    _write_newick_contracted(ancestry, &[], write_leaf, write_label)
+}
 
-    // Create the Newick string in the form (left, right)p
-    format!("({},{}){}", left, right, p)
+/// Write the Newick string of an ancestry matrix with some edges contracted
+///
+/// The edge above each internal node of `contracted` is removed: the node is
+/// written without parentheses or label, so its children are listed with
+/// those of its parent, which yields a multifurcation.
+pub(crate) fn _write_newick_contracted(
+    ancestry: &Ancestry,
+    contracted: &[usize],
+    mut write_leaf: impl FnMut(&mut String, usize),
+    mut write_label: impl FnMut(&mut String, usize),
+) -> String {
+    enum Token {
+        Node(usize),
+        Comma,
+        Close(usize),
+    }
+
+    let n_leaves = ancestry.len() + 1;
+    // A tree with a single leaf has an empty ancestry
+    let root = ancestry.last().map_or(0, |row| row[2]);
+    let mut newick = String::with_capacity(8 * n_leaves);
+
+    let mut is_contracted = vec![false; 2 * n_leaves - 1];
+    for &node in contracted {
+        is_contracted[node] = true;
+    }
+
+    let mut stack = vec![Token::Node(root)];
+    while let Some(token) = stack.pop() {
+        match token {
+            Token::Node(node) if node < n_leaves => write_leaf(&mut newick, node),
+            Token::Node(node) if is_contracted[node] => {
+                let [c1, c2, _] = ancestry[node - n_leaves];
+                stack.push(Token::Node(c2));
+                stack.push(Token::Comma);
+                stack.push(Token::Node(c1));
+            }
+            Token::Node(node) => {
+                let [c1, c2, _] = ancestry[node - n_leaves];
+                newick.push('(');
+                stack.push(Token::Close(node));
+                stack.push(Token::Node(c2));
+                stack.push(Token::Comma);
+                stack.push(Token::Node(c1));
+            }
+            Token::Comma => newick.push(','),
+            Token::Close(node) => {
+                newick.push(')');
+                write_label(&mut newick, node);
+            }
+        }
+    }
+    newick.push(';');
+
+    newick
 }
 
 /// Build newick string from the ancestry matrix and branch lengths
@@ -317,8 +698,103 @@ pub fn build_newick_with_bls(ancestry: &Ancestry, branch_lengths: &Vec<[f32; 2]>
 /// assert_eq!(result, "(((0,(3,5)),2),(1,4));");
 /// ```
 pub fn remove_parent_labels(newick: &str) -> String {
-    let newick_patterns = NewickPatterns::new();
-    return newick_patterns.parents.replace_all(newick, ")").to_string();
+    let mut result = String::with_capacity(newick.len());
+    let mut after_parenthesis = false;
+
+    for ch in newick.chars() {
+        // Skip the digits of a label right after a closing parenthesis
+        if after_parenthesis && ch.is_ascii_digit() {
+            continue;
+        }
+        after_parenthesis = ch == ')';
+        result.push(ch);
+    }
+
+    result
+}
+
+/// Remove branch lengths from the Newick string
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::remove_branch_lengths;
+///
+/// let newick = "((0:0.1,2:0.2)3:0.3,1:4e-1)4:0.5;";
+/// assert_eq!(remove_branch_lengths(newick), "((0,2)3,1)4;");
+/// ```
+pub fn remove_branch_lengths(newick: &str) -> String {
+    let mut result = String::with_capacity(newick.len());
+    let mut in_branch_length = false;
+
+    for token in _tokenize(newick) {
+        match token.kind {
+            TokenKind::Colon => in_branch_length = true,
+            TokenKind::Text if in_branch_length => {}
+            TokenKind::Comment => result.push_str(token.text),
+            _ => {
+                in_branch_length = false;
+                result.push_str(token.text);
+            }
+        }
+    }
+
+    result
+}
+
+/// Shift all integer node labels of a Newick string by `offset`
+///
+/// Labels are the node names written right after `(`, `,` or `)`, so branch
+/// lengths are left untouched, as are non-integer labels. This converts
+/// between 0-based labels (as used by Phylo2Vec) and 1-based labels (as
+/// used by R and some Newick tools).
+///
+/// # Panics
+///
+/// Panics if a shifted label is negative
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::shift_labels;
+///
+/// assert_eq!(shift_labels("((0,2)5:0.5,(1,3)4)6;", 1), "((1,3)6:0.5,(2,4)5)7;");
+/// assert_eq!(shift_labels("((1,3),(2,4));", -1), "((0,2),(1,3));");
+/// ```
+pub fn shift_labels(newick: &str, offset: isize) -> String {
+    let mut result = String::with_capacity(newick.len() + newick.len() / 4);
+    let mut is_label = true;
+
+    for token in _tokenize(newick) {
+        match token.kind {
+            TokenKind::Text if is_label => result.push_str(&_shift_label(token.text, offset)),
+            _ => result.push_str(token.text),
+        }
+        match token.kind {
+            TokenKind::Open | TokenKind::Close | TokenKind::Comma => is_label = true,
+            TokenKind::Colon | TokenKind::Semicolon => is_label = false,
+            _ => {}
+        }
+    }
+
+    result
+}
+
+// Shift a single label if it is an integer
+fn _shift_label(label: &str, offset: isize) -> String {
+    match label.trim().parse::<usize>() {
+        Ok(value) => {
+            let shifted = value as isize + offset;
+            assert!(
+                shifted >= 0,
+                "Label {} cannot be shifted by {}",
+                value,
+                offset
+            );
+            shifted.to_string()
+        }
+        Err(_) => label.to_string(),
+    }
 }
 
 /// Check if the Newick string has parent labels
@@ -337,12 +813,55 @@ pub fn remove_parent_labels(newick: &str) -> String {
 /// assert_eq!(result_no_parents, false);
 /// ```
 pub fn has_parents(newick: &str) -> bool {
-    let newick_patterns = NewickPatterns::new();
-    return newick_patterns.parents.is_match(newick);
+    let mut after_close = false;
+    _tokenize(newick).any(|token| {
+        let is_parent = after_close
+            && token.kind == TokenKind::Text
+            && _unquote(token.text).starts_with(|c: char| c.is_ascii_digit());
+        if token.kind != TokenKind::Comment {
+            after_close = token.kind == TokenKind::Close;
+        }
+        is_parent
+    })
+}
+
+/// Get the maximum nesting depth of the parentheses of a Newick string
+///
+/// The string is scanned once, without being parsed. Parentheses inside
+/// comments in square brackets or quoted labels are ignored. For a valid
+/// tree, this is the height of the tree, i.e. the number of edges from the
+/// root to its deepest leaf.
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::max_nesting_depth;
+///
+/// assert_eq!(max_nesting_depth("(((0,(3,5)6)8,2)9,(1,4)7)10;"), 4);
+/// assert_eq!(max_nesting_depth("0;"), 0);
+/// ```
+pub fn max_nesting_depth(newick: &str) -> usize {
+    let mut depth: usize = 0;
+    let mut max_depth = 0;
+
+    for token in _tokenize(newick) {
+        match token.kind {
+            TokenKind::Open => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            TokenKind::Close => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    max_depth
 }
 
 /// Find the number of leaves in the Newick string
 ///
+/// Only leaves with integer labels (possibly quoted) are counted.
+///
 /// # Example
 ///
 /// ```
@@ -352,11 +871,23 @@ pub fn has_parents(newick: &str) -> bool {
 /// let result = find_num_leaves(newick);
 /// assert_eq!(result, 6);
 /// ```
+#[cfg(feature = "newick")]
 pub fn find_num_leaves(newick: &str) -> usize {
-    let newick_patterns = NewickPatterns::new();
+    let newick_patterns = NewickPatterns::get();
+    // Unquote integer labels and hide the others, which may contain commas
+    let newick = newick_patterns
+        .quoted_label
+        .replace_all(newick, |caps: &regex::Captures| {
+            let label = &caps[1];
+            if !label.is_empty() && label.bytes().all(|b| b.is_ascii_digit()) {
+                label.to_string()
+            } else {
+                "_".to_string()
+            }
+        });
     let result: Vec<usize> = newick_patterns
         .pairs
-        .captures_iter(newick)
+        .captures_iter(&newick)
         .map(|caps| {
             let (_, [_, node]) = caps.extract();
             node.parse::<usize>().unwrap()
@@ -367,30 +898,433 @@ pub fn find_num_leaves(newick: &str) -> usize {
 }
 
 /// Build newick string from the ancestry matrix
+///
+/// The tree is traversed iteratively, so this also works for very deep trees.
 pub fn build_newick(ancestry: &Ancestry) -> String {
-    // Get the root node, which is the parent value of the last ancestry element
-    let root = ancestry.last().unwrap()[2];
+    _write_newick(ancestry, _write_integer, _write_integer)
+}
 
-    // Build the Newick string starting from the root, and append a semicolon
-    format!("{};", _build_newick_recursive_inner(root, ancestry))
+// Write a node as its integer label
+pub(crate) fn _write_integer(newick: &mut String, node: usize) {
+    newick.push_str(&node.to_string());
+}
+
+// Get a writer of the length of the edge above each node (nothing for the root)
+//
+// `bls[k]` holds the branch lengths of the two children of ancestry row `k`.
+// They are written with `precision` decimals if given, or at f32 precision
+// otherwise, as branch lengths are parsed as f32.
+pub(crate) fn _branch_length_writer(
+    ancestry: &Ancestry,
+    bls: &[(f64, f64)],
+    precision: Option<usize>,
+) -> impl Fn(&mut String, usize) {
+    let mut node_bls: Vec<Option<f64>> = vec![None; 2 * ancestry.len() + 1];
+    for (&[c1, c2, _], &(bl1, bl2)) in ancestry.iter().zip(bls.iter()) {
+        node_bls[c1] = Some(bl1);
+        node_bls[c2] = Some(bl2);
+    }
+
+    move |newick: &mut String, node: usize| match (node_bls[node], precision) {
+        (Some(bl), Some(precision)) => newick.push_str(&format!(":{:.*}", precision, bl)),
+        (Some(bl), None) => newick.push_str(&format!(":{}", bl as f32)),
+        (None, _) => {}
+    }
+}
+
+/// Quote a label if it contains characters with a special meaning in Newick
+///
+/// Quoted labels are enclosed in single quotes, and single quotes inside
+/// them are doubled (e.g., `O'Brien` becomes `'O''Brien'`).
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::quote_label;
+///
+/// assert_eq!(quote_label("Gorilla"), "Gorilla");
+/// assert_eq!(quote_label("Homo sapiens"), "'Homo sapiens'");
+/// assert_eq!(quote_label("O'Brien"), "'O''Brien'");
+/// ```
+pub fn quote_label(label: &str) -> String {
+    let needs_quotes = label.is_empty()
+        || label
+            .chars()
+            .any(|ch| ch.is_whitespace() || "()[]':;,".contains(ch));
+    if needs_quotes {
+        format!("'{}'", label.replace('\'', "''"))
+    } else {
+        label.to_string()
+    }
+}
+
+/// Compute a histogram of all branch lengths in a Newick string
+///
+/// The range between the smallest and the largest branch length is split into
+/// `n_bins` bins of equal width. The last bin includes its right edge.
+/// If all branch lengths are equal, a single bin is returned.
+///
+/// # Returns
+///
+/// A tuple of bin edges (of length `n_bins + 1`) and counts (of length `n_bins`)
+///
+/// # Panics
+///
+/// Panics if `n_bins` is 0
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::branch_length_histogram;
+///
+/// let newick = "((0:0.5,1:1.0)3:1.0,2:2.5)4:4.5;";
+/// let (edges, counts) = branch_length_histogram(newick, 2);
+/// assert_eq!(edges, vec![0.5, 2.5, 4.5]);
+/// assert_eq!(counts, vec![3, 2]);
+/// ```
+#[cfg(feature = "newick")]
+pub fn branch_length_histogram(newick: &str, n_bins: usize) -> (Vec<f32>, Vec<usize>) {
+    assert!(n_bins > 0, "The number of bins must be positive");
+
+    let newick_patterns = NewickPatterns::get();
+    let bls: Vec<f32> = newick_patterns
+        .branch_lengths
+        .find_iter(newick)
+        .map(|m| m.as_str()[1..].parse::<f32>().unwrap())
+        .collect();
+
+    if bls.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    let min = bls.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = bls.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+    // Degenerate case: all branch lengths are equal
+    if min == max {
+        return (vec![min, max], vec![bls.len()]);
+    }
+
+    let width = (max - min) / n_bins as f32;
+    let edges: Vec<f32> = (0..=n_bins).map(|i| min + i as f32 * width).collect();
+
+    let mut counts = vec![0; n_bins];
+    for bl in bls {
+        let idx = (((bl - min) / width) as usize).min(n_bins - 1);
+        counts[idx] += 1;
+    }
+
+    (edges, counts)
+}
+
+/// Internal node labels keyed by clade (smallest leaf, number of leaves)
+pub type CladeLabels = HashMap<(usize, usize), String>;
+
+/// Remove the internal node labels of a Newick string with integer leaves
+///
+/// Each internal node is identified by its clade, as a tuple of its
+/// smallest leaf and its number of leaves. This is enough to tell clades
+/// apart: two clades sharing a leaf are nested, so they differ in size.
+///
+/// # Returns
+///
+/// A tuple of the Newick string without internal labels (branch lengths are
+/// kept) and the non-empty internal labels, keyed by clade
+///
+/// # Errors
+///
+/// Returns `NewickError::ParseIntError` if a leaf label is not an integer
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::split_internal_labels;
+///
+/// let (newick, labels) = split_internal_labels("((0,2)0.9,(1,3))root;").unwrap();
+/// assert_eq!(newick, "((0,2),(1,3));");
+/// assert_eq!(labels[&(0, 2)], "0.9");
+/// assert_eq!(labels[&(0, 4)], "root");
+/// assert_eq!(labels.len(), 2);
+/// ```
+pub fn split_internal_labels(newick: &str) -> Result<(String, CladeLabels), NewickError> {
+    let mut result = String::with_capacity(newick.len());
+    let mut labels = HashMap::new();
+
+    // (smallest leaf, number of leaves) of the clades being read
+    let mut stack: Vec<(usize, usize)> = vec![(usize::MAX, 0)];
+    // Last closed clade, labelled by the text following its closing parenthesis
+    let mut closed = (usize::MAX, 0);
+    let mut previous = None;
+
+    for token in _tokenize(newick) {
+        let clade = match (token.kind, previous) {
+            (TokenKind::Open, _) => {
+                stack.push((usize::MAX, 0));
+                None
+            }
+            (TokenKind::Close, _) => {
+                closed = stack.pop().unwrap_or((usize::MAX, 0));
+                Some(closed)
+            }
+            (TokenKind::Text, Some(TokenKind::Open | TokenKind::Comma)) => {
+                let label = _unquote(token.text);
+                if label.is_empty() {
+                    None
+                } else {
+                    let leaf = label.parse::<usize>()?;
+                    result.push_str(&label);
+                    Some((leaf, 1))
+                }
+            }
+            (TokenKind::Text, Some(TokenKind::Close)) => {
+                let label = _unquote(token.text);
+                if !label.is_empty() {
+                    labels.insert(closed, label.into_owned());
+                }
+                None
+            }
+            _ => None,
+        };
+
+        // Labels are written above, branch lengths and the rest as is
+        let is_label = matches!(
+            previous,
+            Some(TokenKind::Open | TokenKind::Comma | TokenKind::Close)
+        );
+        if token.kind != TokenKind::Text || !is_label {
+            result.push_str(token.text);
+        }
+        if token.kind != TokenKind::Comment {
+            previous = Some(token.kind);
+        }
+        if let (Some(clade), Some(top)) = (clade, stack.last_mut()) {
+            *top = (top.0.min(clade.0), top.1 + clade.1);
+        }
+    }
+
+    Ok((result, labels))
+}
+
+/// Relabel the leaves of a Newick string with integers
+///
+/// Leaves are numbered 0, 1, 2, ... in their order of appearance in the
+/// string, and internal node labels are removed. Branch lengths are kept.
+///
+/// # Returns
+///
+/// A tuple of the integer-labelled Newick string and the original leaf
+/// names, such that `taxa[i]` is the name of leaf `i`
+///
+/// # Errors
+///
+/// Returns `NewickError::DuplicateTaxon` if two leaves have the same label
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::{create_label_mapping, NewickError};
+///
+/// let (newick, taxa) = create_label_mapping("((a:0.5,b:0.6)ab:0.7,c:0.8);").unwrap();
+/// assert_eq!(newick, "((0:0.5,1:0.6):0.7,2:0.8);");
+/// assert_eq!(taxa, vec!["a", "b", "c"]);
+///
+/// assert_eq!(
+///     create_label_mapping("((a,a),c);"),
+///     Err(NewickError::DuplicateTaxon("a".to_string()))
+/// );
+/// ```
+pub fn create_label_mapping(newick: &str) -> Result<(String, Vec<String>), NewickError> {
+    let mut taxa: Vec<String> = Vec::new();
+    let relabelled = _relabel_leaves(newick, |label| {
+        taxa.push(label);
+        taxa.len() - 1
+    });
+
+    let mut seen: HashSet<&str> = HashSet::with_capacity(taxa.len());
+    if let Some(name) = taxa.iter().find(|name| !seen.insert(name.as_str())) {
+        return Err(NewickError::DuplicateTaxon(name.clone()));
+    }
+
+    Ok((relabelled, taxa))
+}
+
+/// Get the leaf labels of a Newick string in their order of appearance
+///
+/// Internal node labels are ignored and quoted labels are unquoted.
+/// This can be used to check that a tree is labelled with the integers
+/// 0..n-1 before calling `to_vector`.
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::get_leaf_labels;
+///
+/// assert_eq!(get_leaf_labels("((0,2)3,1)4;"), vec!["0", "2", "1"]);
+/// assert_eq!(get_leaf_labels("(('x y':0.5,b)ab,c);"), vec!["x y", "b", "c"]);
+/// ```
+pub fn get_leaf_labels(newick: &str) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    _relabel_leaves(newick, |label| {
+        labels.push(label);
+        labels.len() - 1
+    });
+    labels
+}
+
+/// Replace each leaf label of a Newick string by the integer returned by `relabel`
+///
+/// `relabel` is called on the leaf labels in their order of appearance.
+/// Internal node labels are removed and branch lengths are kept.
+pub(crate) fn _relabel_leaves(newick: &str, mut relabel: impl FnMut(String) -> usize) -> String {
+    let mut relabelled = String::with_capacity(newick.len());
+    let mut previous = None;
+
+    for token in _tokenize(newick) {
+        match (token.kind, previous) {
+            // A leaf label can follow an opening parenthesis or a comma
+            (TokenKind::Text, Some(TokenKind::Open | TokenKind::Comma)) => {
+                let label = _unquote(token.text);
+                if !label.is_empty() {
+                    relabelled.push_str(&relabel(label.into_owned()).to_string());
+                }
+            }
+            // An internal node label can follow a closing parenthesis: drop it
+            (TokenKind::Text, Some(TokenKind::Close)) => {}
+            _ => relabelled.push_str(token.text),
+        }
+        if token.kind != TokenKind::Comment {
+            previous = Some(token.kind);
+        }
+    }
+
+    relabelled
+}
+
+/// Relabel the leaves of a Newick string using a shared taxon table
+///
+/// The taxa of the tree are added to `table` if they are not in it yet.
+/// Leaves are then numbered 0..n-1 by increasing table id, so that the
+/// relative order of the taxa is the same across all trees sharing the table.
+/// In particular, if all trees have the same taxa, leaf `i` is the taxon
+/// with id `i` in the table.
+///
+/// # Errors
+///
+/// Returns a `NewickError` if a taxon appears more than once in the tree.
+/// The table is not modified in that case.
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::{relabel_with_table, TaxonTable};
+///
+/// let mut table = TaxonTable::new();
+/// assert_eq!(relabel_with_table("((b,c),a);", &mut table).unwrap(), "((0,1),2);");
+/// assert_eq!(relabel_with_table("((a,b),c);", &mut table).unwrap(), "((2,0),1);");
+/// assert_eq!(table.names(), &["b", "c", "a"]);
+/// ```
+pub fn relabel_with_table(newick: &str, table: &mut TaxonTable) -> Result<String, NewickError> {
+    // Check for duplicates before modifying the table
+    let (_, taxa) = create_label_mapping(newick)?;
+
+    let ids: Vec<usize> = taxa.iter().map(|name| table.get_or_insert(name)).collect();
+
+    // Rank of each table id among the taxa of the tree
+    let mut sorted_ids = ids.clone();
+    sorted_ids.sort_unstable();
+    let ranks: HashMap<String, usize> = taxa
+        .into_iter()
+        .zip(ids.iter())
+        .map(|(name, id)| (name, sorted_ids.binary_search(id).unwrap()))
+        .collect();
+
+    Ok(_relabel_leaves(newick, |label| ranks[&label]))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::tree_vec::ops::to_newick_from_vector;
+    use crate::tree_vec::ops::vector::leaf_depths;
     use crate::utils::sample_vector;
     use rstest::*;
 
+    #[rstest]
+    #[case(NewickError::ParseIntError("a".parse::<usize>().unwrap_err()), "Invalid node label: invalid digit found in string")]
+    #[case(NewickError::ParseFloatError("a".parse::<f32>().unwrap_err()), "Invalid branch length: invalid float literal")]
+    #[case(NewickError::NonFiniteBranchLength("inf".to_string()), "Invalid branch length: inf is not finite")]
+    #[case(NewickError::InvalidCherry("0,1,2".to_string()), "Invalid cherry: expected two children in '(0,1,2)'")]
+    #[case(NewickError::DuplicateTaxon("a".to_string()), "Duplicate taxon: a")]
+    #[case(
+        NewickError::UnterminatedComment(6),
+        "Unterminated comment starting at position 6"
+    )]
+    #[case(
+        NewickError::MissingSemicolon,
+        "Missing semicolon at the end of the Newick string"
+    )]
+    fn test_newick_error_display(#[case] error: NewickError, #[case] expected: &str) {
+        assert_eq!(error.to_string(), expected);
+    }
+
     #[rstest]
     #[case("(((0,(3,5)6)8,2)9,(1,4)7)10;", "(((0,(3,5)),2),(1,4));")]
     #[case("(0,(1,(2,(3,(4,5)6)7)8)9)10;", "(0,(1,(2,(3,(4,5)))));")]
     #[case("((0,2)5,(1,3)4)6;", "((0,2),(1,3));")]
+    #[case("((0:0.1,2:0.2)3:0.3,1:0.4)4:0.5;", "((0:0.1,2:0.2):0.3,1:0.4):0.5;")]
     fn test_remove_parent_labels(#[case] newick: &str, #[case] expected: &str) {
         let result = remove_parent_labels(&newick);
         assert_eq!(result, expected);
     }
 
+    #[rstest]
+    #[case("((0:0.1,2:0.2)3:0.3,1:4e-1)4:0.5;", "((0,2)3,1)4;")]
+    #[case("(('a:b':1,c:[&x]2),d);", "(('a:b',c[&x]),d);")]
+    fn test_remove_branch_lengths(#[case] newick: &str, #[case] expected: &str) {
+        assert_eq!(remove_branch_lengths(newick), expected);
+    }
+
+    #[rstest]
+    #[case("((0,2)5,(1,3)4)6;", 1, "((1,3)6,(2,4)5)7;")]
+    #[case("((1,3),(2,4));", -1, "((0,2),(1,3));")]
+    #[case(
+        "((0:0.1,2:10)3:0.3,1:0.4)4:0.5;",
+        1,
+        "((1:0.1,3:10)4:0.3,2:0.4)5:0.5;"
+    )]
+    #[case("((a,b)x,c);", 1, "((a,b)x,c);")]
+    #[case("0;", 1, "1;")]
+    #[case("('1,2':0.5,[0]3);", 1, "('1,2':0.5,[0]4);")]
+    fn test_shift_labels(#[case] newick: &str, #[case] offset: isize, #[case] expected: &str) {
+        assert_eq!(shift_labels(newick, offset), expected);
+        assert_eq!(shift_labels(expected, -offset), newick);
+    }
+
+    #[rstest]
+    #[case("0;", 0)]
+    #[case("(0,1);", 1)]
+    #[case("((0,2)5,(1,3)4)6;", 2)]
+    #[case("(0,(1,(2,3)));", 3)]
+    #[case("((0[&a(b)],1)[(((],2);", 2)]
+    #[case("(('a(b',1),'c)''d');", 2)]
+    fn test_max_nesting_depth(#[case] newick: &str, #[case] expected: usize) {
+        assert_eq!(max_nesting_depth(newick), expected);
+    }
+
+    /// Test that the nesting depth is the height of the tree
+    #[rstest]
+    #[case(10, false)]
+    #[case(100, false)]
+    #[case(100, true)]
+    fn test_max_nesting_depth_sampled(#[case] n_leaves: usize, #[case] ordering: bool) {
+        let v = sample_vector(n_leaves, ordering);
+        let height = *leaf_depths(&v).iter().max().unwrap();
+        let newick = to_newick_from_vector(&v);
+
+        assert_eq!(max_nesting_depth(&newick), height);
+        assert_eq!(max_nesting_depth(&remove_parent_labels(&newick)), height);
+    }
+
     #[rstest]
     #[case(10)]
     #[case(100)]
@@ -411,6 +1345,7 @@ mod tests {
     #[case(10)]
     #[case(100)]
     #[case(1000)]
+    #[cfg(feature = "newick")]
     fn test_find_num_leaves(#[case] n_leaves: usize) {
         let v = sample_vector(n_leaves, false);
         let newick = to_newick_from_vector(&v);
@@ -420,8 +1355,55 @@ mod tests {
     }
 
     #[rstest]
-    #[case("((1:0.5,2:0.7)1:0.9,3:0.8)2:0.8;", vec![[1, 2, 1], [1, 3, 2]], vec![[0.5, 0.7], [0.9, 0.8]])]
-    #[case("(1:0.5,2:0.7);", vec![[1, 2, 2]], vec![[0.5, 0.7]] )]
+    #[case("(('0','1'),2);", 3)]
+    #[case("(('a,1',2),'3');", 2)]
+    #[case("(('x'',4',2)5,'3')6;", 2)]
+    #[cfg(feature = "newick")]
+    fn test_find_num_leaves_quoted(#[case] newick: &str, #[case] expected: usize) {
+        assert_eq!(find_num_leaves(newick), expected);
+    }
+
+    /// Test that quoted integer labels are parsed like unquoted ones
+    #[rstest]
+    #[case("(('0','2')'3',1)4;")]
+    #[case("(('0':0.1,'2':0.2)'3':0.3,1:0.4)4;")]
+    #[case("(('0','2'),'1');")]
+    fn test_get_cherries_quoted(#[case] newick: &str) {
+        let unquoted = newick.replace('\'', "");
+        assert_eq!(has_parents(newick), has_parents(&unquoted));
+        let get_cherries = if has_parents(newick) {
+            try_get_cherries
+        } else {
+            try_get_cherries_no_parents
+        };
+        assert_eq!(
+            get_cherries(&remove_branch_lengths(newick)),
+            get_cherries(&remove_branch_lengths(&unquoted))
+        );
+        assert_eq!(
+            get_cherries_with_optional_bls(newick),
+            get_cherries_with_optional_bls(&unquoted)
+        );
+    }
+
+    #[rstest]
+    #[case("(('0,1),2);")]
+    #[case("(('',1),2);")]
+    fn test_get_cherries_quoted_invalid(#[case] newick: &str) {
+        assert!(try_get_cherries_no_parents(newick).is_err());
+    }
+
+    #[rstest]
+    #[case("((0:0.5,1:0.7)3:0.9,2:0.8)4:0.8;", vec![[0, 1, 3], [3, 2, 4]], vec![[0.5, 0.7], [0.9, 0.8]])]
+    #[case("(0:0.5,1:0.7);", vec![[0, 1, 1]], vec![[0.5, 0.7]] )]
+    #[case("((0:0.1,2:0.2)3:0.3,1:0.4)4;", vec![[0, 2, 3], [3, 1, 4]], vec![[0.1, 0.2], [0.3, 0.4]])]
+    #[case("(0:1e-1,1:2.5)2;", vec![[0, 1, 2]], vec![[0.1, 2.5]])]
+    #[case("(0:1.0e-2,1:3.4E+1);", vec![[0, 1, 1]], vec![[0.01, 34.0]])]
+    // The semicolon is optional, also when the root has no branch length
+    #[case("(0:0.7,(1:0.5,2:0.8)3:0.6)4", vec![[1, 2, 3], [0, 3, 4]], vec![[0.5, 0.8], [0.7, 0.6]])]
+    // Comments (e.g., BEAST or NHX annotations) are ignored
+    #[case("((0:0.1[&rate=0.5],1:0.2):0.3,2:0.4);", vec![[0, 1, 1], [0, 2, 2]], vec![[0.1, 0.2], [0.3, 0.4]])]
+    #[case("[&R] ((0:0.1[&&NHX:S=human],2:0.2)3:0.3[&&NHX:B=90],1:0.4)4;", vec![[0, 2, 3], [3, 1, 4]], vec![[0.1, 0.2], [0.3, 0.4]])]
     fn test_get_cherries_with_bls(
         #[case] newick: &str,
         #[case] expected_ancestry: Vec<[usize; 3]>,
@@ -430,9 +1412,9 @@ mod tests {
         let ancestry: Ancestry;
         let bls: Vec<[f32; 2]>;
         if has_parents(newick) {
-            (ancestry, bls) = get_cherries_with_bls(newick);
+            (ancestry, bls) = get_cherries_with_bls(newick).unwrap();
         } else {
-            (ancestry, bls) = get_cherries_no_parents_with_bls(newick);
+            (ancestry, bls) = get_cherries_no_parents_with_bls(newick).unwrap();
         }
 
         // Verify the ancestry
@@ -442,4 +1424,170 @@ mod tests {
         assert_eq!(bls.len(), expected_bls.len()); // Ensure the number of branch lengths is correct
         assert_eq!(bls, expected_bls); // Ensure branch lengths match the expected
     }
+
+    #[rstest]
+    #[case("((0:0.5,1:1.0)3:1.0,2:2.5)4:4.5;", 2, vec![0.5, 2.5, 4.5], vec![3, 2])]
+    #[case("((0:0.5,1:1.0)3:1.0,2:2.5)4:4.5;", 4, vec![0.5, 1.5, 2.5, 3.5, 4.5], vec![3, 0, 1, 1])]
+    #[case("((0:0.5,1:1.0),2:2.5);", 1, vec![0.5, 2.5], vec![3])]
+    #[case("((0:1.0,1:1.0)3:1.0,2:1.0)4;", 4, vec![1.0, 1.0], vec![4])]
+    #[case("((0:5e-1,1:1E0)3:1.0e+0,2:2.5)4;", 1, vec![0.5, 2.5], vec![4])]
+    #[case("(1:1.0e-2,2:3.4E+1);", 1, vec![0.01, 34.0], vec![2])]
+    #[case("((0,1)3,2)4;", 4, vec![], vec![])]
+    #[cfg(feature = "newick")]
+    fn test_branch_length_histogram(
+        #[case] newick: &str,
+        #[case] n_bins: usize,
+        #[case] expected_edges: Vec<f32>,
+        #[case] expected_counts: Vec<usize>,
+    ) {
+        let (edges, counts) = branch_length_histogram(newick, n_bins);
+        assert_eq!(edges, expected_edges);
+        assert_eq!(counts, expected_counts);
+    }
+
+    #[rstest]
+    #[case("((a:0.5,b:0.6):0.7,(c:0.8,d:0.9):1.0);", "((0:0.5,1:0.6):0.7,(2:0.8,3:0.9):1.0);", vec!["a", "b", "c", "d"])]
+    #[case("((a,b)ab,c)root;", "((0,1),2);", vec!["a", "b", "c"])]
+    #[case("(('Homo sapiens':0.1,'Pan troglodytes':0.2):0.3,Gorilla:0.4);", "((0:0.1,1:0.2):0.3,2:0.4);", vec!["Homo sapiens", "Pan troglodytes", "Gorilla"])]
+    #[case("(c, (b, a));", "(0,(1,2));", vec!["c", "b", "a"])]
+    #[case("(('Homo sapiens, strain 1':0.1,'O''Brien'),'(c)');", "((0:0.1,1),2);", vec!["Homo sapiens, strain 1", "O'Brien", "(c)"])]
+    fn test_create_label_mapping(
+        #[case] newick: &str,
+        #[case] expected_newick: &str,
+        #[case] expected_taxa: Vec<&str>,
+    ) {
+        let (relabelled, taxa) = create_label_mapping(newick).unwrap();
+        assert_eq!(relabelled, expected_newick);
+        assert_eq!(taxa, expected_taxa);
+    }
+
+    #[rstest]
+    #[case("((a,a),c);", "a")]
+    #[case("(('x y':0.1,b),'x y');", "x y")]
+    #[case("(0,0)2;", "0")]
+    fn test_create_label_mapping_duplicate(#[case] newick: &str, #[case] expected: &str) {
+        assert_eq!(
+            create_label_mapping(newick),
+            Err(NewickError::DuplicateTaxon(expected.to_string()))
+        );
+    }
+
+    #[rstest]
+    #[case("(((0,(3,5)6)8,2)9,(1,4)7)10;", vec!["0", "3", "5", "2", "1", "4"])]
+    #[case("((1:0.5,2:0.6):0.7,(3:0.8,4:0.9):1.0);", vec!["1", "2", "3", "4"])]
+    #[case("((a,b)ab,c)root;", vec!["a", "b", "c"])]
+    #[case("(('O''Brien','a,b')x,c);", vec!["O'Brien", "a,b", "c"])]
+    #[case("", vec![])]
+    fn test_get_leaf_labels(#[case] newick: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(get_leaf_labels(newick), expected);
+    }
+
+    #[rstest]
+    #[case("(0:0.5,1)2;", vec![[0, 1, 2]], vec![[Some(0.5), None]])]
+    #[case("(0,1:0.5)2:0.1;", vec![[0, 1, 2]], vec![[None, Some(0.5)]])]
+    #[case("((0:0.1,2)3,1:0.4)4;", vec![[0, 2, 3], [3, 1, 4]], vec![[Some(0.1), None], [None, Some(0.4)]])]
+    #[case("((0,2)3:0.3,1)4;", vec![[0, 2, 3], [3, 1, 4]], vec![[None, None], [Some(0.3), None]])]
+    #[case("((0:0.1,2:0.2)3:0.3,1:0.4)4;", vec![[0, 2, 3], [3, 1, 4]], vec![[Some(0.1), Some(0.2)], [Some(0.3), Some(0.4)]])]
+    fn test_get_cherries_with_optional_bls(
+        #[case] newick: &str,
+        #[case] expected_ancestry: Ancestry,
+        #[case] expected_bls: Vec<[Option<f32>; 2]>,
+    ) {
+        let (ancestry, bls) = get_cherries_with_optional_bls(newick).unwrap();
+        assert_eq!(ancestry, expected_ancestry);
+        assert_eq!(bls, expected_bls);
+
+        // Missing branch lengths default to 0 in `get_cherries_with_bls`
+        let (ancestry_filled, bls_filled) = get_cherries_with_bls(newick).unwrap();
+        assert_eq!(ancestry_filled, expected_ancestry);
+        for (filled, optional) in bls_filled.iter().zip(expected_bls.iter()) {
+            assert_eq!(filled[0], optional[0].unwrap_or(0.0));
+            assert_eq!(filled[1], optional[1].unwrap_or(0.0));
+        }
+    }
+
+    #[rstest]
+    #[case("((0[a],2)3[b],1)4;", "((0[a],2)[b],1);")]
+    #[case(
+        "[&R] ((0,2)[&support=0.9]3,1[x])4;[end]",
+        "[&R] ((0,2)[&support=0.9],1[x]);[end]"
+    )]
+    fn test_get_cherries_comments(#[case] newick: &str, #[case] newick_no_parents: &str) {
+        assert_eq!(try_get_cherries(newick), Ok(vec![[0, 2, 3], [3, 1, 4]]));
+        assert_eq!(
+            try_get_cherries_no_parents(newick_no_parents),
+            Ok(vec![[0, 2, 2], [0, 1, 1]])
+        );
+    }
+
+    #[rstest]
+    fn test_get_cherries_unterminated_comment() {
+        let newick = "((0:0.1[&rate,2:0.2):0.3,1:0.4);";
+        assert_eq!(
+            get_cherries_no_parents_with_bls(newick),
+            Err(NewickError::UnterminatedComment(7))
+        );
+        assert_eq!(
+            try_get_cherries_no_parents(newick),
+            Err(NewickError::UnterminatedComment(7))
+        );
+    }
+
+    #[rstest]
+    #[case("((0:0.1,a:0.2)3:0.3,1:0.4)4;")]
+    #[case("((0:0.1,2:abc)3:0.3,1:0.4)4;")]
+    #[case("((0:0.1)3:0.3,1:0.4)4;")]
+    #[case("(((0:0.1,2:0.2)3:0.3,1:0.4)4;")]
+    fn test_get_cherries_with_bls_invalid(#[case] newick: &str) {
+        assert!(get_cherries_with_bls(newick).is_err());
+    }
+
+    // Test that the branch-length parsers validate labels like `try_get_cherries`
+    #[rstest]
+    #[case("(0,0)2;", NewickError::DuplicateNode(0))]
+    #[case("(0,5)2;", NewickError::ParentBelowChild { parent: 2, child: 5 })]
+    #[case("((0,1)2,3)4;", NewickError::NodeOutOfRange { node: 3, min: 0, max: 2 })]
+    #[case("((0,1)5,(2,3)5)6;", NewickError::DuplicateNode(5))]
+    fn test_get_cherries_with_bls_invalid_labels(
+        #[case] newick: &str,
+        #[case] expected: NewickError,
+    ) {
+        assert_eq!(get_cherries_with_bls(newick), Err(expected));
+        assert_eq!(
+            get_cherries_with_bls(newick).unwrap_err(),
+            try_get_cherries(newick).unwrap_err()
+        );
+    }
+
+    #[rstest]
+    #[case("(0,0);", NewickError::DuplicateNode(0))]
+    #[case("((0,3),1);", NewickError::NodeOutOfRange { node: 3, min: 0, max: 2 })]
+    fn test_get_cherries_no_parents_with_bls_invalid_labels(
+        #[case] newick: &str,
+        #[case] expected: NewickError,
+    ) {
+        assert_eq!(get_cherries_no_parents_with_bls(newick), Err(expected));
+        assert_eq!(
+            get_cherries_no_parents_with_bls(newick).unwrap_err(),
+            try_get_cherries_no_parents(newick).unwrap_err()
+        );
+    }
+
+    #[rstest]
+    #[case("((0:0.1,2:inf)3:0.3,1:0.4)4;", "inf")]
+    #[case("((0:0.1,2:0.2)3:-inf,1:0.4)4;", "-inf")]
+    #[case("((0:nan,2:0.2)3:0.3,1:0.4)4;", "nan")]
+    #[case("((0:0.1,2:0.2),1:NaN);", "NaN")]
+    #[case("((0:0.1,2:0.2),1:1e39);", "1e39")]
+    fn test_get_cherries_with_bls_non_finite(#[case] newick: &str, #[case] bl: &str) {
+        let result = if has_parents(newick) {
+            get_cherries_with_bls(newick)
+        } else {
+            get_cherries_no_parents_with_bls(newick)
+        };
+        match result {
+            Err(NewickError::NonFiniteBranchLength(s)) => assert_eq!(s, bl),
+            other => panic!("Expected a NonFiniteBranchLength error, got {:?}", other),
+        }
+    }
 }
@@ -1,15 +1,20 @@
+use std::sync::OnceLock;
+
 /// Represents the regular expressions used to parse Newick trees.
 ///
 /// This is essentially a holder for the various regular expressions
 /// used to parse newick trees such as the left node, right node, pairs,
-/// branch lengths, and parents.
+/// branch lengths, parents, and quoted labels.
+///
+/// Compiling the regexes is expensive, so prefer the shared instance
+/// returned by `NewickPatterns::get` over `NewickPatterns::new`.
 ///
 /// # Example
 ///
 /// ```
 /// use phylo2vec::tree_vec::ops::newick::NewickPatterns;
 ///
-/// let newick_patterns = NewickPatterns::new();
+/// let newick_patterns = NewickPatterns::get();
 /// let newick = "(1,2)3";
 /// let result = newick_patterns.pairs.is_match(newick);
 /// assert_eq!(result, true);
@@ -21,14 +26,16 @@ pub struct NewickPatterns {
     pub pairs: regex::Regex,
     pub branch_lengths: regex::Regex,
     pub parents: regex::Regex,
+    pub quoted_label: regex::Regex,
 }
 
 impl NewickPatterns {
     pub fn new() -> Self {
         let _left_node = r"\(\b(\d+)\b";
         let _right_node = r",\b(\d+)\b";
-        let _branch_lengths = r":\d+(\.\d+)?";
+        let _branch_lengths = r":[-+]?\d+(\.\d+)?([eE][-+]?\d+)?";
         let _parents = r"\)(\d+)";
+        let _quoted_label = r"'((?:[^']|'')*)'";
         let _pairs = format!(r"({})|({})", _left_node, _right_node);
         NewickPatterns {
             // Pattern of an integer label on the left of a pair
@@ -41,6 +48,40 @@ impl NewickPatterns {
             branch_lengths: regex::Regex::new(&_branch_lengths).unwrap(),
             // Pattern of a parent label
             parents: regex::Regex::new(&_parents).unwrap(),
+            // Pattern of a quoted label, where '' is an escaped quote
+            quoted_label: regex::Regex::new(_quoted_label).unwrap(),
         }
     }
+
+    /// Get the shared set of patterns, compiled once on first use
+    pub fn get() -> &'static Self {
+        static PATTERNS: OnceLock<NewickPatterns> = OnceLock::new();
+        PATTERNS.get_or_init(NewickPatterns::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("(1:1.0e-2,2:3.4E+1);", vec![":1.0e-2", ":3.4E+1"])]
+    #[case("((0:0.5,1:2)3:-1e3,2:+7)4;", vec![":0.5", ":2", ":-1e3", ":+7"])]
+    #[case("((0,1)3,2)4;", vec![])]
+    fn test_branch_lengths(#[case] newick: &str, #[case] expected: Vec<&str>) {
+        let newick_patterns = NewickPatterns::get();
+        let bls: Vec<&str> = newick_patterns
+            .branch_lengths
+            .find_iter(newick)
+            .map(|m| m.as_str())
+            .collect();
+        assert_eq!(bls, expected);
+    }
+
+    /// Test that the shared patterns are only compiled once
+    #[rstest]
+    fn test_get_shared() {
+        assert!(std::ptr::eq(NewickPatterns::get(), NewickPatterns::get()));
+    }
 }
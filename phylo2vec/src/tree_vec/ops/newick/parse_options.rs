@@ -0,0 +1,172 @@
+use crate::tree_vec::ops::newick::tokenizer::{_strip_whitespace, _tokenize, TokenKind};
+use crate::tree_vec::ops::newick::NewickError;
+
+/// Options for lenient Newick parsing
+///
+/// Each option enables a transformation applied to the Newick string before
+/// parsing. The default options enable all of them; use
+/// `NewickParseOptions::strict` to parse the string as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewickParseOptions {
+    /// Remove comments in square brackets, e.g. `[&R]`
+    pub strip_comments: bool,
+    /// Remove whitespace (spaces, tabs, newlines)
+    pub strip_whitespace: bool,
+    /// Add the trailing semicolon if it is missing
+    pub add_semicolon: bool,
+}
+
+impl Default for NewickParseOptions {
+    fn default() -> Self {
+        NewickParseOptions {
+            strip_comments: true,
+            strip_whitespace: true,
+            add_semicolon: true,
+        }
+    }
+}
+
+impl NewickParseOptions {
+    /// Options that leave the Newick string unchanged
+    pub fn strict() -> Self {
+        NewickParseOptions {
+            strip_comments: false,
+            strip_whitespace: false,
+            add_semicolon: false,
+        }
+    }
+}
+
+/// A non-fatal transformation applied to a Newick string during lenient parsing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// Comments in square brackets were removed
+    StrippedComments { count: usize },
+    /// Whitespace characters were removed
+    StrippedWhitespace { count: usize },
+    /// The trailing semicolon was missing and has been added
+    AddedSemicolon,
+}
+
+/// Apply the transformations enabled in `options` to a Newick string
+///
+/// # Returns
+///
+/// The normalized Newick string and a warning for each transformation
+/// that changed it
+///
+/// # Errors
+///
+/// Returns `NewickError::UnterminatedComment` if comments are stripped
+/// and a `[` is never closed
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::{normalize_newick, NewickParseOptions, ParseWarning};
+///
+/// let (newick, warnings) =
+///     normalize_newick("((0,1)[&R], 2)", &NewickParseOptions::default()).unwrap();
+/// assert_eq!(newick, "((0,1),2);");
+/// assert_eq!(
+///     warnings,
+///     vec![
+///         ParseWarning::StrippedComments { count: 1 },
+///         ParseWarning::StrippedWhitespace { count: 1 },
+///         ParseWarning::AddedSemicolon,
+///     ]
+/// );
+/// ```
+pub fn normalize_newick(
+    newick: &str,
+    options: &NewickParseOptions,
+) -> Result<(String, Vec<ParseWarning>), NewickError> {
+    let mut warnings = Vec::new();
+    let mut result = newick.to_string();
+
+    if options.strip_comments {
+        let mut stripped = String::with_capacity(result.len());
+        let mut count = 0;
+        for token in _tokenize(&result) {
+            match token.kind {
+                TokenKind::Comment if !token.text.ends_with(']') => {
+                    return Err(NewickError::UnterminatedComment(token.start));
+                }
+                TokenKind::Comment => count += 1,
+                _ => stripped.push_str(token.text),
+            }
+        }
+        if count > 0 {
+            warnings.push(ParseWarning::StrippedComments { count });
+            result = stripped;
+        }
+    }
+
+    if options.strip_whitespace {
+        let length = result.len();
+        // Whitespace inside quoted labels is part of the label
+        let mut stripped = String::with_capacity(length);
+        for token in _tokenize(&result) {
+            match token.kind {
+                TokenKind::Text => stripped.push_str(&_strip_whitespace(token.text)),
+                _ => stripped.push_str(token.text),
+            }
+        }
+        result = stripped;
+        // Whitespace characters are all ASCII in Newick strings
+        let count = length - result.len();
+        if count > 0 {
+            warnings.push(ParseWarning::StrippedWhitespace { count });
+        }
+    }
+
+    if options.add_semicolon && !result.ends_with(';') {
+        result.push(';');
+        warnings.push(ParseWarning::AddedSemicolon);
+    }
+
+    Ok((result, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("((0,1),2);", "((0,1),2);", vec![])]
+    #[case("((0,1),2)", "((0,1),2);", vec![ParseWarning::AddedSemicolon])]
+    #[case("(('a [b]',[c] c),'d e');", "(('a [b]',c),'d e');", vec![
+        ParseWarning::StrippedComments { count: 1 },
+        ParseWarning::StrippedWhitespace { count: 1 },
+    ])]
+    #[case("[&R] ((0[a],1)[b],\n\t2);", "((0,1),2);", vec![
+        ParseWarning::StrippedComments { count: 3 },
+        ParseWarning::StrippedWhitespace { count: 3 },
+    ])]
+    fn test_normalize_newick(
+        #[case] newick: &str,
+        #[case] expected: &str,
+        #[case] expected_warnings: Vec<ParseWarning>,
+    ) {
+        let (result, warnings) = normalize_newick(newick, &NewickParseOptions::default()).unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(warnings, expected_warnings);
+    }
+
+    #[rstest]
+    fn test_normalize_newick_strict() {
+        let newick = "[&R] ((0,1), 2)";
+        let (result, warnings) = normalize_newick(newick, &NewickParseOptions::strict()).unwrap();
+        assert_eq!(result, newick);
+        assert!(warnings.is_empty());
+    }
+
+    #[rstest]
+    fn test_normalize_newick_unterminated_comment() {
+        assert_eq!(
+            normalize_newick("((0,1)[abc,2);", &NewickParseOptions::default()),
+            Err(NewickError::UnterminatedComment(6))
+        );
+    }
+}
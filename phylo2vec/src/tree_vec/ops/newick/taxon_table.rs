@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+/// Bidirectional mapping between taxon names and integer ids
+///
+/// Ids are assigned in order of insertion (0, 1, 2, ...), so a table can be
+/// shared across many trees to give each taxon the same id in all of them.
+///
+/// # Example
+///
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::TaxonTable;
+///
+/// let mut table = TaxonTable::new();
+/// assert_eq!(table.get_or_insert("Homo sapiens"), 0);
+/// assert_eq!(table.get_or_insert("Pan troglodytes"), 1);
+/// assert_eq!(table.get_or_insert("Homo sapiens"), 0);
+/// assert_eq!(table.name(1), Some("Pan troglodytes"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaxonTable {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+}
+
+impl TaxonTable {
+    /// Creates an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the id of a taxon, inserting it with the next free id if needed
+    pub fn get_or_insert(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    /// Gets the id of a taxon, if it is in the table
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    /// Gets the name of the taxon with a given id, if any
+    pub fn name(&self, id: usize) -> Option<&str> {
+        self.names.get(id).map(String::as_str)
+    }
+
+    /// Gets the names of all taxa, indexed by id
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Gets the number of taxa in the table
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Checks whether the table is empty
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn test_taxon_table() {
+        let mut table = TaxonTable::new();
+        assert!(table.is_empty());
+
+        for (name, expected_id) in [("a", 0), ("b", 1), ("a", 0), ("c", 2), ("b", 1)] {
+            assert_eq!(table.get_or_insert(name), expected_id);
+        }
+
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.names(), &["a", "b", "c"]);
+        assert_eq!(table.id("c"), Some(2));
+        assert_eq!(table.id("d"), None);
+        assert_eq!(table.name(1), Some("b"));
+        assert_eq!(table.name(3), None);
+    }
+}
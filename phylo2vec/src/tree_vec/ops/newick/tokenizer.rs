@@ -0,0 +1,158 @@
+use std::borrow::Cow;
+
+/// Kind of a Newick token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    /// `(`
+    Open,
+    /// `)`
+    Close,
+    /// `,`
+    Comma,
+    /// `:`, followed by a branch length
+    Colon,
+    /// `;`
+    Semicolon,
+    /// A comment in square brackets, e.g. `[&R]`
+    Comment,
+    /// A node label or a branch length, possibly quoted and surrounded by whitespace
+    Text,
+}
+
+/// A token of a Newick string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Token<'a> {
+    pub kind: TokenKind,
+    /// The text of the token, including quotes and brackets
+    pub text: &'a str,
+    /// Byte position of the token in the Newick string
+    pub start: usize,
+}
+
+/// Iterator over the tokens of a Newick string, see `_tokenize`
+pub(crate) struct Tokens<'a> {
+    newick: &'a str,
+    pos: usize,
+}
+
+/// Split a Newick string into tokens
+///
+/// Quoted labels (`'...'`) may contain any character, including structural
+/// ones and brackets; a single quote is written as two (`''`). Comments run
+/// from `[` to the next `]`, or to the end of the string if unterminated.
+/// Concatenating the text of all tokens gives back the Newick string.
+pub(crate) fn _tokenize(newick: &str) -> Tokens<'_> {
+    Tokens { newick, pos: 0 }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let bytes = self.newick.as_bytes();
+        let start = self.pos;
+        let kind = match bytes.get(start)? {
+            b'(' => TokenKind::Open,
+            b')' => TokenKind::Close,
+            b',' => TokenKind::Comma,
+            b':' => TokenKind::Colon,
+            b';' => TokenKind::Semicolon,
+            b'[' => TokenKind::Comment,
+            _ => TokenKind::Text,
+        };
+
+        // All delimiters are ASCII, so `pos` always ends on a char boundary
+        self.pos += 1;
+        match kind {
+            TokenKind::Comment => {
+                self.pos = match self.newick[start..].find(']') {
+                    Some(end) => start + end + 1,
+                    None => bytes.len(),
+                };
+            }
+            TokenKind::Text => {
+                // A doubled quote toggles twice, so escaped quotes are handled too
+                let mut in_quotes = bytes[start] == b'\'';
+                while let Some(&byte) = bytes.get(self.pos) {
+                    match byte {
+                        b'\'' => in_quotes = !in_quotes,
+                        _ if in_quotes => {}
+                        b'(' | b')' | b',' | b':' | b';' | b'[' => break,
+                        _ => {}
+                    }
+                    self.pos += 1;
+                }
+            }
+            _ => {}
+        }
+
+        Some(Token {
+            kind,
+            text: &self.newick[start..self.pos],
+            start,
+        })
+    }
+}
+
+/// Get the value of a text token, without surrounding whitespace and quotes
+pub(crate) fn _unquote(text: &str) -> Cow<'_, str> {
+    let text = text.trim();
+    match text.strip_prefix('\'') {
+        Some(quoted) => {
+            let quoted = quoted.strip_suffix('\'').unwrap_or(quoted);
+            Cow::Owned(quoted.replace("''", "'"))
+        }
+        None => Cow::Borrowed(text),
+    }
+}
+
+/// Remove the whitespace of a text token outside its quotes
+pub(crate) fn _strip_whitespace(text: &str) -> Cow<'_, str> {
+    let text = text.trim();
+    if text.starts_with('\'') || !text.contains(char::is_whitespace) {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(text.split_whitespace().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("((0,1)2:0.5,3);", vec!["(", "(", "0", ",", "1", ")", "2", ":", "0.5", ",", "3", ")", ";"])]
+    #[case("('a,(b)':1,[&R c]d)", vec!["(", "'a,(b)'", ":", "1", ",", "[&R c]", "d", ")"])]
+    #[case("('it''s' x,y[z", vec!["(", "'it''s' x", ",", "y", "[z"])]
+    #[case("", vec![])]
+    fn test_tokenize(#[case] newick: &str, #[case] expected: Vec<&str>) {
+        let tokens: Vec<Token> = _tokenize(newick).collect();
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text).collect();
+        assert_eq!(texts, expected);
+        assert_eq!(texts.concat(), newick);
+        for token in tokens {
+            assert_eq!(
+                &newick[token.start..token.start + token.text.len()],
+                token.text
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(" a ", "a")]
+    #[case(" 'a b' ", "a b")]
+    #[case("'it''s'", "it's")]
+    #[case("'unterminated", "unterminated")]
+    fn test_unquote(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(_unquote(text), expected);
+    }
+
+    #[rstest]
+    #[case(" a b\n", "ab")]
+    #[case(" 'a b' ", "'a b'")]
+    #[case("\t", "")]
+    fn test_strip_whitespace(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(_strip_whitespace(text), expected);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::tree_vec::ops::vector::get_ancestry;
+use crate::tree_vec::TreeVec;
+
+/// Escape the special XML characters of a string
+fn _escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Convert a tree to a minimal NeXML document
+///
+/// The document contains an `<otus>` block with one `<otu>` per leaf
+/// (labelled by the taxa if present, by the leaf index otherwise) and a single
+/// `<tree xsi:type="nex:FloatTree">` made of `<node>` and `<edge>` elements.
+/// Node `n{i}` corresponds to node `i` of the ancestry. Edges carry a `length`
+/// attribute if the tree has branch lengths.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::TreeVec;
+/// use phylo2vec::tree_vec::ops::nexml::to_nexml;
+///
+/// let tree = TreeVec::new(vec![0, 0, 1], None, None);
+/// let nexml = to_nexml(&tree);
+/// assert_eq!(nexml.matches("<node ").count(), 7);
+/// assert_eq!(nexml.matches("<edge ").count(), 6);
+/// ```
+pub fn to_nexml(tree: &TreeVec) -> String {
+    let n_leaves = tree.data.len() + 1;
+    let ancestry = get_ancestry(&tree.data);
+    let root = 2 * n_leaves - 2;
+
+    let mut nexml = String::new();
+    nexml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    nexml.push_str(
+        "<nex:nexml xmlns:nex=\"http://www.nexml.org/2009\" \
+         xmlns=\"http://www.nexml.org/2009\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" version=\"0.9\">\n",
+    );
+
+    // Operational taxonomic units (one per leaf)
+    nexml.push_str("  <otus id=\"otus1\">\n");
+    for leaf in 0..n_leaves {
+        let label = match &tree.taxa {
+            Some(taxa) => _escape_xml(&taxa[leaf]),
+            None => leaf.to_string(),
+        };
+        nexml.push_str(&format!(
+            "    <otu id=\"otu{}\" label=\"{}\"/>\n",
+            leaf, label
+        ));
+    }
+    nexml.push_str("  </otus>\n");
+
+    nexml.push_str("  <trees id=\"trees1\" otus=\"otus1\">\n");
+    nexml.push_str("    <tree id=\"tree1\" xsi:type=\"nex:FloatTree\">\n");
+
+    // Nodes: leaves are linked to their OTU, the root is flagged if the tree is rooted
+    for node in 0..=root {
+        if node < n_leaves {
+            nexml.push_str(&format!(
+                "      <node id=\"n{}\" otu=\"otu{}\"/>\n",
+                node, node
+            ));
+        } else if node == root && tree.is_rooted {
+            nexml.push_str(&format!("      <node id=\"n{}\" root=\"true\"/>\n", node));
+        } else {
+            nexml.push_str(&format!("      <node id=\"n{}\"/>\n", node));
+        }
+    }
+
+    // Edges: one per child in the ancestry
+    for (i, &[c1, c2, p]) in ancestry.iter().enumerate() {
+        for (j, child) in [c1, c2].into_iter().enumerate() {
+            let length = match &tree.branch_lengths {
+                Some(bls) => {
+                    let bl = if j == 0 { bls[i].0 } else { bls[i].1 };
+                    format!(" length=\"{}\"", bl)
+                }
+                None => String::new(),
+            };
+            nexml.push_str(&format!(
+                "      <edge id=\"e{}\" source=\"n{}\" target=\"n{}\"{}/>\n",
+                child, p, child, length
+            ));
+        }
+    }
+
+    nexml.push_str("    </tree>\n");
+    nexml.push_str("  </trees>\n");
+    nexml.push_str("</nex:nexml>\n");
+
+    nexml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    /// Check that the tags of an XML document are balanced
+    fn is_well_formed(xml: &str) -> bool {
+        let mut stack: Vec<String> = Vec::new();
+        let mut rest = xml;
+
+        while let Some(start) = rest.find('<') {
+            let end = match rest[start..].find('>') {
+                Some(end) => start + end,
+                None => return false,
+            };
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+
+            if tag.starts_with('?') || tag.ends_with('/') {
+                // Declaration or self-closing tag
+                continue;
+            }
+            let name = tag.split_whitespace().next().unwrap_or("");
+            if let Some(name) = name.strip_prefix('/') {
+                if stack.pop().as_deref() != Some(name) {
+                    return false;
+                }
+            } else {
+                stack.push(name.to_string());
+            }
+        }
+
+        stack.is_empty()
+    }
+
+    #[rstest]
+    #[case(vec![0], None, None)]
+    #[case(vec![0, 0, 1], None, None)]
+    #[case(vec![0, 0, 1], Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]), Some(vec!["a", "b & c", "<d>", "e"]))]
+    #[case(vec![0, 0, 0, 1, 3, 3, 1, 4, 4], None, None)]
+    fn test_to_nexml(
+        #[case] v: Vec<usize>,
+        #[case] branch_lengths: Option<Vec<(f64, f64)>>,
+        #[case] taxa: Option<Vec<&str>>,
+    ) {
+        let n_leaves = v.len() + 1;
+        let has_bls = branch_lengths.is_some();
+        let taxa = taxa.map(|t| t.into_iter().map(String::from).collect());
+        let tree = TreeVec::new(v, branch_lengths, taxa);
+
+        let nexml = to_nexml(&tree);
+
+        assert!(is_well_formed(&nexml));
+        assert_eq!(nexml.matches("<otu ").count(), n_leaves);
+        assert_eq!(nexml.matches("<node ").count(), 2 * n_leaves - 1);
+        assert_eq!(nexml.matches("<edge ").count(), 2 * n_leaves - 2);
+        assert_eq!(nexml.matches("root=\"true\"").count(), 1);
+        let expected_lengths = if has_bls { 2 * n_leaves - 2 } else { 0 };
+        assert_eq!(nexml.matches("length=").count(), expected_lengths);
+    }
+
+    #[rstest]
+    fn test_to_nexml_labels_and_lengths() {
+        let taxa = vec!["a", "b & c", "<d>", "e"];
+        let tree = TreeVec::new(
+            vec![0, 0, 1],
+            Some(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)]),
+            Some(taxa.into_iter().map(String::from).collect()),
+        );
+
+        let nexml = to_nexml(&tree);
+
+        assert!(nexml.contains("<otu id=\"otu1\" label=\"b &amp; c\"/>"));
+        assert!(nexml.contains("<otu id=\"otu2\" label=\"&lt;d&gt;\"/>"));
+        // Ancestry of [0, 0, 1]: [[1, 3, 4], [0, 2, 5], [5, 4, 6]]
+        assert!(nexml.contains("<edge id=\"e1\" source=\"n4\" target=\"n1\" length=\"0.1\"/>"));
+        assert!(nexml.contains("<edge id=\"e4\" source=\"n6\" target=\"n4\" length=\"0.6\"/>"));
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::tree_vec::ops::newick::{
+    _relabel_leaves, _write_newick, get_cherries_no_parents_with_bls, normalize_newick,
+    quote_label, NewickError, NewickParseOptions,
+};
+use crate::tree_vec::ops::vector::{get_ancestry, to_vector_from_cherries};
+
+/// Quote a label if it contains characters with a special meaning in Nexus
+///
+/// Nexus has more punctuation than Newick (see `quote_label`), e.g. `=`, `*` or `-`.
+fn _quote_nexus(label: &str) -> String {
+    if label.chars().any(|ch| "{}/\\=*\"`+-<>~".contains(ch)) {
+        format!("'{}'", label.replace('\'', "''"))
+    } else {
+        quote_label(label)
+    }
+}
+
+/// Convert a Phylo2Vec vector to a Nexus document
+///
+/// The document contains a `TAXA` block with one label per leaf and a
+/// `TREES` block with a translate table and a single rooted tree. In the
+/// tree, leaf `i` is written as its translate index `i + 1` (Nexus indices
+/// start at 1). Leaf `i` is labelled `taxa[i]` if `taxa` is given,
+/// `t{i}` otherwise. Labels are quoted if needed.
+///
+/// # Panics
+///
+/// Panics if `taxa` does not have one name per leaf
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::nexus::to_nexus;
+///
+/// let nexus = to_nexus(&[0, 0, 1], None);
+/// assert!(nexus.starts_with("#NEXUS\n"));
+/// assert!(nexus.contains("TREE tree1 = [&R] ((1,3),(2,4));"));
+/// ```
+pub fn to_nexus(v: &[usize], taxa: Option<&[String]>) -> String {
+    let n_leaves = v.len() + 1;
+    if let Some(taxa) = taxa {
+        assert_eq!(
+            taxa.len(),
+            n_leaves,
+            "Expected one taxon per leaf: got {} taxa for {} leaves",
+            taxa.len(),
+            n_leaves
+        );
+    }
+
+    let labels: Vec<String> = (0..n_leaves)
+        .map(|leaf| match taxa {
+            Some(taxa) => _quote_nexus(&taxa[leaf]),
+            None => format!("t{}", leaf),
+        })
+        .collect();
+
+    let mut nexus = String::new();
+    nexus.push_str("#NEXUS\n\n");
+
+    nexus.push_str("BEGIN TAXA;\n");
+    nexus.push_str(&format!("    DIMENSIONS NTAX={};\n", n_leaves));
+    nexus.push_str("    TAXLABELS\n");
+    for label in labels.iter() {
+        nexus.push_str(&format!("        {}\n", label));
+    }
+    nexus.push_str("    ;\n");
+    nexus.push_str("END;\n\n");
+
+    nexus.push_str("BEGIN TREES;\n");
+    nexus.push_str("    TRANSLATE\n");
+    for (leaf, label) in labels.iter().enumerate() {
+        let separator = if leaf + 1 < n_leaves { "," } else { "" };
+        nexus.push_str(&format!("        {} {}{}\n", leaf + 1, label, separator));
+    }
+    nexus.push_str("    ;\n");
+
+    let tree = _write_newick(
+        &get_ancestry(v),
+        |newick, leaf| newick.push_str(&(leaf + 1).to_string()),
+        |_, _| {},
+    );
+    nexus.push_str(&format!("    TREE tree1 = [&R] {}\n", tree));
+    nexus.push_str("END;\n");
+
+    nexus
+}
+
+// Split a Nexus document into statements (separated by semicolons outside quotes)
+fn _split_statements(nexus: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    for (i, ch) in nexus.char_indices() {
+        match ch {
+            '\'' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                statements.push(nexus[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    statements.push(nexus[start..].trim());
+    statements
+}
+
+// Split a Nexus statement into words. Commas are separate words and
+// quoted words are unquoted ('' being an escaped quote).
+fn _split_words(statement: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut chars = statement.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            ',' => words.push(",".to_string()),
+            '\'' => {
+                let mut word = String::new();
+                while let Some(ch) = chars.next() {
+                    if ch == '\'' {
+                        if chars.peek() != Some(&'\'') {
+                            break;
+                        }
+                        chars.next();
+                    }
+                    word.push(ch);
+                }
+                words.push(word);
+            }
+            ch if ch.is_whitespace() => {}
+            ch => {
+                let mut word = ch.to_string();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || ch == ',' || ch == '\'' {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                words.push(word);
+            }
+        }
+    }
+    words
+}
+
+/// Parse the first tree of a Nexus document
+///
+/// Reads the `TRANSLATE` table and the first `TREE` statement of the `TREES`
+/// block. Comments in square brackets (e.g. `[&R]`) are ignored, and
+/// keywords are case-insensitive. Leaves are numbered following the
+/// `TAXLABELS` of the `TAXA` block if present, then the translate table,
+/// then their order of appearance in the tree. Branch lengths and internal
+/// node labels are discarded.
+///
+/// # Returns
+///
+/// A tuple of the Phylo2Vec vector and the taxa, such that `taxa[i]`
+/// is the name of leaf `i`
+///
+/// # Errors
+///
+/// Returns `NewickError::InvalidNexus` if there is no tree, and other
+/// `NewickError`s if the tree cannot be parsed or has duplicate taxa
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::nexus::from_nexus;
+///
+/// let nexus = "#NEXUS
+/// begin trees;
+///     translate 1 a, 2 b, 3 c, 4 'd e';
+///     tree one = [&R] ((1,3),(2,4));
+/// end;";
+/// let (v, taxa) = from_nexus(nexus).unwrap();
+/// assert_eq!(v, vec![0, 0, 1]);
+/// assert_eq!(taxa, vec!["a", "b", "c", "d e"]);
+/// ```
+pub fn from_nexus(nexus: &str) -> Result<(Vec<usize>, Vec<String>), NewickError> {
+    let comments_only = NewickParseOptions {
+        strip_comments: true,
+        ..NewickParseOptions::strict()
+    };
+    let (nexus, _) = normalize_newick(nexus, &comments_only)?;
+
+    // The #NEXUS header is not terminated by a semicolon
+    let nexus = nexus.trim_start();
+    let nexus = match nexus.get(..6) {
+        Some(header) if header.eq_ignore_ascii_case("#NEXUS") => &nexus[6..],
+        _ => nexus,
+    };
+
+    let mut block = String::new();
+    let mut taxlabels: Vec<String> = Vec::new();
+    let mut translate: Vec<(String, String)> = Vec::new();
+    let mut tree: Option<&str> = None;
+
+    for statement in _split_statements(nexus) {
+        let words = _split_words(statement);
+        let keyword = match words.first() {
+            Some(word) => word.to_uppercase(),
+            None => continue,
+        };
+        match (keyword.as_str(), block.as_str()) {
+            ("BEGIN", _) => block = words.get(1).map_or(String::new(), |w| w.to_uppercase()),
+            ("END" | "ENDBLOCK", _) => block.clear(),
+            ("TAXLABELS", "TAXA" | "DATA") => taxlabels = words[1..].to_vec(),
+            ("TRANSLATE", "TREES") => {
+                for entry in words[1..].split(|word| word == ",") {
+                    match entry {
+                        [key, label] => translate.push((key.clone(), label.clone())),
+                        _ => {
+                            return Err(NewickError::InvalidNexus(format!(
+                                "invalid translate entry '{}'",
+                                entry.join(" ")
+                            )))
+                        }
+                    }
+                }
+            }
+            ("TREE" | "UTREE", "TREES") => {
+                let (_, newick) = statement.split_once('=').ok_or_else(|| {
+                    NewickError::InvalidNexus(format!("missing '=' in '{}'", statement))
+                })?;
+                tree = Some(newick);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let tree = tree.ok_or_else(|| NewickError::InvalidNexus("no tree found".to_string()))?;
+    let (newick, _) = normalize_newick(tree, &NewickParseOptions::default())?;
+
+    // Taxon name of each leaf, in order of appearance
+    let names: HashMap<&str, &str> = translate
+        .iter()
+        .map(|(key, label)| (key.as_str(), label.as_str()))
+        .collect();
+    let mut leaves: Vec<String> = Vec::new();
+    _relabel_leaves(&newick, |label| {
+        leaves.push(
+            names
+                .get(label.as_str())
+                .map_or(label, |name| name.to_string()),
+        );
+        0
+    });
+
+    let mut seen: HashSet<&str> = HashSet::with_capacity(leaves.len());
+    if let Some(name) = leaves.iter().find(|name| !seen.insert(name.as_str())) {
+        return Err(NewickError::DuplicateTaxon(name.clone()));
+    }
+
+    // Order the taxa of the tree as declared, then by appearance
+    let taxa: Vec<String> = taxlabels
+        .into_iter()
+        .chain(translate.into_iter().map(|(_, label)| label))
+        .chain(leaves.iter().cloned())
+        .filter(|name| seen.remove(name.as_str()))
+        .collect();
+    let ids: HashMap<&str, usize> = taxa
+        .iter()
+        .enumerate()
+        .map(|(id, name)| (name.as_str(), id))
+        .collect();
+
+    let mut leaves = leaves.iter();
+    let newick_int = _relabel_leaves(&newick, |_| ids[leaves.next().unwrap().as_str()]);
+    let (ancestry, _) = get_cherries_no_parents_with_bls(&newick_int)?;
+
+    Ok((to_vector_from_cherries(ancestry, false), taxa))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_vec::ops::newick::shift_labels;
+    use crate::tree_vec::ops::{to_newick_no_labels, to_vector, to_vector_with_base};
+    use crate::utils::sample_vector;
+    use rstest::*;
+
+    #[rstest]
+    fn test_to_nexus() {
+        let taxa = ["a", "Homo sapiens", "b-c", "O'Brien"].map(String::from);
+        let expected = "#NEXUS
+
+BEGIN TAXA;
+    DIMENSIONS NTAX=4;
+    TAXLABELS
+        a
+        'Homo sapiens'
+        'b-c'
+        'O''Brien'
+    ;
+END;
+
+BEGIN TREES;
+    TRANSLATE
+        1 a,
+        2 'Homo sapiens',
+        3 'b-c',
+        4 'O''Brien'
+    ;
+    TREE tree1 = [&R] ((1,3),(2,4));
+END;
+";
+        assert_eq!(to_nexus(&[0, 0, 1], Some(&taxa)), expected);
+    }
+
+    /// Test that the tree line is the 1-based Newick string of the vector
+    #[rstest]
+    #[case(2)]
+    #[case(10)]
+    #[case(100)]
+    fn test_to_nexus_tree(#[case] n_leaves: usize) {
+        let v = sample_vector(n_leaves, false);
+        let nexus = to_nexus(&v, None);
+
+        let tree = nexus
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("TREE tree1 = [&R] "))
+            .unwrap();
+        assert_eq!(tree, shift_labels(&to_newick_no_labels(&v), 1));
+        assert_eq!(to_vector_with_base(tree, true), v);
+
+        assert!(nexus.contains(&format!("DIMENSIONS NTAX={};", n_leaves)));
+        assert!(nexus.contains(&format!("        {} t{}\n", n_leaves, n_leaves - 1)));
+    }
+
+    #[rstest]
+    #[should_panic(expected = "Expected one taxon per leaf")]
+    fn test_to_nexus_wrong_taxa() {
+        to_nexus(&[0, 0, 1], Some(&["a".to_string()]));
+    }
+
+    /// Test that exported documents are parsed back
+    #[rstest]
+    #[case(2)]
+    #[case(10)]
+    #[case(100)]
+    fn test_from_nexus_round_trip(#[case] n_leaves: usize) {
+        let v = sample_vector(n_leaves, false);
+        let taxa: Vec<String> = (0..n_leaves).map(|i| format!("taxon {}", i)).collect();
+
+        assert_eq!(
+            from_nexus(&to_nexus(&v, Some(&taxa))),
+            Ok((v.clone(), taxa))
+        );
+
+        let (v2, taxa2) = from_nexus(&to_nexus(&v, None)).unwrap();
+        assert_eq!(v2, v);
+        assert_eq!(
+            taxa2,
+            (0..n_leaves).map(|i| format!("t{}", i)).collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "#nexus\nbegin taxa; taxlabels c b a; end;\nbegin trees;\ntree t1 = [&U] ((a:0.1,b:0.2)0.9:0.3,c:0.4);\nend;",
+        "((2,1),0);",
+        vec!["c", "b", "a"]
+    )]
+    #[case(
+        "#NEXUS [written by hand]\nBEGIN TREES;\n  Translate\n    1 'x;y',\n    2 z\n  ;\n  TREE * t1 = [&R] (2[&rate=0.5],1);\n  TREE t2 = (1,2);\nEND;",
+        "(1,0);",
+        vec!["x;y", "z"]
+    )]
+    #[case(
+        "#NEXUS\nBEGIN TREES;\nUTREE t1 = ((a,b),(c,d));\nEND;",
+        "((0,1),(2,3));",
+        vec!["a", "b", "c", "d"]
+    )]
+    fn test_from_nexus(
+        #[case] nexus: &str,
+        #[case] newick: &str,
+        #[case] expected_taxa: Vec<&str>,
+    ) {
+        let (v, taxa) = from_nexus(nexus).unwrap();
+        assert_eq!(v, to_vector(newick));
+        assert_eq!(taxa, expected_taxa);
+    }
+
+    #[rstest]
+    #[case(
+        "#NEXUS\nBEGIN TAXA; TAXLABELS a b; END;",
+        "Invalid Nexus document: no tree found"
+    )]
+    #[case(
+        "#NEXUS\nBEGIN TREES; TREE t1 ((a,b),c); END;",
+        "Invalid Nexus document: missing '=' in 'TREE t1 ((a,b),c)'"
+    )]
+    #[case(
+        "#NEXUS\nBEGIN TREES; TRANSLATE 1 a b, 2 c; TREE t1 = (1,2); END;",
+        "Invalid Nexus document: invalid translate entry '1 a b'"
+    )]
+    #[case("#NEXUS\nBEGIN TREES; TREE t1 = ((a,b),a); END;", "Duplicate taxon: a")]
+    #[case(
+        "#NEXUS\nBEGIN TREES; TREE t1 = [&R ((a,b),c); END;",
+        "Unterminated comment starting at position 30"
+    )]
+    fn test_from_nexus_invalid(#[case] nexus: &str, #[case] expected: &str) {
+        assert_eq!(from_nexus(nexus).unwrap_err().to_string(), expected);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::tree_vec::ops::vector::{get_ancestry, leaf_depths};
+
+/// Compute the Sackin index of a tree, i.e. the sum of its leaf depths
+///
+/// Depths are counted in edges from the root (see `leaf_depths`).
+/// For a tree with n leaves, the index ranges from about n log2(n)
+/// for balanced trees to n(n+1)/2 - 1 for caterpillars.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::stats::sackin_index;
+///
+/// // ((0,2),(1,3)): all leaves at depth 2
+/// assert_eq!(sackin_index(&vec![0, 0, 1]), 8);
+/// // (0,(1,(2,3))): depths 1, 2, 3, 3
+/// assert_eq!(sackin_index(&vec![0, 1, 2]), 9);
+/// ```
+pub fn sackin_index(v: &[usize]) -> usize {
+    leaf_depths(v).iter().sum()
+}
+
+/// Compute the Colless index of a tree
+///
+/// The Colless index is the sum, over all internal nodes, of the absolute
+/// difference between the number of leaves below their two children.
+/// It is 0 for perfectly balanced trees and (n-1)(n-2)/2 for caterpillars.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::stats::colless_index;
+///
+/// // ((0,2),(1,3))
+/// assert_eq!(colless_index(&vec![0, 0, 1]), 0);
+/// // (0,(1,(2,3))): |1-3| + |1-2| + |1-1|
+/// assert_eq!(colless_index(&vec![0, 1, 2]), 3);
+/// ```
+pub fn colless_index(v: &[usize]) -> usize {
+    let n_leaves = v.len() + 1;
+    let mut n_descendants: Vec<usize> = vec![1; 2 * n_leaves - 1];
+
+    // Children always appear in earlier rows than their parents
+    let mut colless = 0;
+    for [c1, c2, p] in get_ancestry(v) {
+        colless += n_descendants[c1].abs_diff(n_descendants[c2]);
+        n_descendants[p] = n_descendants[c1] + n_descendants[c2];
+    }
+
+    colless
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_vec::ops::vector::descendant_bitsets;
+    use crate::utils::sample_vector;
+    use rstest::*;
+
+    #[rstest]
+    #[case(vec![0], 2, 0)]
+    #[case(vec![0, 0, 1], 8, 0)]
+    #[case(vec![0, 1, 2], 9, 3)]
+    // (((0,(3,5)),2),(1,4))
+    #[case(vec![0, 0, 0, 1, 3], 17, 5)]
+    fn test_balance_indices(
+        #[case] v: Vec<usize>,
+        #[case] expected_sackin: usize,
+        #[case] expected_colless: usize,
+    ) {
+        assert_eq!(sackin_index(&v), expected_sackin);
+        assert_eq!(colless_index(&v), expected_colless);
+    }
+
+    #[rstest]
+    #[case(2)]
+    #[case(10)]
+    #[case(100)]
+    fn test_balance_indices_caterpillar(#[case] n_leaves: usize) {
+        let v: Vec<usize> = (0..n_leaves - 1).collect();
+        assert_eq!(sackin_index(&v), n_leaves * (n_leaves + 1) / 2 - 1);
+        assert_eq!(colless_index(&v), (n_leaves - 1) * (n_leaves - 2) / 2);
+    }
+
+    #[rstest]
+    #[case(10)]
+    #[case(100)]
+    fn test_sackin_index_clade_sizes(#[case] n_leaves: usize) {
+        // Each leaf is counted once per internal node above it
+        let v = sample_vector(n_leaves, false);
+        let clade_sizes: u32 = descendant_bitsets(&v)
+            .iter()
+            .flatten()
+            .map(|block| block.count_ones())
+            .sum();
+        assert_eq!(sackin_index(&v), clade_sizes as usize);
+    }
+}
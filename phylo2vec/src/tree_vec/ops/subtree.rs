@@ -0,0 +1,400 @@
+use crate::tree_vec::ops::vector::{get_ancestry, to_vector_from_cherries};
+use crate::tree_vec::types::Ancestry;
+
+/// Get the Phylo2Vec vector of the tree induced by a set of leaves
+///
+/// Internal nodes left with a single child are suppressed. The leaves of the
+/// output are relabelled 0..k-1 by increasing original label: leaf i of the
+/// output corresponds to the i-th smallest element of `leaves`.
+/// Arbitrarily deep trees (e.g., caterpillars with 100k+ leaves) are
+/// supported, as the traversal does not recurse (see `visit_postorder`).
+///
+/// # Panics
+///
+/// Panics if `leaves` is empty, contains duplicates or out-of-range leaves
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::subtree::restrict_to_leaves;
+/// use phylo2vec::tree_vec::ops::to_vector;
+///
+/// // ((0,2)5,(1,3)4)6; restricted to 0, 1, 2 --> ((0,2),1);
+/// let v = restrict_to_leaves(&[0, 0, 1], &[0, 1, 2]);
+/// assert_eq!(v, to_vector("((0,2),1);"));
+/// ```
+pub fn restrict_to_leaves(v: &[usize], leaves: &[usize]) -> Vec<usize> {
+    let n_leaves = v.len() + 1;
+    assert!(!leaves.is_empty(), "At least one leaf must be kept");
+
+    // New label of each kept leaf (its rank among the kept leaves)
+    let mut sorted_leaves = leaves.to_vec();
+    sorted_leaves.sort_unstable();
+    let mut new_labels: Vec<Option<usize>> = vec![None; n_leaves];
+    for (rank, &leaf) in sorted_leaves.iter().enumerate() {
+        assert!(
+            leaf < n_leaves,
+            "Leaf {} is out of bounds (max = {})",
+            leaf,
+            n_leaves - 1
+        );
+        assert!(new_labels[leaf].is_none(), "Leaf {} is duplicated", leaf);
+        new_labels[leaf] = Some(rank);
+    }
+
+    let ancestry = get_ancestry(v);
+    _restrict_ancestry(&ancestry, 2 * n_leaves - 2, &new_labels)
+}
+
+/// Get the Phylo2Vec vector of the clade below a node
+///
+/// The leaves of the output are relabelled 0..k-1 by increasing original
+/// label. Extracting a leaf yields the empty vector (a single-leaf tree).
+/// Deep trees are supported, as in `restrict_to_leaves`.
+///
+/// # Panics
+///
+/// Panics if `node` is not a node of the tree
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::subtree::extract_subtree;
+/// use phylo2vec::tree_vec::ops::to_vector;
+///
+/// // (((0,(3,5)6)8,2)9,(1,4)7)10; --> clade 8 = (0,(3,5)) --> (0,(1,2));
+/// let v = extract_subtree(&[0, 0, 0, 1, 3], 8);
+/// assert_eq!(v, to_vector("(0,(1,2));"));
+/// ```
+pub fn extract_subtree(v: &[usize], node: usize) -> Vec<usize> {
+    let n_leaves = v.len() + 1;
+    let root = 2 * n_leaves - 2;
+    assert!(
+        node <= root,
+        "Node {} is out of bounds (max = {})",
+        node,
+        root
+    );
+
+    let ancestry = get_ancestry(v);
+
+    // Collect the leaves of the clade, then relabel them by increasing label
+    let mut is_in_clade = vec![false; n_leaves];
+    let mut stack = vec![node];
+    while let Some(current) = stack.pop() {
+        if current < n_leaves {
+            is_in_clade[current] = true;
+        } else {
+            let [c1, c2, _] = ancestry[current - n_leaves];
+            stack.push(c1);
+            stack.push(c2);
+        }
+    }
+
+    let mut new_labels: Vec<Option<usize>> = vec![None; n_leaves];
+    let mut rank = 0;
+    for (leaf, &in_clade) in is_in_clade.iter().enumerate() {
+        if in_clade {
+            new_labels[leaf] = Some(rank);
+            rank += 1;
+        }
+    }
+
+    _restrict_ancestry(&ancestry, node, &new_labels)
+}
+
+/// Replace a leaf of a tree by another tree
+///
+/// This is the converse of `restrict_to_leaves`. The leaves of `graft` are
+/// relabelled `at_leaf..at_leaf + k - 1`, where k is the number of leaves of
+/// `graft`, and the leaves of `host` above `at_leaf` are shifted up by k - 1.
+/// Grafting a single-leaf tree (the empty vector) leaves the host unchanged.
+///
+/// # Panics
+///
+/// Panics if `at_leaf` is not a leaf of `host`
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::subtree::graft;
+/// use phylo2vec::tree_vec::ops::to_vector;
+///
+/// // ((0,2),(1,3)) with (0,1) grafted at leaf 1 --> ((0,3),((1,2),4))
+/// let v = graft(&[0, 0, 1], &[0], 1);
+/// assert_eq!(v, to_vector("((0,3),((1,2),4));"));
+/// ```
+pub fn graft(host: &[usize], graft: &[usize], at_leaf: usize) -> Vec<usize> {
+    let n_host = host.len() + 1;
+    let n_graft = graft.len() + 1;
+    assert!(
+        at_leaf < n_host,
+        "Leaf {} is out of bounds (max = {})",
+        at_leaf,
+        n_host - 1
+    );
+    let n_leaves = n_host + n_graft - 1;
+
+    // Rows of the grafted tree come first, so that its internal nodes are
+    // n..n + k - 2 and the root of the host remains the last row
+    let graft_label = |node: usize| {
+        if node < n_graft {
+            at_leaf + node
+        } else {
+            n_leaves + node - n_graft
+        }
+    };
+    let graft_root = graft_label(2 * n_graft - 2);
+    let host_label = |node: usize| match node {
+        leaf if leaf < at_leaf => leaf,
+        leaf if leaf == at_leaf => graft_root,
+        leaf if leaf < n_host => leaf + n_graft - 1,
+        internal => n_leaves + n_graft - 1 + internal - n_host,
+    };
+
+    let ancestry: Ancestry = get_ancestry(graft)
+        .into_iter()
+        .map(|row| row.map(graft_label))
+        .chain(
+            get_ancestry(host)
+                .into_iter()
+                .map(|row| row.map(host_label)),
+        )
+        .collect();
+
+    _ancestry_to_vector(&ancestry)
+}
+
+// Build the vector of a tree whose internal node n + i is described by row i of
+// `ancestry`. Unlike `build_vector`, the internal node labels need not be canonical.
+pub(crate) fn _ancestry_to_vector(ancestry: &Ancestry) -> Vec<usize> {
+    let n_leaves = ancestry.len() + 1;
+    let new_labels: Vec<Option<usize>> = (0..n_leaves).map(Some).collect();
+    _restrict_ancestry(ancestry, 2 * n_leaves - 2, &new_labels)
+}
+
+// Build the vector of the tree below `node`, restricted to the leaves with a new label.
+//
+// The cherries are listed in post-order (first child first), in the same
+// format as `get_cherries_no_parents`: each cherry is [c1, c2, max(c1, c2)]
+// where c1 and c2 are the smallest (new) leaf labels below each child.
+fn _restrict_ancestry(
+    ancestry: &Ancestry,
+    node: usize,
+    new_labels: &[Option<usize>],
+) -> Vec<usize> {
+    let n_leaves = ancestry.len() + 1;
+
+    // Smallest kept leaf below each node (None if no leaf is kept)
+    let mut min_leaf: Vec<Option<usize>> = vec![None; 2 * n_leaves - 1];
+    let mut cherries: Ancestry = Vec::new();
+
+    // (node, whether its children have already been visited)
+    let mut stack: Vec<(usize, bool)> = vec![(node, false)];
+    while let Some((current, expanded)) = stack.pop() {
+        if current < n_leaves {
+            min_leaf[current] = new_labels[current];
+            continue;
+        }
+
+        let [c1, c2, _] = ancestry[current - n_leaves];
+        if !expanded {
+            stack.push((current, true));
+            stack.push((c2, false));
+            stack.push((c1, false));
+            continue;
+        }
+
+        min_leaf[current] = match (min_leaf[c1], min_leaf[c2]) {
+            (Some(l1), Some(l2)) => {
+                cherries.push([l1, l2, l1.max(l2)]);
+                Some(l1.min(l2))
+            }
+            // Nodes with a single kept child are suppressed
+            (Some(l), None) | (None, Some(l)) => Some(l),
+            (None, None) => None,
+        };
+    }
+
+    to_vector_from_cherries(cherries, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_vec::ops::to_vector;
+    use crate::utils::sample_vector;
+    use rand::seq::SliceRandom;
+    use rstest::*;
+
+    /// Build the Newick string (without parents) of an induced subtree recursively
+    fn induced_newick(
+        ancestry: &Ancestry,
+        node: usize,
+        new_labels: &[Option<usize>],
+    ) -> Option<String> {
+        let n_leaves = ancestry.len() + 1;
+        if node < n_leaves {
+            return new_labels[node].map(|label| label.to_string());
+        }
+        let [c1, c2, _] = ancestry[node - n_leaves];
+        match (
+            induced_newick(ancestry, c1, new_labels),
+            induced_newick(ancestry, c2, new_labels),
+        ) {
+            (Some(left), Some(right)) => Some(format!("({},{})", left, right)),
+            (Some(child), None) | (None, Some(child)) => Some(child),
+            (None, None) => None,
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], vec![0, 1, 2], "((0,2),1);")]
+    #[case(vec![0, 0, 1], vec![3, 1], "(0,1);")]
+    #[case(vec![0, 0, 0, 1, 3], vec![0, 2, 3, 4], "(((0,2),1),3);")]
+    #[case(vec![0, 0, 0, 1, 3], vec![5, 1, 4], "((0,1),2);")]
+    fn test_restrict_to_leaves(
+        #[case] v: Vec<usize>,
+        #[case] leaves: Vec<usize>,
+        #[case] expected_newick: &str,
+    ) {
+        assert_eq!(restrict_to_leaves(&v, &leaves), to_vector(expected_newick));
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], vec![2], vec![])]
+    #[case(vec![0, 0, 1], vec![0, 1, 2, 3], vec![0, 0, 1])]
+    fn test_restrict_to_leaves_edge_cases(
+        #[case] v: Vec<usize>,
+        #[case] leaves: Vec<usize>,
+        #[case] expected: Vec<usize>,
+    ) {
+        assert_eq!(restrict_to_leaves(&v, &leaves), expected);
+    }
+
+    /// Test against a recursive Newick-based implementation on random trees
+    #[rstest]
+    #[case(10, 3)]
+    #[case(50, 20)]
+    #[case(200, 199)]
+    #[case(200, 200)]
+    fn test_restrict_to_leaves_random(#[case] n_leaves: usize, #[case] n_kept: usize) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let v = sample_vector(n_leaves, false);
+            let mut leaves: Vec<usize> = (0..n_leaves).collect();
+            leaves.shuffle(&mut rng);
+            leaves.truncate(n_kept);
+
+            let mut sorted_leaves = leaves.clone();
+            sorted_leaves.sort();
+            let mut new_labels = vec![None; n_leaves];
+            for (rank, &leaf) in sorted_leaves.iter().enumerate() {
+                new_labels[leaf] = Some(rank);
+            }
+            let newick = induced_newick(&get_ancestry(&v), 2 * n_leaves - 2, &new_labels).unwrap();
+
+            assert_eq!(
+                restrict_to_leaves(&v, &leaves),
+                to_vector(&format!("{};", newick))
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], vec![])]
+    #[case(vec![0, 0, 1], vec![0, 0])]
+    #[case(vec![0, 0, 1], vec![4])]
+    #[should_panic]
+    fn test_restrict_to_leaves_invalid(#[case] v: Vec<usize>, #[case] leaves: Vec<usize>) {
+        restrict_to_leaves(&v, &leaves);
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 0, 1, 3], 8, "(0,(1,2));")]
+    #[case(vec![0, 0, 0, 1, 3], 7, "(0,1);")]
+    #[case(vec![0, 0, 0, 1, 3], 9, "((0,(2,3)),1);")]
+    fn test_extract_subtree(
+        #[case] v: Vec<usize>,
+        #[case] node: usize,
+        #[case] expected_newick: &str,
+    ) {
+        assert_eq!(extract_subtree(&v, node), to_vector(expected_newick));
+    }
+
+    #[rstest]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(100, true))]
+    fn test_extract_subtree_root_and_leaves(#[case] v: Vec<usize>) {
+        assert_eq!(extract_subtree(&v, 2 * v.len()), v);
+        for leaf in 0..=v.len() {
+            assert_eq!(extract_subtree(&v, leaf), Vec::<usize>::new());
+        }
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_extract_subtree_out_of_bounds() {
+        extract_subtree(&[0, 0, 1], 7);
+    }
+
+    #[rstest]
+    #[case(vec![0], vec![0], 0, "((0,1),2);")]
+    #[case(vec![0], vec![0], 1, "(0,(1,2));")]
+    #[case(vec![0, 0, 1], vec![0], 1, "((0,3),((1,2),4));")]
+    #[case(vec![0, 0, 1], vec![0, 1], 3, "((0,2),(1,(3,(4,5))));")]
+    #[case(vec![], vec![0, 0, 1], 0, "((0,2),(1,3));")]
+    #[case(vec![0, 0, 1], vec![], 2, "((0,2),(1,3));")]
+    fn test_graft(
+        #[case] host: Vec<usize>,
+        #[case] v_graft: Vec<usize>,
+        #[case] at_leaf: usize,
+        #[case] expected_newick: &str,
+    ) {
+        assert_eq!(graft(&host, &v_graft, at_leaf), to_vector(expected_newick));
+    }
+
+    /// Test that restricting a grafted tree recovers the host and the graft
+    #[rstest]
+    #[case(10, 5)]
+    #[case(50, 20)]
+    #[case(100, 1)]
+    fn test_graft_restrict(#[case] n_host: usize, #[case] n_graft: usize) {
+        let host = sample_vector(n_host, false);
+        let v_graft = sample_vector(n_graft, false);
+
+        for at_leaf in [0, n_host / 2, n_host - 1] {
+            let v = graft(&host, &v_graft, at_leaf);
+            assert_eq!(v.len(), n_host + n_graft - 2);
+
+            let grafted: Vec<usize> = (at_leaf..at_leaf + n_graft).collect();
+            assert_eq!(restrict_to_leaves(&v, &grafted), v_graft);
+
+            let host_leaves: Vec<usize> = (0..=at_leaf)
+                .chain(at_leaf + n_graft..n_host + n_graft - 1)
+                .collect();
+            assert_eq!(restrict_to_leaves(&v, &host_leaves), host);
+        }
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_graft_out_of_bounds() {
+        graft(&[0, 0, 1], &[0], 4);
+    }
+
+    /// Stress test on a deep caterpillar: (0,(1,(2,(...))))
+    ///
+    /// Node n + j is the clade of leaves n - 2 - j..n - 1.
+    #[rstest]
+    fn test_deep_caterpillar() {
+        let n_leaves = 100_000;
+        let v: Vec<usize> = (0..n_leaves - 1).collect();
+
+        let k = 50_000;
+        let node = n_leaves + k - 2;
+        let expected: Vec<usize> = (0..k - 1).collect();
+        assert_eq!(extract_subtree(&v, node), expected);
+
+        let even_leaves: Vec<usize> = (0..n_leaves).step_by(2).collect();
+        let expected: Vec<usize> = (0..even_leaves.len() - 1).collect();
+        assert_eq!(restrict_to_leaves(&v, &even_leaves), expected);
+    }
+}
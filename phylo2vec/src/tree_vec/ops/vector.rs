@@ -1,5 +1,10 @@
+use crate::errors::Phylo2VecError;
 use crate::tree_vec::ops::avl::AVLTree;
-use crate::tree_vec::types::{Ancestry, Pair, PairsVec};
+use crate::tree_vec::ops::lca::LcaIndex;
+use crate::tree_vec::ops::newick::{build_newick, remove_parent_labels};
+use crate::tree_vec::ops::subtree::_ancestry_to_vector;
+use crate::tree_vec::ops::to_vector;
+use crate::tree_vec::types::{Ancestry, LeafIndex, Pair, PairsVec};
 use crate::utils::is_unordered;
 use core::num;
 use std::collections::HashMap;
@@ -16,7 +21,7 @@ use std::usize;
 /// let v = vec![0, 0, 0, 1, 3, 3, 1, 4, 4];
 /// let pairs = get_pairs(&v);
 /// ```
-pub fn get_pairs(v: &Vec<usize>) -> PairsVec {
+pub fn get_pairs<T: LeafIndex>(v: &[T]) -> PairsVec {
     let num_of_leaves: usize = v.len();
     let mut pairs: PairsVec = Vec::with_capacity(num_of_leaves);
 
@@ -27,26 +32,28 @@ pub fn get_pairs(v: &Vec<usize>) -> PairsVec {
         The next pair to add is (v[i], next_leaf) as the branch leading to v[i]
         gives birth to next_leaf.
         */
+        let v_i = v[i].to_usize();
         let next_leaf: usize = i + 1;
-        let pair: Pair = (v[i], next_leaf);
-        if v[i] <= i {
+        let pair: Pair = (v_i, next_leaf);
+        if v_i <= i {
             pairs.push(pair);
         }
     }
 
     // Second loop
     for j in 1..num_of_leaves {
+        let v_j = v[j].to_usize();
         let next_leaf = j + 1;
-        if v[j] == 2 * j {
+        if v_j == 2 * j {
             // 2 * j = extra root ==> pairing = (0, next_leaf)
             let pair: Pair = (0, next_leaf);
             pairs.push(pair);
-        } else if v[j] > j {
+        } else if v_j > j {
             /*
             If v[j] > j, it's not the branch leading to v[j] that gives birth,
             but an internal branch. Insert at the calculated index.
             */
-            let index: usize = pairs.len() + v[j] - 2 * j;
+            let index: usize = pairs.len() + v_j - 2 * j;
             let new_pair: Pair = (pairs[index - 1].0, next_leaf);
             pairs.insert(index, new_pair);
         }
@@ -65,18 +72,19 @@ pub fn get_pairs(v: &Vec<usize>) -> PairsVec {
 /// let v = vec![0, 0, 0, 1, 3, 3, 1, 4, 4];
 /// let pairs = get_pairs_avl(&v);
 /// ```
-pub fn get_pairs_avl(v: &Vec<usize>) -> PairsVec {
+pub fn get_pairs_avl<T: LeafIndex>(v: &[T]) -> PairsVec {
     // AVL tree implementation of get_pairs
     let k = v.len();
     let mut avl_tree = AVLTree::new();
     avl_tree.insert(0, (0, 1));
 
     for i in 1..k {
+        let v_i = v[i].to_usize();
         let next_leaf = i + 1;
-        if v[i] <= i {
-            avl_tree.insert(0, (v[i], next_leaf));
+        if v_i <= i {
+            avl_tree.insert(0, (v_i, next_leaf));
         } else {
-            let index = v[i] - next_leaf;
+            let index = v_i - next_leaf;
             let pair = AVLTree::lookup(&avl_tree, index);
             avl_tree.insert(index + 1, (pair.0, next_leaf));
         }
@@ -101,7 +109,7 @@ pub fn get_pairs_avl(v: &Vec<usize>) -> PairsVec {
 /// The new branch yields leaf 2 (like in ordered trees)
 ///
 /// v[1] = 2 is somewhat similar: we create a new branch from R that yields leaf 2
-pub fn get_ancestry(v: &Vec<usize>) -> Ancestry {
+pub fn get_ancestry<T: LeafIndex>(v: &[T]) -> Ancestry {
     let pairs: PairsVec;
 
     // Determine the implementation to use
@@ -147,6 +155,43 @@ pub fn get_ancestry(v: &Vec<usize>) -> Ancestry {
     ancestry
 }
 
+/// Identifier of a clade: its minimum leaf and its number of leaves
+pub(crate) type CladeKey = (usize, usize);
+
+/// Get the clade keys of the two children of each cherry of an ancestry
+///
+/// Unlike node labels, clade keys do not depend on how the internal nodes are
+/// numbered, so they can be used to match the nodes of two ancestries of the
+/// same tree (e.g., to carry branch lengths over). `leaf_key` gives the key
+/// of each leaf, usually `(leaf, 1)`. A leaf keyed `(usize::MAX, 0)` is
+/// ignored, so that its parent gets the same key as its sister.
+///
+/// If `has_parents` is false, the third column is ignored and each cherry is
+/// referred to by its smallest child, as in `get_cherries_no_parents_with_bls`.
+pub(crate) fn _get_clade_keys(
+    ancestry: &Ancestry,
+    has_parents: bool,
+    leaf_key: impl Fn(usize) -> CladeKey,
+) -> Vec<[CladeKey; 2]> {
+    // Keys of the internal nodes seen so far, by label
+    let mut internal_keys: HashMap<usize, CladeKey> = HashMap::with_capacity(ancestry.len());
+
+    ancestry
+        .iter()
+        .map(|&[c1, c2, p]| {
+            let [key1, key2] = [c1, c2].map(|child| {
+                internal_keys
+                    .get(&child)
+                    .copied()
+                    .unwrap_or_else(|| leaf_key(child))
+            });
+            let parent = if has_parents { p } else { c1.min(c2) };
+            internal_keys.insert(parent, (key1.0.min(key2.0), key1.1 + key2.1));
+            [key1, key2]
+        })
+        .collect()
+}
+
 pub fn find_coords_of_first_leaf(ancestry: &Ancestry, leaf: usize) -> (usize, usize) {
     for r in 0..ancestry.len() {
         for c in 0..3 {
@@ -308,29 +353,65 @@ impl Fenwick {
     }
 }
 
-pub fn build_vector(cherries: &Ancestry) -> Vec<usize> {
+pub fn build_vector<T: LeafIndex>(cherries: &Ancestry) -> Vec<T> {
     let num_cherries = cherries.len();
     let num_leaves = num_cherries + 1;
 
-    let mut v = vec![0; num_cherries];
+    let mut v = vec![T::from_usize(0); num_cherries];
     let mut bit = Fenwick::new(num_leaves);
 
     for [c1, c2, c_max] in cherries.iter().copied() {
         let idx = bit.prefix_sum(c_max - 1);
 
-        v[c_max - 1] = if idx == 0 {
+        v[c_max - 1] = T::from_usize(if idx == 0 {
             std::cmp::min(c1, c2)
         } else {
             c_max - 1 + idx
-        };
+        });
         bit.update(c_max, 1);
     }
     return v;
 }
 
+/// Order the cherries of an ancestry and build the corresponding vector
+///
+/// If `has_parents` is true, `ancestry` must contain parent labels (see
+/// `get_cherries`), with parents labelled above their children. It is first
+/// sorted by parent with `order_cherries`, which also converts it to the
+/// format of `get_cherries_no_parents`. In all cases, the cherries are then
+/// ordered with `order_cherries_no_parents` before building the vector.
+///
+/// The second pass is required whenever the parent labels are not the
+/// canonical Phylo2Vec labels, e.g. after relabelling the leaves of an
+/// ancestry in `add_leaf`, where ordering by parent alone yields wrong vectors.
+///
+/// The vector elements have type `T` (e.g., `usize` or `u32`).
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::{get_cherries, get_cherries_no_parents};
+/// use phylo2vec::tree_vec::ops::vector::to_vector_from_cherries;
+///
+/// let v: Vec<usize> = to_vector_from_cherries(get_cherries("((0,2)5,(1,3)4)6;"), true);
+/// assert_eq!(v, vec![0, 0, 1]);
+///
+/// let v: Vec<u32> = to_vector_from_cherries(get_cherries_no_parents("((0,2),(1,3));"), false);
+/// assert_eq!(v, vec![0, 0, 1]);
+/// ```
+pub fn to_vector_from_cherries<T: LeafIndex>(mut ancestry: Ancestry, has_parents: bool) -> Vec<T> {
+    if has_parents {
+        order_cherries(&mut ancestry);
+    }
+    order_cherries_no_parents(&mut ancestry);
+    build_vector(&ancestry)
+}
+
 /// Get the cophenetic distances from the Phylo2Vec vector
 /// Output is a pairwise distance matrix of dimensions n x n
 ///
+/// With the `parallel` feature enabled, the rows of the matrix
+/// are computed in parallel using rayon.
+///
 /// # Example
 /// ```
 /// use phylo2vec::tree_vec::ops::vector::cophenetic_distances;
@@ -339,6 +420,61 @@ pub fn build_vector(cherries: &Ancestry) -> Vec<usize> {
 /// let dist = cophenetic_distances(&v, false);
 /// ```
 pub fn cophenetic_distances(v: &Vec<usize>, unrooted: bool) -> Vec<Vec<usize>> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        let ancestry = get_ancestry(v);
+        (0..=v.len())
+            .into_par_iter()
+            .map(|leaf| _cophenetic_row(&ancestry, leaf, unrooted))
+            .collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        _cophenetic_distances_serial(v, unrooted)
+    }
+}
+
+// Helper function that computes the distances from one leaf to every other leaf.
+// Each row only reads the ancestry, so rows can be computed independently.
+#[cfg(any(feature = "parallel", test))]
+fn _cophenetic_row(ancestry: &Ancestry, leaf: usize, unrooted: bool) -> Vec<usize> {
+    let n_leaves = ancestry.len() + 1;
+    let root = 2 * n_leaves - 2;
+    let parents = _get_parent_vector(ancestry);
+
+    // Distances from the leaf to its ancestors
+    let mut dist = vec![usize::MAX; 2 * n_leaves - 1];
+    let mut node = leaf;
+    dist[node] = 0;
+    while node != root {
+        dist[parents[node]] = dist[node] + 1;
+        node = parents[node];
+    }
+
+    // Distances to the other nodes, from the root down
+    // (parents always appear in later rows than their children)
+    for &[c1, c2, p] in ancestry.iter().rev() {
+        for c in [c1, c2] {
+            if dist[c] == usize::MAX {
+                // In an unrooted tree, the two root edges form a single edge
+                dist[c] = if unrooted && p == root {
+                    dist[p]
+                } else {
+                    dist[p] + 1
+                };
+            }
+        }
+    }
+
+    dist.truncate(n_leaves);
+    dist
+}
+
+// Serial implementation of cophenetic_distances, filling the matrix from the root down
+#[cfg(any(not(feature = "parallel"), test))]
+fn _cophenetic_distances_serial(v: &[usize], unrooted: bool) -> Vec<Vec<usize>> {
     let mut ancestry = get_ancestry(v);
 
     if unrooted {
@@ -395,3 +531,1608 @@ pub fn cophenetic_distances(v: &Vec<usize>, unrooted: bool) -> Vec<Vec<usize>> {
     }
     result
 }
+
+/// Get the cophenetic distances of a batch of Phylo2Vec vectors
+/// Output is a stacked tensor of dimensions batch x n x n
+///
+/// All trees must have the same number of leaves. With the `parallel`
+/// feature enabled, the trees are processed in parallel using rayon.
+///
+/// # Errors
+///
+/// Returns `Phylo2VecError::LeafCountMismatch` with the index of the first
+/// tree whose number of leaves differs from that of the first tree
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::cophenetic_distances_batch;
+///
+/// let trees = vec![vec![0, 0, 1], vec![0, 1, 2]];
+/// let dists = cophenetic_distances_batch(&trees, false).unwrap();
+/// assert_eq!(dists.len(), 2);
+/// assert_eq!(dists[0].len(), 4);
+/// ```
+pub fn cophenetic_distances_batch(
+    trees: &[Vec<usize>],
+    unrooted: bool,
+) -> Result<Vec<Vec<Vec<usize>>>, Phylo2VecError> {
+    if let Some(first) = trees.first() {
+        let expected = first.len() + 1;
+        if let Some((index, v)) = trees
+            .iter()
+            .enumerate()
+            .find(|(_, v)| v.len() + 1 != expected)
+        {
+            return Err(Phylo2VecError::LeafCountMismatch {
+                index,
+                expected,
+                found: v.len() + 1,
+            });
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        Ok(trees
+            .par_iter()
+            .map(|v| cophenetic_distances(v, unrooted))
+            .collect())
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        Ok(trees
+            .iter()
+            .map(|v| cophenetic_distances(v, unrooted))
+            .collect())
+    }
+}
+
+/// Get the cophenetic distances from the Phylo2Vec vector and its branch lengths
+/// Output is a pairwise distance matrix of dimensions n x n, where each entry
+/// is the sum of the branch lengths on the path between two leaves
+///
+/// `bls[k]` holds the branch lengths of the two children of ancestry row `k`,
+/// as in `TreeVec`. Unlike `cophenetic_distances`, there is no `unrooted`
+/// option: unrooting merges the two edges below the root into a single edge
+/// of their summed length, which does not change any path length.
+///
+/// # Panics
+///
+/// Panics if `bls` does not have one row per entry of `v`
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::cophenetic_distances_with_bls;
+///
+/// // ((0:1.0,2:2.0)3:3.0,1:4.0)4;
+/// let v = vec![0, 0];
+/// let bls = vec![(1.0, 2.0), (3.0, 4.0)];
+/// let dist = cophenetic_distances_with_bls(&v, &bls);
+/// assert_eq!(dist[0][2], 3.0);
+/// assert_eq!(dist[0][1], 8.0);
+/// assert_eq!(dist[2][1], 9.0);
+/// ```
+pub fn cophenetic_distances_with_bls(v: &[usize], bls: &[(f64, f64)]) -> Vec<Vec<f64>> {
+    assert_eq!(
+        bls.len(),
+        v.len(),
+        "Expected one pair of branch lengths per entry of v"
+    );
+
+    let ancestry = get_ancestry(v);
+    let n_leaves = v.len() + 1;
+    let size = 2 * n_leaves - 1;
+    let mut dist: Vec<Vec<f64>> = vec![vec![0.0; size]; size];
+    let mut all_visited: Vec<usize> = vec![size - 1];
+
+    // Traverse the tree from the root: the distances of a parent to all
+    // visited nodes are known before its children are reached
+    for (&[c1, c2, p], &(b1, b2)) in ancestry.iter().zip(bls.iter()).rev() {
+        for &visited in all_visited.iter().filter(|&&visited| visited != p) {
+            let dist_from_parent = dist[p][visited];
+            dist[c1][visited] = dist_from_parent + b1;
+            dist[visited][c1] = dist_from_parent + b1;
+            dist[c2][visited] = dist_from_parent + b2;
+            dist[visited][c2] = dist_from_parent + b2;
+        }
+        dist[c1][c2] = b1 + b2;
+        dist[c2][c1] = b1 + b2;
+        dist[c1][p] = b1;
+        dist[p][c1] = b1;
+        dist[c2][p] = b2;
+        dist[p][c2] = b2;
+
+        all_visited.push(c1);
+        all_visited.push(c2);
+    }
+
+    // Extract the top-left n_leaves x n_leaves submatrix
+    dist.truncate(n_leaves);
+    for row in dist.iter_mut() {
+        row.truncate(n_leaves);
+    }
+    dist
+}
+
+/// Get the descendant leaves of each internal node as a bitset
+///
+/// Entry i corresponds to internal node n + i (i.e., row i of the ancestry).
+/// Each bitset is stored as blocks of 64 bits: leaf j is a descendant
+/// if bit j % 64 of block j / 64 is set. Set operations on bipartitions
+/// therefore cost O(n / 64).
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::descendant_bitsets;
+///
+/// // Ancestry of [0, 0, 1]: [[1, 3, 4], [0, 2, 5], [5, 4, 6]]
+/// let bitsets = descendant_bitsets(&vec![0, 0, 1]);
+/// assert_eq!(bitsets, vec![vec![0b1010], vec![0b0101], vec![0b1111]]);
+/// ```
+pub fn descendant_bitsets(v: &[usize]) -> Vec<Vec<u64>> {
+    let n_leaves = v.len() + 1;
+    let n_blocks = n_leaves.div_ceil(64);
+    let ancestry = get_ancestry(v);
+
+    let mut bitsets: Vec<Vec<u64>> = Vec::with_capacity(ancestry.len());
+
+    // Children always appear in earlier rows than their parents
+    for &[c1, c2, _] in ancestry.iter() {
+        let mut bitset = vec![0u64; n_blocks];
+        for child in [c1, c2] {
+            if child < n_leaves {
+                bitset[child / 64] |= 1 << (child % 64);
+            } else {
+                for (block, child_block) in bitset.iter_mut().zip(&bitsets[child - n_leaves]) {
+                    *block |= child_block;
+                }
+            }
+        }
+        bitsets.push(bitset);
+    }
+
+    bitsets
+}
+
+/// Swap the two children of an internal node in an ancestry matrix
+///
+/// A Phylo2Vec vector does not encode the left/right order of the children
+/// of a node, so a rotation leaves the vector itself unchanged. The rotation
+/// is applied to the ancestry instead, which controls the order in which
+/// `build_newick` writes the subtrees.
+///
+/// # Panics
+///
+/// Panics if `internal_node` is not an internal node of the tree
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::build_newick;
+/// use phylo2vec::tree_vec::ops::vector::{get_ancestry, rotate_node};
+///
+/// let ancestry = get_ancestry(&vec![0, 0, 1]);
+/// let rotated = rotate_node(&ancestry, 6);
+/// assert_eq!(build_newick(&rotated), "((1,3)4,(0,2)5)6;");
+/// ```
+pub fn rotate_node(ancestry: &Ancestry, internal_node: usize) -> Ancestry {
+    let n_leaves = ancestry.len() + 1;
+    assert!(
+        internal_node >= n_leaves && internal_node < 2 * n_leaves - 1,
+        "Node {} is not an internal node (expected {} <= node <= {})",
+        internal_node,
+        n_leaves,
+        2 * n_leaves - 2
+    );
+
+    let mut rotated = ancestry.clone();
+    rotated[internal_node - n_leaves].swap(0, 1);
+    rotated
+}
+
+/// Ladderize an ancestry matrix, ordering the children of every node by subtree size
+///
+/// With `ascending`, the child with fewer leaves comes first, otherwise the
+/// child with more leaves comes first. Children with the same number of leaves
+/// are ordered by their smallest leaf, so that all orderings of the same tree
+/// give the same ancestry. As with `rotate_node`, the vector itself does not
+/// encode the order of the children, so it is unchanged: only the order in
+/// which `build_newick` writes the subtrees is affected.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::newick::build_newick;
+/// use phylo2vec::tree_vec::ops::vector::{get_ancestry, ladderize};
+///
+/// let ancestry = get_ancestry(&vec![0, 0, 0, 1, 3]);
+/// assert_eq!(build_newick(&ancestry), "(((0,(3,5)6)8,2)9,(1,4)7)10;");
+/// assert_eq!(
+///     build_newick(&ladderize(&ancestry, true)),
+///     "((1,4)7,(2,(0,(3,5)6)8)9)10;"
+/// );
+/// ```
+pub fn ladderize(ancestry: &Ancestry, ascending: bool) -> Ancestry {
+    let n_leaves = ancestry.len() + 1;
+
+    // Number of leaves and smallest leaf below each node
+    let mut sizes = vec![1; 2 * n_leaves - 1];
+    let mut min_leaves: Vec<usize> = (0..2 * n_leaves - 1).collect();
+
+    let mut ladderized = ancestry.clone();
+    // Children always appear in earlier rows than their parents
+    for row in ladderized.iter_mut() {
+        let [c1, c2, p] = *row;
+        sizes[p] = sizes[c1] + sizes[c2];
+        min_leaves[p] = min_leaves[c1].min(min_leaves[c2]);
+
+        let by_size = if ascending {
+            sizes[c1].cmp(&sizes[c2])
+        } else {
+            sizes[c2].cmp(&sizes[c1])
+        };
+        if by_size.then(min_leaves[c1].cmp(&min_leaves[c2])).is_gt() {
+            row.swap(0, 1);
+        }
+    }
+
+    ladderized
+}
+
+/// Get the depth of each leaf, i.e. its number of edges from the root
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::leaf_depths;
+///
+/// // (0,(1,(2,3)4)5)6;
+/// assert_eq!(leaf_depths(&vec![0, 1, 2]), vec![1, 2, 3, 3]);
+/// ```
+pub fn leaf_depths(v: &[usize]) -> Vec<usize> {
+    let n_leaves = v.len() + 1;
+    let ancestry = get_ancestry(v);
+
+    let mut depths = vec![0; 2 * n_leaves - 1];
+    // Parents always appear in later rows than their children
+    for &[c1, c2, p] in ancestry.iter().rev() {
+        depths[c1] = depths[p] + 1;
+        depths[c2] = depths[p] + 1;
+    }
+
+    depths.truncate(n_leaves);
+    depths
+}
+
+/// Get the depth of the most recent common ancestor (MRCA) of every pair of leaves
+/// Output is a matrix of dimensions n x n
+///
+/// Depths are counted in edges from the root. The diagonal holds the depth
+/// of each leaf (see `leaf_depths`). This is the shared-ancestry matrix used
+/// e.g. in phylogenetic generalized least squares (PGLS), and it relates to
+/// the cophenetic distances by
+/// `coph[i][j] = depth[i] + depth[j] - 2 * mrca_depth[i][j]`.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::mrca_depth_matrix;
+///
+/// // (0,(1,(2,3)4)5)6;
+/// let mrca_depths = mrca_depth_matrix(&vec![0, 1, 2]);
+/// assert_eq!(mrca_depths[0], vec![1, 0, 0, 0]);
+/// assert_eq!(mrca_depths[2], vec![0, 1, 3, 2]);
+/// ```
+pub fn mrca_depth_matrix(v: &[usize]) -> Vec<Vec<usize>> {
+    let n_leaves = v.len() + 1;
+    let ancestry = get_ancestry(v);
+
+    let mut depths = vec![0; 2 * n_leaves - 1];
+    // Parents always appear in later rows than their children
+    for &[c1, c2, p] in ancestry.iter().rev() {
+        depths[c1] = depths[p] + 1;
+        depths[c2] = depths[p] + 1;
+    }
+
+    let mut mrca_depths = vec![vec![0; n_leaves]; n_leaves];
+    for leaf in 0..n_leaves {
+        mrca_depths[leaf][leaf] = depths[leaf];
+    }
+
+    let mut descendants: Vec<Vec<usize>> = (0..n_leaves).map(|leaf| vec![leaf]).collect();
+    descendants.resize(2 * n_leaves - 1, Vec::new());
+
+    // Children always appear in earlier rows than their parents
+    for &[c1, c2, p] in ancestry.iter() {
+        // p is the MRCA of every leaf below c1 and every leaf below c2
+        let leaves1 = std::mem::take(&mut descendants[c1]);
+        let leaves2 = std::mem::take(&mut descendants[c2]);
+        for &i in leaves1.iter() {
+            for &j in leaves2.iter() {
+                mrca_depths[i][j] = depths[p];
+                mrca_depths[j][i] = depths[p];
+            }
+        }
+        descendants[p] = leaves1;
+        descendants[p].extend(leaves2);
+    }
+
+    mrca_depths
+}
+
+/// Get the node ids of a tree in postorder (children before their parent)
+///
+/// Nodes follow the Phylo2Vec numbering: leaves are 0..n-1, internal nodes
+/// are n..2n-2 and the root, which always comes last, is 2n-2. Children are
+/// visited in ancestry order, so the sequence matches the order in which
+/// nodes appear in `to_newick_from_vector`.
+///
+/// This is a thin wrapper around `visit_postorder`.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::postorder_sequence;
+///
+/// // ((0,2)5,(1,3)4)6;
+/// assert_eq!(postorder_sequence(&vec![0, 0, 1]), vec![0, 2, 5, 1, 3, 4, 6]);
+/// ```
+pub fn postorder_sequence(v: &[usize]) -> Vec<usize> {
+    let mut sequence = Vec::with_capacity(2 * v.len() + 1);
+    visit_postorder(v, |visit| sequence.push(visit.id));
+    sequence
+}
+
+/// A node visited by `visit_postorder`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeVisit {
+    /// Node id, following the Phylo2Vec numbering
+    pub id: usize,
+    /// Children of the node, in ancestry order (None for a leaf)
+    pub children: Option<(usize, usize)>,
+    /// Whether the node is a leaf
+    pub is_leaf: bool,
+}
+
+/// Call a closure once per node of a tree, in postorder
+///
+/// Children are visited before their parent, in the same order as
+/// `postorder_sequence`, so reductions over the tree (e.g., clade sizes)
+/// can be computed in a single pass without building intermediate structures.
+///
+/// The tree is traversed with an explicit stack, so deep trees do not
+/// overflow the call stack.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::visit_postorder;
+///
+/// // ((0,2)5,(1,3)4)6; --> number of leaves below each node
+/// let mut sizes = vec![0; 7];
+/// visit_postorder(&vec![0, 0, 1], |visit| {
+///     sizes[visit.id] = match visit.children {
+///         Some((c1, c2)) => sizes[c1] + sizes[c2],
+///         None => 1,
+///     };
+/// });
+/// assert_eq!(sizes, vec![1, 1, 1, 1, 2, 2, 4]);
+/// ```
+pub fn visit_postorder<F: FnMut(NodeVisit)>(v: &[usize], mut f: F) {
+    let n_leaves = v.len() + 1;
+    let ancestry = get_ancestry(v);
+
+    // (node, whether its children have already been visited)
+    let mut stack: Vec<(usize, bool)> = vec![(2 * n_leaves - 2, false)];
+    while let Some((node, expanded)) = stack.pop() {
+        if node < n_leaves {
+            f(NodeVisit {
+                id: node,
+                children: None,
+                is_leaf: true,
+            });
+            continue;
+        }
+
+        let [c1, c2, _] = ancestry[node - n_leaves];
+        if expanded {
+            f(NodeVisit {
+                id: node,
+                children: Some((c1, c2)),
+                is_leaf: false,
+            });
+        } else {
+            stack.push((node, true));
+            stack.push((c2, false));
+            stack.push((c1, false));
+        }
+    }
+}
+
+/// Count the cherries of a tree, i.e. the internal nodes whose two children are leaves
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::count_cherries;
+///
+/// // ((0,2)5,(1,3)4)6;
+/// assert_eq!(count_cherries(&vec![0, 0, 1]), 2);
+/// // (0,(1,(2,3)4)5)6;
+/// assert_eq!(count_cherries(&vec![0, 1, 2]), 1);
+/// ```
+pub fn count_cherries(v: &[usize]) -> usize {
+    let n_leaves = v.len() + 1;
+    get_ancestry(v)
+        .iter()
+        .filter(|&&[c1, c2, _]| c1 < n_leaves && c2 < n_leaves)
+        .count()
+}
+
+// Helper function that maps every node of an ancestry to its parent.
+// The root, which has no parent, is mapped to usize::MAX.
+fn _get_parent_vector(ancestry: &Ancestry) -> Vec<usize> {
+    let mut parents = vec![usize::MAX; 2 * ancestry.len() + 1];
+    for &[c1, c2, p] in ancestry.iter() {
+        parents[c1] = p;
+        parents[c2] = p;
+    }
+    parents
+}
+
+/// Check whether node `a` is an ancestor of node `b`
+///
+/// Nodes follow the Phylo2Vec numbering: leaves are 0..n-1,
+/// internal nodes are n..2n-2 and the root is 2n-2.
+/// A node is not considered to be its own ancestor.
+///
+/// Walks up from `b` to the root, so the complexity is O(depth of `b`)
+/// once the ancestry is built.
+///
+/// # Panics
+///
+/// Panics if `a` or `b` is not a node of the tree
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::is_ancestor;
+///
+/// let v = vec![0, 0, 1];
+/// // ((0,2)5,(1,3)4)6;
+/// assert!(is_ancestor(&v, 5, 2));
+/// assert!(!is_ancestor(&v, 4, 2));
+/// ```
+pub fn is_ancestor(v: &[usize], a: usize, b: usize) -> bool {
+    let root = 2 * v.len();
+    assert!(
+        a <= root && b <= root,
+        "Nodes ({}, {}) are out of bounds (max = {})",
+        a,
+        b,
+        root
+    );
+
+    let parents = _get_parent_vector(&get_ancestry(v));
+
+    let mut node = b;
+    while node != root {
+        node = parents[node];
+        if node == a {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Get the parent of a node, or None if the node is the root
+///
+/// Nodes follow the Phylo2Vec numbering: leaves are 0..n-1,
+/// internal nodes are n..2n-2 and the root is 2n-2.
+///
+/// # Panics
+///
+/// Panics if `node` is not a node of the tree
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::parent_of;
+///
+/// let v = vec![0, 0, 1];
+/// // ((0,2)5,(1,3)4)6;
+/// assert_eq!(parent_of(&v, 2), Some(5));
+/// assert_eq!(parent_of(&v, 4), Some(6));
+/// assert_eq!(parent_of(&v, 6), None);
+/// ```
+pub fn parent_of(v: &[usize], node: usize) -> Option<usize> {
+    let root = 2 * v.len();
+    assert!(
+        node <= root,
+        "Node {} is out of bounds (max = {})",
+        node,
+        root
+    );
+
+    get_ancestry(v)
+        .iter()
+        .find(|&&[c1, c2, _]| c1 == node || c2 == node)
+        .map(|&[_, _, p]| p)
+}
+
+/// Get the most recent common ancestor (MRCA) of two leaves
+///
+/// Nodes follow the Phylo2Vec numbering: leaves are 0..n-1,
+/// internal nodes are n..2n-2 and the root is 2n-2.
+/// The MRCA of a leaf with itself is the leaf.
+///
+/// Walks up the ancestry from both leaves, so the complexity is
+/// O(n) per query. For many queries on the same tree, build an
+/// `LcaIndex` once instead.
+///
+/// # Errors
+///
+/// Returns `Phylo2VecError::InvalidNode` if `leaf_a` or `leaf_b` is not a leaf
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::mrca;
+///
+/// let v = vec![0, 0, 1];
+/// // ((0,2)5,(1,3)4)6;
+/// assert_eq!(mrca(&v, 0, 2), Ok(5));
+/// assert_eq!(mrca(&v, 0, 1), Ok(6));
+/// assert_eq!(mrca(&v, 3, 3), Ok(3));
+/// assert!(mrca(&v, 0, 4).is_err());
+/// ```
+pub fn mrca(v: &[usize], leaf_a: usize, leaf_b: usize) -> Result<usize, Phylo2VecError> {
+    Ok(_walk_to_mrca(v, leaf_a, leaf_b)?.0)
+}
+
+/// Get the number of edges on the path between two leaves
+///
+/// This is one entry of `cophenetic_distances`, found by walking up
+/// to the most recent common ancestor (MRCA) of both leaves instead
+/// of building the full n x n matrix.
+///
+/// If `unrooted` is true, the two edges below the root count as a
+/// single edge, as in the unrooted tree.
+///
+/// # Errors
+///
+/// Returns `Phylo2VecError::InvalidNode` if `leaf_a` or `leaf_b` is not a leaf
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::path_length;
+///
+/// let v = vec![0, 0, 1];
+/// // ((0,2)5,(1,3)4)6;
+/// assert_eq!(path_length(&v, 0, 2, false), Ok(2));
+/// assert_eq!(path_length(&v, 0, 1, false), Ok(4));
+/// assert_eq!(path_length(&v, 0, 1, true), Ok(3));
+/// ```
+pub fn path_length(
+    v: &[usize],
+    leaf_a: usize,
+    leaf_b: usize,
+    unrooted: bool,
+) -> Result<usize, Phylo2VecError> {
+    let (mrca, n_edges) = _walk_to_mrca(v, leaf_a, leaf_b)?;
+
+    // Merge the two root edges if the path goes through the root
+    // (in a tree with one leaf, the root is the leaf itself and there is no edge)
+    if unrooted && mrca == 2 * v.len() && n_edges > 0 {
+        Ok(n_edges - 1)
+    } else {
+        Ok(n_edges)
+    }
+}
+
+// Helper function that walks up the ancestry from two leaves.
+// Returns their MRCA and the number of edges on the path between them.
+fn _walk_to_mrca(
+    v: &[usize],
+    leaf_a: usize,
+    leaf_b: usize,
+) -> Result<(usize, usize), Phylo2VecError> {
+    let n_leaves = v.len() + 1;
+    if let Some(&node) = [leaf_a, leaf_b].iter().find(|&&leaf| leaf >= n_leaves) {
+        return Err(Phylo2VecError::InvalidNode {
+            node,
+            max: n_leaves - 1,
+        });
+    }
+
+    let root = 2 * v.len();
+    let parents = _get_parent_vector(&get_ancestry(v));
+
+    // Number of edges from leaf_a to each of its ancestors (and itself)
+    let mut steps_from_a = vec![None; parents.len()];
+    let mut node = leaf_a;
+    let mut n_steps = 0;
+    steps_from_a[node] = Some(n_steps);
+    while node != root {
+        node = parents[node];
+        n_steps += 1;
+        steps_from_a[node] = Some(n_steps);
+    }
+
+    // The first ancestor of leaf_a on the way up from leaf_b is the MRCA
+    let mut node = leaf_b;
+    let mut n_steps = 0;
+    loop {
+        if let Some(steps_a) = steps_from_a[node] {
+            return Ok((node, steps_a + n_steps));
+        }
+        node = parents[node];
+        n_steps += 1;
+    }
+}
+
+/// Check whether a set of leaves forms a monophyletic clade
+///
+/// Returns true if and only if `leaves` is exactly the set of descendant
+/// leaves of an internal node, i.e. if no other leaf descends from the
+/// most recent common ancestor (MRCA) of `leaves`. A single leaf is not
+/// the descendant set of an internal node, so it is not considered a clade.
+/// Duplicate leaves are ignored.
+///
+/// # Panics
+///
+/// Panics if a leaf is out of bounds
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::is_monophyletic;
+///
+/// // ((0,2)5,(1,3)4)6;
+/// assert!(is_monophyletic(&vec![0, 0, 1], &[0, 2]));
+/// assert!(!is_monophyletic(&vec![0, 0, 1], &[0, 1]));
+/// ```
+pub fn is_monophyletic(v: &[usize], leaves: &[usize]) -> bool {
+    let n_leaves = v.len() + 1;
+    for &leaf in leaves {
+        assert!(
+            leaf < n_leaves,
+            "Leaf {} is out of bounds (max = {})",
+            leaf,
+            n_leaves - 1
+        );
+    }
+
+    let mut selected = leaves.to_vec();
+    selected.sort_unstable();
+    selected.dedup();
+    if selected.len() < 2 {
+        return false;
+    }
+
+    let index = LcaIndex::new(v);
+    let mrca = selected[1..]
+        .iter()
+        .fold(selected[0], |mrca, &leaf| index.lca(mrca, leaf));
+
+    // The set is a clade if the MRCA has no other descendant leaf
+    _get_leaves_below(&get_ancestry(v), mrca).len() == selected.len()
+}
+
+// Helper function that collects the leaves below a node of an ancestry
+fn _get_leaves_below(ancestry: &Ancestry, node: usize) -> Vec<usize> {
+    let n_leaves = ancestry.len() + 1;
+    let mut leaves = Vec::new();
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if node < n_leaves {
+            leaves.push(node);
+        } else {
+            let [c1, c2, _] = ancestry[node - n_leaves];
+            stack.push(c1);
+            stack.push(c2);
+        }
+    }
+    leaves
+}
+
+// Helper function that locates the nodes involved in an NNI move.
+// Returns (x, y_col, p, c_col): the move swaps the child of `x` in column
+// `y_col` with the sibling of `x`, found in column `c_col` of the row of `p`.
+fn _get_nni_nodes(ancestry: &Ancestry, edge_index: usize) -> (usize, usize, usize, usize) {
+    let n_leaves = ancestry.len() + 1;
+    let n_moves = 2 * n_leaves.saturating_sub(2);
+    assert!(
+        edge_index < n_moves,
+        "NNI index {} is out of bounds (expected < {})",
+        edge_index,
+        n_moves
+    );
+
+    let x = n_leaves + edge_index / 2;
+    let y_col = edge_index % 2;
+    let p = _get_parent_vector(ancestry)[x];
+    let c_col = if ancestry[p - n_leaves][0] == x { 1 } else { 0 };
+
+    (x, y_col, p, c_col)
+}
+
+/// Apply a nearest-neighbour interchange (NNI) to a Phylo2Vec vector
+///
+/// Each internal edge, i.e. the edge above a non-root internal node `x`,
+/// admits two moves: the sibling subtree of `x` is swapped with either child
+/// of `x`. There are therefore 2(n-2) rooted moves for a tree with n leaves,
+/// indexed by `edge_index`: the move swaps the sibling of internal node
+/// n + edge_index / 2 with its child in column edge_index % 2 of the ancestry.
+///
+/// # Panics
+///
+/// Panics if `edge_index` >= 2(n-2)
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_newick_from_vector;
+/// use phylo2vec::tree_vec::ops::vector::nni;
+///
+/// // (0,(1,2)3)4; --> ((0,2)3,1)4;
+/// let v = nni(&vec![0, 1], 0);
+/// assert_eq!(to_newick_from_vector(&v), "((0,2)3,1)4;");
+/// ```
+pub fn nni(v: &[usize], edge_index: usize) -> Vec<usize> {
+    let mut ancestry = get_ancestry(v);
+    let n_leaves = v.len() + 1;
+    let (x, y_col, p, c_col) = _get_nni_nodes(&ancestry, edge_index);
+
+    let y = ancestry[x - n_leaves][y_col];
+    let c = ancestry[p - n_leaves][c_col];
+    ancestry[x - n_leaves][y_col] = c;
+    ancestry[p - n_leaves][c_col] = y;
+
+    // Internal node labels are not canonical anymore, so go through a Newick without them
+    to_vector(&remove_parent_labels(&build_newick(&ancestry)))
+}
+
+/// Get a canonical vector of the unrooted topology of a tree
+///
+/// The root is removed and the tree is rerooted on the edge above leaf 0,
+/// so that all rootings of the same unrooted tree yield the same vector:
+/// two trees have the same unrooted topology if and only if their canonical
+/// vectors are equal. Trees with less than 3 leaves are returned as is.
+/// Like `visit_postorder`, this does not recurse, so it supports deep trees.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_vector;
+/// use phylo2vec::tree_vec::ops::vector::unrooted_canonical;
+///
+/// let v1 = to_vector("(((0,1),(2,3)),4);");
+/// let v2 = to_vector("((0,1),((2,3),4));");
+/// assert_ne!(v1, v2);
+/// assert_eq!(unrooted_canonical(&v1), unrooted_canonical(&v2));
+/// ```
+pub fn unrooted_canonical(v: &[usize]) -> Vec<usize> {
+    let n_leaves = v.len() + 1;
+    if n_leaves < 3 {
+        return v.to_vec();
+    }
+
+    // Adjacency of the unrooted tree: the two children of the root are joined
+    let root = 2 * n_leaves - 2;
+    let mut neighbors: Vec<Vec<usize>> = vec![Vec::with_capacity(3); root];
+    for [c1, c2, p] in get_ancestry(v) {
+        if p == root {
+            neighbors[c1].push(c2);
+            neighbors[c2].push(c1);
+        } else {
+            for c in [c1, c2] {
+                neighbors[c].push(p);
+                neighbors[p].push(c);
+            }
+        }
+    }
+
+    // Root on the edge above leaf 0: the other side of that edge is the
+    // second child of the new root. Internal nodes are relabelled n + i
+    // in postorder, so that node n + i is described by row i.
+    let start = neighbors[0][0];
+    let mut new_ids: Vec<usize> = (0..root).collect();
+    let mut ancestry: Ancestry = Vec::with_capacity(n_leaves - 1);
+
+    // (node, node it was reached from, whether its children have already been visited)
+    let mut stack: Vec<(usize, usize, bool)> = vec![(start, 0, false)];
+    while let Some((node, from, expanded)) = stack.pop() {
+        if node < n_leaves {
+            continue;
+        }
+
+        let mut children = neighbors[node].iter().copied().filter(|&c| c != from);
+        let (c1, c2) = (children.next().unwrap(), children.next().unwrap());
+        if expanded {
+            new_ids[node] = n_leaves + ancestry.len();
+            ancestry.push([new_ids[c1], new_ids[c2], new_ids[node]]);
+        } else {
+            stack.push((node, from, true));
+            stack.push((c2, node, false));
+            stack.push((c1, node, false));
+        }
+    }
+    ancestry.push([0, new_ids[start], root]);
+
+    _ancestry_to_vector(&ancestry)
+}
+
+/// Get all the trees reachable from a Phylo2Vec vector by one NNI move
+///
+/// Neighbours are enumerated on the unrooted tree: each of its n-3 internal
+/// edges admits two moves, giving 2(n-3) distinct neighbours for n >= 3 leaves.
+/// This differs from the 2(n-2) rooted moves of `nni`, as the moves on the two
+/// edges below the root only displace the root and leave the unrooted topology
+/// unchanged. The root stays on the same edge in every neighbour.
+///
+/// The output vectors are sorted and contain no duplicates.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::nni_neighbors;
+///
+/// // ((0,2),(1,3)) has a single internal edge
+/// let neighbors = nni_neighbors(&vec![0, 0, 1]);
+/// assert_eq!(neighbors.len(), 2);
+/// ```
+pub fn nni_neighbors(v: &[usize]) -> Vec<Vec<usize>> {
+    let n_leaves = v.len() + 1;
+    if n_leaves < 4 {
+        return Vec::new();
+    }
+
+    let ancestry = get_ancestry(v);
+    let root = 2 * n_leaves - 2;
+    let [r1, r2, _] = ancestry[root - n_leaves];
+
+    let mut neighbors: Vec<Vec<usize>> = Vec::with_capacity(2 * (n_leaves - 3));
+
+    // Internal edges not adjacent to the root: use the rooted moves
+    for x in n_leaves..root {
+        if x == r1 || x == r2 {
+            continue;
+        }
+        for y_col in 0..2 {
+            neighbors.push(nni(v, 2 * (x - n_leaves) + y_col));
+        }
+    }
+
+    // The two edges below the root form a single unrooted edge if both
+    // children of the root are internal: swap a child of r1 with either child of r2
+    if r1 >= n_leaves && r2 >= n_leaves {
+        for c_col in 0..2 {
+            let mut new_ancestry = ancestry.clone();
+            let y = new_ancestry[r1 - n_leaves][0];
+            let c = new_ancestry[r2 - n_leaves][c_col];
+            new_ancestry[r1 - n_leaves][0] = c;
+            new_ancestry[r2 - n_leaves][c_col] = y;
+            neighbors.push(_ancestry_to_vector(&new_ancestry));
+        }
+    }
+
+    neighbors.sort_unstable();
+    neighbors.dedup();
+    neighbors
+}
+
+/// Update the cophenetic distances of a tree after an NNI
+///
+/// `prev` must be the (rooted) cophenetic distance matrix of `v`, as returned
+/// by `cophenetic_distances(v, false)`. The output is the distance matrix of
+/// `nni(v, edge_index)`, obtained by updating only the pairs of leaves whose
+/// path crosses the moved edges.
+///
+/// With `x` the lower node of the edge, `y` the child of `x` that is moved,
+/// `b` the other child of `x` and `c` the sibling of `x`:
+///  * y <-> b gets one edge longer and c <-> b one edge shorter
+///  * y <-> (leaves outside the parent of `x`) gets one edge shorter
+///  * c <-> (leaves outside the parent of `x`) gets one edge longer
+///
+/// # Panics
+///
+/// Panics if `edge_index` >= 2(n-2)
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::vector::{cophenetic_after_nni, cophenetic_distances, nni};
+///
+/// let v = vec![0, 0, 0, 1, 3];
+/// let prev = cophenetic_distances(&v, false);
+/// let updated = cophenetic_after_nni(&v, &prev, 3);
+/// assert_eq!(updated, cophenetic_distances(&nni(&v, 3), false));
+/// ```
+pub fn cophenetic_after_nni(
+    v: &[usize],
+    prev: &[Vec<usize>],
+    edge_index: usize,
+) -> Vec<Vec<usize>> {
+    let ancestry = get_ancestry(v);
+    let n_leaves = v.len() + 1;
+    let (x, y_col, p, c_col) = _get_nni_nodes(&ancestry, edge_index);
+
+    let y_leaves = _get_leaves_below(&ancestry, ancestry[x - n_leaves][y_col]);
+    let b_leaves = _get_leaves_below(&ancestry, ancestry[x - n_leaves][1 - y_col]);
+    let c_leaves = _get_leaves_below(&ancestry, ancestry[p - n_leaves][c_col]);
+
+    let mut below_p = vec![false; n_leaves];
+    for &leaf in y_leaves.iter().chain(&b_leaves).chain(&c_leaves) {
+        below_p[leaf] = true;
+    }
+    let outside_leaves: Vec<usize> = (0..n_leaves).filter(|&leaf| !below_p[leaf]).collect();
+
+    let mut dist = prev.to_vec();
+    for &i in y_leaves.iter() {
+        for &j in b_leaves.iter() {
+            dist[i][j] += 1;
+            dist[j][i] += 1;
+        }
+        for &j in outside_leaves.iter() {
+            dist[i][j] -= 1;
+            dist[j][i] -= 1;
+        }
+    }
+    for &i in c_leaves.iter() {
+        for &j in b_leaves.iter() {
+            dist[i][j] -= 1;
+            dist[j][i] -= 1;
+        }
+        for &j in outside_leaves.iter() {
+            dist[i][j] += 1;
+            dist[j][i] += 1;
+        }
+    }
+
+    dist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_vec::ops::metrics::robinson_foulds;
+    use crate::tree_vec::ops::to_newick_from_vector;
+    use crate::utils::{check_v, sample_vector};
+    use rstest::*;
+
+    /// Test that both implementations of get_pairs agree
+    #[rstest]
+    #[case(10, true)]
+    #[case(10, false)]
+    #[case(100, true)]
+    #[case(100, false)]
+    fn test_get_pairs_avl(#[case] n_leaves: usize, #[case] ordered: bool) {
+        let v = sample_vector(n_leaves, ordered);
+        assert_eq!(get_pairs(&v), get_pairs_avl(&v));
+    }
+
+    /// Test that rotating a node changes the Newick order but not the vector
+    #[rstest]
+    #[case(vec![0, 0, 1], 6, "((1,3)4,(0,2)5)6;")]
+    #[case(vec![0, 0, 1], 5, "((2,0)5,(1,3)4)6;")]
+    #[case(vec![0, 0, 0, 1, 3], 8, "((((3,5)6,0)8,2)9,(1,4)7)10;")]
+    fn test_rotate_node(#[case] v: Vec<usize>, #[case] node: usize, #[case] expected: &str) {
+        let rotated = rotate_node(&get_ancestry(&v), node);
+        let newick = build_newick(&rotated);
+
+        assert_eq!(newick, expected);
+        assert_ne!(newick, to_newick_from_vector(&v));
+        assert_eq!(to_vector(&newick), v);
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], 3)]
+    #[case(vec![0, 0, 1], 7)]
+    #[should_panic]
+    fn test_rotate_node_not_internal(#[case] v: Vec<usize>, #[case] node: usize) {
+        rotate_node(&get_ancestry(&v), node);
+    }
+
+    #[rstest]
+    #[case(vec![0], true, "(0,1)2;")]
+    #[case(vec![0, 1, 2], true, "(0,(1,(2,3)4)5)6;")]
+    #[case(vec![0, 1, 2], false, "(((2,3)4,1)5,0)6;")]
+    #[case(vec![0, 0, 0, 1, 3], true, "((1,4)7,(2,(0,(3,5)6)8)9)10;")]
+    #[case(vec![0, 0, 0, 1, 3], false, "((((3,5)6,0)8,2)9,(1,4)7)10;")]
+    fn test_ladderize(#[case] v: Vec<usize>, #[case] ascending: bool, #[case] expected: &str) {
+        let newick = build_newick(&ladderize(&get_ancestry(&v), ascending));
+        assert_eq!(newick, expected);
+        assert_eq!(to_vector(&newick), v);
+    }
+
+    /// Test that all orderings of a tree ladderize to the same ancestry
+    #[rstest]
+    #[case(10)]
+    #[case(100)]
+    fn test_ladderize_rotations(#[case] n_leaves: usize) {
+        let ancestry = get_ancestry(&sample_vector(n_leaves, false));
+
+        let mut rotated = ancestry.clone();
+        for node in (n_leaves..2 * n_leaves - 1).step_by(3) {
+            rotated = rotate_node(&rotated, node);
+        }
+
+        for ascending in [true, false] {
+            assert_eq!(
+                ladderize(&rotated, ascending),
+                ladderize(&ancestry, ascending)
+            );
+        }
+    }
+
+    /// Test that the root is an ancestor of every other node
+    /// and that a leaf is an ancestor of nothing
+    #[rstest]
+    #[case(vec![0])]
+    #[case(vec![0, 0, 1])]
+    #[case(vec![0, 1, 2, 3, 4])]
+    #[case(vec![0, 0, 0, 3, 2, 9, 4, 1, 12])]
+    fn test_is_ancestor_root_and_leaves(#[case] v: Vec<usize>) {
+        let n_leaves = v.len() + 1;
+        let root = 2 * v.len();
+
+        for node in 0..root {
+            assert!(is_ancestor(&v, root, node));
+            assert!(!is_ancestor(&v, node, root));
+        }
+
+        for leaf in 0..n_leaves {
+            for node in 0..=root {
+                assert!(!is_ancestor(&v, leaf, node));
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], 5, 0, true)]
+    #[case(vec![0, 0, 1], 5, 2, true)]
+    #[case(vec![0, 0, 1], 5, 1, false)]
+    #[case(vec![0, 0, 1], 4, 5, false)]
+    #[case(vec![0, 0, 0, 1, 3], 8, 5, true)]
+    #[case(vec![0, 0, 0, 1, 3], 9, 6, true)]
+    #[case(vec![0, 0, 0, 1, 3], 7, 6, false)]
+    fn test_is_ancestor(
+        #[case] v: Vec<usize>,
+        #[case] a: usize,
+        #[case] b: usize,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(is_ancestor(&v, a, b), expected);
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_is_ancestor_out_of_bounds() {
+        is_ancestor(&[0, 0, 1], 7, 0);
+    }
+
+    #[rstest]
+    #[case(vec![vec![0, 0, 1], vec![0, 1, 2], vec![0, 2, 4]], false)]
+    #[case(vec![vec![0, 0, 1], vec![0, 1, 2], vec![0, 2, 4]], true)]
+    #[case(vec![vec![0, 0, 0, 1, 3, 3, 1, 4, 4], vec![0, 0, 0, 3, 2, 9, 4, 1, 12]], false)]
+    #[case(vec![], false)]
+    fn test_cophenetic_distances_batch(#[case] trees: Vec<Vec<usize>>, #[case] unrooted: bool) {
+        let batch = cophenetic_distances_batch(&trees, unrooted).unwrap();
+        assert_eq!(batch.len(), trees.len());
+        for (v, dist) in trees.iter().zip(batch.iter()) {
+            assert_eq!(*dist, cophenetic_distances(v, unrooted));
+        }
+    }
+
+    #[rstest]
+    fn test_cophenetic_distances_batch_mismatched_leaves() {
+        assert_eq!(
+            cophenetic_distances_batch(&[vec![0, 0, 1], vec![0, 1, 2], vec![0, 1]], false),
+            Err(Phylo2VecError::LeafCountMismatch {
+                index: 2,
+                expected: 4,
+                found: 3
+            })
+        );
+    }
+
+    /// Get the descendant leaves of each internal node using sorted vectors
+    fn descendant_vecs(v: &[usize]) -> Vec<Vec<usize>> {
+        let n_leaves = v.len() + 1;
+        let mut descendants: Vec<Vec<usize>> = Vec::new();
+        for [c1, c2, _] in get_ancestry(v) {
+            let mut leaves = Vec::new();
+            for child in [c1, c2] {
+                if child < n_leaves {
+                    leaves.push(child);
+                } else {
+                    leaves.extend(&descendants[child - n_leaves]);
+                }
+            }
+            leaves.sort();
+            descendants.push(leaves);
+        }
+        descendants
+    }
+
+    #[rstest]
+    #[case(vec![0])]
+    #[case(vec![0, 0, 1])]
+    #[case(vec![0, 0, 0, 1, 3, 3, 1, 4, 4])]
+    #[case(sample_vector(64, false))]
+    #[case(sample_vector(200, false))]
+    fn test_descendant_bitsets(#[case] v: Vec<usize>) {
+        let n_leaves = v.len() + 1;
+        let bitsets = descendant_bitsets(&v);
+        let expected = descendant_vecs(&v);
+
+        assert_eq!(bitsets.len(), expected.len());
+        for (bitset, leaves) in bitsets.iter().zip(expected.iter()) {
+            assert_eq!(bitset.len(), n_leaves.div_ceil(64));
+            let decoded: Vec<usize> = (0..n_leaves)
+                .filter(|&j| bitset[j / 64] & (1 << (j % 64)) != 0)
+                .collect();
+            assert_eq!(&decoded, leaves);
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0, 1], 0, "((0,2)3,1)4;")]
+    #[case(vec![0, 1], 1, "((0,1)3,2)4;")]
+    fn test_nni(#[case] v: Vec<usize>, #[case] edge_index: usize, #[case] expected: &str) {
+        let new_v = nni(&v, edge_index);
+        assert_eq!(to_newick_from_vector(&new_v), expected);
+    }
+
+    #[rstest]
+    #[case(vec![0], 0)]
+    #[case(vec![0, 0, 1], 4)]
+    #[should_panic]
+    fn test_nni_out_of_bounds(#[case] v: Vec<usize>, #[case] edge_index: usize) {
+        nni(&v, edge_index);
+    }
+
+    #[rstest]
+    #[case(vec![0, 1, 2, 3])]
+    #[case(vec![0, 0, 1, 2])]
+    #[case(vec![0, 2, 2, 6])]
+    #[case(vec![0, 0, 0, 1, 3])]
+    #[case(sample_vector(20, false))]
+    #[case(sample_vector(20, true))]
+    fn test_nni_neighbors(#[case] v: Vec<usize>) {
+        let n_leaves = v.len() + 1;
+        let neighbors = nni_neighbors(&v);
+        assert_eq!(neighbors.len(), 2 * (n_leaves - 3));
+
+        for neighbor in neighbors.iter() {
+            check_v(neighbor);
+            // An NNI changes exactly one split of the unrooted tree
+            assert_eq!(robinson_foulds(&v, neighbor), 2);
+        }
+        for (i, n1) in neighbors.iter().enumerate() {
+            for n2 in neighbors[i + 1..].iter() {
+                assert_ne!(robinson_foulds(n1, n2), 0);
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec![0])]
+    #[case(vec![0, 1])]
+    fn test_nni_neighbors_small(#[case] v: Vec<usize>) {
+        assert!(nni_neighbors(&v).is_empty());
+    }
+
+    #[rstest]
+    #[case(vec![], vec![0])]
+    #[case(vec![0], vec![0, 1, 2])]
+    #[case(vec![0, 1, 2], vec![0, 1, 2, 3, 4, 5, 6])]
+    #[case(vec![0, 0, 1], vec![0, 2, 5, 1, 3, 4, 6])]
+    // (((0,(3,5)6)8,2)9,(1,4)7)10;
+    #[case(vec![0, 0, 0, 1, 3], vec![0, 3, 5, 6, 8, 2, 9, 1, 4, 7, 10])]
+    fn test_postorder_sequence(#[case] v: Vec<usize>, #[case] expected: Vec<usize>) {
+        assert_eq!(postorder_sequence(&v), expected);
+    }
+
+    /// Test that every node comes after its children
+    #[rstest]
+    #[case(sample_vector(50, false))]
+    #[case(sample_vector(50, true))]
+    fn test_postorder_sequence_sampled(#[case] v: Vec<usize>) {
+        let sequence = postorder_sequence(&v);
+        assert_eq!(sequence.len(), 2 * v.len() + 1);
+
+        let mut position = vec![usize::MAX; sequence.len()];
+        for (i, &node) in sequence.iter().enumerate() {
+            position[node] = i;
+        }
+        for [c1, c2, p] in get_ancestry(&v) {
+            assert!(position[c1] < position[p]);
+            assert!(position[c2] < position[p]);
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0], 0, Some(2))]
+    #[case(vec![0], 2, None)]
+    #[case(vec![0, 0, 1], 2, Some(5))]
+    #[case(vec![0, 0, 1], 3, Some(4))]
+    #[case(vec![0, 0, 1], 5, Some(6))]
+    #[case(vec![0, 0, 1], 6, None)]
+    fn test_parent_of(#[case] v: Vec<usize>, #[case] node: usize, #[case] expected: Option<usize>) {
+        assert_eq!(parent_of(&v, node), expected);
+    }
+
+    /// Test that the parent of each child of a cherry is the parent of the cherry
+    #[rstest]
+    #[case(sample_vector(50, false))]
+    #[case(sample_vector(50, true))]
+    fn test_parent_of_sampled(#[case] v: Vec<usize>) {
+        assert_eq!(parent_of(&v, 2 * v.len()), None);
+        for [c1, c2, p] in get_ancestry(&v) {
+            assert_eq!(parent_of(&v, c1), Some(p));
+            assert_eq!(parent_of(&v, c2), Some(p));
+        }
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_parent_of_out_of_bounds() {
+        parent_of(&[0, 0, 1], 7);
+    }
+
+    #[rstest]
+    #[case(vec![0], 0, 1, 2)]
+    #[case(vec![0, 0, 1], 0, 2, 5)]
+    #[case(vec![0, 0, 1], 3, 1, 4)]
+    #[case(vec![0, 0, 1], 2, 3, 6)]
+    #[case(vec![0, 0, 1], 3, 3, 3)]
+    #[case(vec![0, 0, 0, 1, 3], 3, 5, 6)]
+    #[case(vec![0, 0, 0, 1, 3], 0, 5, 8)]
+    #[case(vec![0, 0, 0, 1, 3], 4, 2, 10)]
+    fn test_mrca(
+        #[case] v: Vec<usize>,
+        #[case] leaf_a: usize,
+        #[case] leaf_b: usize,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(mrca(&v, leaf_a, leaf_b), Ok(expected));
+        assert_eq!(mrca(&v, leaf_b, leaf_a), Ok(expected));
+    }
+
+    /// Test that the MRCA matches the LCA index for every pair of leaves
+    #[rstest]
+    #[case(sample_vector(20, false))]
+    #[case(sample_vector(20, true))]
+    fn test_mrca_sampled(#[case] v: Vec<usize>) {
+        let index = LcaIndex::new(&v);
+        for a in 0..=v.len() {
+            for b in 0..=v.len() {
+                assert_eq!(mrca(&v, a, b), Ok(index.lca(a, b)));
+            }
+        }
+    }
+
+    #[rstest]
+    fn test_mrca_out_of_bounds() {
+        assert_eq!(
+            mrca(&[0, 0, 1], 0, 4),
+            Err(Phylo2VecError::InvalidNode { node: 4, max: 3 })
+        );
+    }
+
+    #[rstest]
+    #[case(vec![0], 0, 1, false, 2)]
+    #[case(vec![0], 0, 1, true, 1)]
+    #[case(vec![0, 0, 1], 0, 2, false, 2)]
+    #[case(vec![0, 0, 1], 0, 2, true, 2)]
+    #[case(vec![0, 0, 1], 2, 3, false, 4)]
+    #[case(vec![0, 0, 1], 2, 3, true, 3)]
+    #[case(vec![0, 0, 1], 1, 1, true, 0)]
+    #[case(vec![0, 0, 0, 1, 3], 3, 2, false, 4)]
+    #[case(vec![0, 0, 0, 1, 3], 5, 4, false, 6)]
+    #[case(vec![0, 0, 0, 1, 3], 5, 4, true, 5)]
+    // With one leaf, the leaf is also the root
+    #[case(vec![], 0, 0, false, 0)]
+    #[case(vec![], 0, 0, true, 0)]
+    fn test_path_length(
+        #[case] v: Vec<usize>,
+        #[case] leaf_a: usize,
+        #[case] leaf_b: usize,
+        #[case] unrooted: bool,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(path_length(&v, leaf_a, leaf_b, unrooted), Ok(expected));
+        assert_eq!(path_length(&v, leaf_b, leaf_a, unrooted), Ok(expected));
+    }
+
+    /// Test that path lengths match the cophenetic distances
+    #[rstest]
+    #[case(sample_vector(20, false), false)]
+    #[case(sample_vector(20, false), true)]
+    #[case(sample_vector(20, true), false)]
+    #[case(sample_vector(20, true), true)]
+    fn test_path_length_cophenetic(#[case] v: Vec<usize>, #[case] unrooted: bool) {
+        let coph = cophenetic_distances(&v, unrooted);
+        for (a, row) in coph.iter().enumerate() {
+            for (b, &dist) in row.iter().enumerate() {
+                assert_eq!(path_length(&v, a, b, unrooted), Ok(dist));
+            }
+        }
+    }
+
+    #[rstest]
+    fn test_path_length_out_of_bounds() {
+        assert_eq!(
+            path_length(&[0, 0, 1], 4, 0, false),
+            Err(Phylo2VecError::InvalidNode { node: 4, max: 3 })
+        );
+    }
+
+    /// Test that the row-by-row and serial implementations are identical
+    #[rstest]
+    #[case(vec![0])]
+    #[case(vec![0, 0, 0, 1, 3, 3, 1, 4, 4])]
+    #[case(sample_vector(50, false))]
+    #[case(sample_vector(50, true))]
+    #[case(sample_vector(200, false))]
+    fn test_cophenetic_rows_serial(#[case] v: Vec<usize>, #[values(false, true)] unrooted: bool) {
+        let ancestry = get_ancestry(&v);
+        let rows: Vec<Vec<usize>> = (0..=v.len())
+            .map(|leaf| _cophenetic_row(&ancestry, leaf, unrooted))
+            .collect();
+        let serial = _cophenetic_distances_serial(&v, unrooted);
+
+        assert_eq!(rows, serial);
+        assert_eq!(cophenetic_distances(&v, unrooted), serial);
+    }
+
+    /// Test a reduction over the tree: the sum of leaf ids
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec![0, 0, 1])]
+    #[case(sample_vector(100, false))]
+    #[case(sample_vector(100, true))]
+    fn test_visit_postorder(#[case] v: Vec<usize>) {
+        let n_leaves = v.len() + 1;
+        let mut leaf_sums = vec![0; 2 * n_leaves - 1];
+        let mut n_visits = 0;
+        visit_postorder(&v, |visit| {
+            n_visits += 1;
+            assert_eq!(visit.is_leaf, visit.id < n_leaves);
+            leaf_sums[visit.id] = match visit.children {
+                Some((c1, c2)) => leaf_sums[c1] + leaf_sums[c2],
+                None => visit.id,
+            };
+        });
+
+        assert_eq!(n_visits, 2 * n_leaves - 1);
+        assert_eq!(leaf_sums[2 * n_leaves - 2], (0..n_leaves).sum::<usize>());
+    }
+
+    /// Test that different rootings of the same unrooted tree share a canonical form
+    #[rstest]
+    #[case(vec![
+        "(((0,1),(2,3)),4);",
+        "((0,1),((2,3),4));",
+        "(((0,1),4),(2,3));",
+        "(0,(1,((2,3),4)));",
+    ], "(((0,2),(1,3)),4);")]
+    #[case(vec![
+        "((0,1),(2,3));",
+        "(((0,1),2),3);",
+        "(0,(1,(2,3)));",
+    ], "((0,2),(1,3));")]
+    fn test_unrooted_canonical(#[case] newicks: Vec<&str>, #[case] other: &str) {
+        let canonical = unrooted_canonical(&to_vector(newicks[0]));
+        for newick in newicks.iter() {
+            assert_eq!(unrooted_canonical(&to_vector(newick)), canonical);
+        }
+        assert_ne!(unrooted_canonical(&to_vector(other)), canonical);
+    }
+
+    /// Test that the canonical form is idempotent and keeps the unrooted topology
+    #[rstest]
+    #[case(sample_vector(3, false))]
+    #[case(sample_vector(50, false))]
+    #[case(sample_vector(50, true))]
+    fn test_unrooted_canonical_sampled(#[case] v: Vec<usize>) {
+        let canonical = unrooted_canonical(&v);
+        check_v(&canonical);
+        assert_eq!(robinson_foulds(&v, &canonical), 0);
+        assert_eq!(unrooted_canonical(&canonical), canonical);
+
+        // NNI neighbours have a different unrooted topology
+        for neighbor in nni_neighbors(&v) {
+            assert_ne!(unrooted_canonical(&neighbor), canonical);
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0], 1)]
+    #[case(vec![0, 0, 1], 2)]
+    #[case((0..99).collect(), 1)] // Caterpillar
+    #[case(to_vector("(((0,1),(2,3)),((4,5),(6,7)));"), 4)] // Balanced
+    #[case(to_vector("(((0,(3,5)),2),(1,4));"), 2)]
+    fn test_count_cherries(#[case] v: Vec<usize>, #[case] expected: usize) {
+        assert_eq!(count_cherries(&v), expected);
+    }
+
+    #[rstest]
+    #[case(vec![0], vec![1, 1])]
+    #[case(vec![0, 0, 1], vec![2, 2, 2, 2])]
+    #[case(vec![0, 1, 2], vec![1, 2, 3, 3])]
+    #[case(vec![0, 0, 0, 1, 3], vec![3, 2, 2, 4, 2, 4])]
+    fn test_leaf_depths(#[case] v: Vec<usize>, #[case] expected: Vec<usize>) {
+        assert_eq!(leaf_depths(&v), expected);
+    }
+
+    /// Test that leaf depths match the number of edges to the root
+    #[rstest]
+    #[case(sample_vector(50, false))]
+    #[case(sample_vector(50, true))]
+    fn test_leaf_depths_sampled(#[case] v: Vec<usize>) {
+        let depths = leaf_depths(&v);
+        let ancestry = get_ancestry(&v);
+        let parents = _get_parent_vector(&ancestry);
+
+        for (leaf, &depth) in depths.iter().enumerate() {
+            let mut n_edges = 0;
+            let mut node = leaf;
+            while node != 2 * v.len() {
+                node = parents[node];
+                n_edges += 1;
+            }
+            assert_eq!(depth, n_edges);
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0], vec![vec![1, 0], vec![0, 1]])]
+    #[case(vec![0, 0, 1], vec![vec![2, 0, 1, 0], vec![0, 2, 0, 1], vec![1, 0, 2, 0], vec![0, 1, 0, 2]])]
+    fn test_mrca_depth_matrix(#[case] v: Vec<usize>, #[case] expected: Vec<Vec<usize>>) {
+        assert_eq!(mrca_depth_matrix(&v), expected);
+    }
+
+    /// Test that unit branch lengths give the topological cophenetic distances
+    #[rstest]
+    #[case(vec![0])]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(100, true))]
+    fn test_cophenetic_distances_with_unit_bls(#[case] v: Vec<usize>) {
+        let bls = vec![(1.0, 1.0); v.len()];
+        let expected: Vec<Vec<f64>> = cophenetic_distances(&v, false)
+            .iter()
+            .map(|row| row.iter().map(|&d| d as f64).collect())
+            .collect();
+        assert_eq!(cophenetic_distances_with_bls(&v, &bls), expected);
+    }
+
+    /// Test that the weighted distances are symmetric with a zero diagonal,
+    /// and satisfy the four-point condition of tree metrics
+    #[rstest]
+    #[case(10)]
+    #[case(50)]
+    fn test_cophenetic_distances_with_bls_sampled(#[case] n_leaves: usize) {
+        let v = sample_vector(n_leaves, false);
+        let bls: Vec<(f64, f64)> = (0..v.len())
+            .map(|k| (0.1 * (k + 1) as f64, 0.2 * (k + 1) as f64))
+            .collect();
+        let dist = cophenetic_distances_with_bls(&v, &bls);
+
+        assert_eq!(dist.len(), n_leaves);
+        for (i, row) in dist.iter().enumerate() {
+            assert_eq!(row[i], 0.0);
+            for (j, &d) in row.iter().enumerate() {
+                assert_eq!(d, dist[j][i]);
+                if i != j {
+                    assert!(d > 0.0);
+                }
+            }
+        }
+
+        let (a, b, c, d) = (0, 1, 2, 3);
+        let mut sums = [
+            dist[a][b] + dist[c][d],
+            dist[a][c] + dist[b][d],
+            dist[a][d] + dist[b][c],
+        ];
+        sums.sort_by(|x, y| x.partial_cmp(y).unwrap());
+        assert!((sums[2] - sums[1]).abs() < 1e-4);
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_cophenetic_distances_with_bls_mismatched_bls() {
+        cophenetic_distances_with_bls(&[0, 0, 1], &[(1.0, 1.0)]);
+    }
+
+    /// Test that coph[i][j] = depth[i] + depth[j] - 2 * mrca_depth[i][j]
+    #[rstest]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(100, false))]
+    #[case(sample_vector(100, true))]
+    fn test_mrca_depth_matrix_cophenetic(#[case] v: Vec<usize>) {
+        let mrca_depths = mrca_depth_matrix(&v);
+        let depths = leaf_depths(&v);
+        let coph = cophenetic_distances(&v, false);
+
+        for i in 0..depths.len() {
+            for j in 0..depths.len() {
+                assert_eq!(coph[i][j], depths[i] + depths[j] - 2 * mrca_depths[i][j]);
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], vec![0, 2], true)]
+    #[case(vec![0, 0, 1], vec![3, 1], true)]
+    #[case(vec![0, 0, 1], vec![0, 1, 2, 3], true)]
+    #[case(vec![0, 0, 1], vec![0, 1], false)]
+    #[case(vec![0, 0, 1], vec![0, 2, 3], false)]
+    #[case(vec![0, 0, 1], vec![2], false)]
+    #[case(vec![0, 0, 1], vec![], false)]
+    #[case(vec![0, 0, 0, 1, 3], vec![0, 3, 5], true)]
+    #[case(vec![0, 0, 0, 1, 3], vec![5, 3, 3], true)]
+    #[case(vec![0, 0, 0, 1, 3], vec![0, 3, 5, 2], true)]
+    #[case(vec![0, 0, 0, 1, 3], vec![0, 2], false)]
+    #[case(vec![0, 0, 0, 1, 3], vec![2, 1, 4], false)]
+    fn test_is_monophyletic(
+        #[case] v: Vec<usize>,
+        #[case] leaves: Vec<usize>,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(is_monophyletic(&v, &leaves), expected);
+    }
+
+    /// Test that the descendants of every internal node form a clade,
+    /// and that other sets of leaves do not
+    #[rstest]
+    #[case(sample_vector(20, false))]
+    #[case(sample_vector(50, true))]
+    fn test_is_monophyletic_sampled(#[case] v: Vec<usize>) {
+        let ancestry = get_ancestry(&v);
+        let clades: Vec<Vec<usize>> = (v.len() + 1..=2 * v.len())
+            .map(|node| {
+                let mut leaves = _get_leaves_below(&ancestry, node);
+                leaves.sort();
+                leaves
+            })
+            .collect();
+
+        for leaves in clades.iter() {
+            assert!(is_monophyletic(&v, leaves));
+
+            // Adding another leaf gives a clade only if it matches another node
+            for other in (0..=v.len()).filter(|leaf| !leaves.contains(leaf)) {
+                let mut extended = leaves.clone();
+                extended.push(other);
+                extended.sort();
+                assert_eq!(is_monophyletic(&v, &extended), clades.contains(&extended));
+            }
+        }
+    }
+
+    /// Test that the incremental update matches a fresh computation for every NNI
+    #[rstest]
+    #[case(vec![0, 1])]
+    #[case(vec![0, 0, 0, 1, 3, 3, 1, 4, 4])]
+    #[case(sample_vector(10, false))]
+    #[case(sample_vector(50, false))]
+    #[case(sample_vector(50, true))]
+    fn test_cophenetic_after_nni(#[case] v: Vec<usize>) {
+        let prev = cophenetic_distances(&v, false);
+        for edge_index in 0..2 * (v.len() - 1) {
+            let expected = cophenetic_distances(&nni(&v, edge_index), false);
+            assert_eq!(cophenetic_after_nni(&v, &prev, edge_index), expected);
+        }
+    }
+
+    /// Test chains of random NNIs, updating the matrix incrementally
+    #[rstest]
+    #[case(20)]
+    #[case(100)]
+    fn test_cophenetic_after_nni_chain(#[case] n_leaves: usize) {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        let mut v = sample_vector(n_leaves, false);
+        let mut dist = cophenetic_distances(&v, false);
+        for _ in 0..50 {
+            let edge_index = rng.gen_range(0..2 * (n_leaves - 2));
+            dist = cophenetic_after_nni(&v, &dist, edge_index);
+            v = nni(&v, edge_index);
+            assert_eq!(dist, cophenetic_distances(&v, false));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::tree_vec::ops;
+#[cfg(feature = "matrix")]
+use crate::tree_vec::ops::matrix::parse_matrix;
+use crate::tree_vec::types::Ancestry;
+use crate::tree_vec::TreeVec;
+
+/// Common interface of the representations of a (binary, rooted) tree
+///
+/// Implemented for Phylo2Vec vectors (`Vec<usize>`), Phylo2Vec matrices
+/// (`Vec<Vec<f32>>`, where the first column is the vector and the other two
+/// hold branch lengths, with the `matrix` feature) and `TreeVec`, so that functions can accept `impl Tree`
+/// regardless of the representation.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::Tree;
+///
+/// fn describe(tree: &impl Tree) -> String {
+///     format!("{} leaves: {}", tree.n_leaves(), tree.to_newick())
+/// }
+///
+/// assert_eq!(describe(&vec![0, 0, 1]), "4 leaves: ((0,2)5,(1,3)4)6;");
+/// ```
+pub trait Tree {
+    /// Converts the tree to Newick format
+    fn to_newick(&self) -> String;
+
+    /// Gets the number of leaves of the tree
+    fn n_leaves(&self) -> usize;
+
+    /// Gets the ancestry matrix representation of the tree
+    fn get_ancestry(&self) -> Ancestry;
+}
+
+impl Tree for Vec<usize> {
+    fn to_newick(&self) -> String {
+        ops::to_newick_from_vector(self)
+    }
+
+    fn n_leaves(&self) -> usize {
+        self.len() + 1
+    }
+
+    fn get_ancestry(&self) -> Ancestry {
+        ops::get_ancestry(self)
+    }
+}
+
+#[cfg(feature = "matrix")]
+impl Tree for Vec<Vec<f32>> {
+    /// Converts the tree to Newick format, including branch lengths
+    fn to_newick(&self) -> String {
+        ops::to_newick_from_matrix(self)
+    }
+
+    fn n_leaves(&self) -> usize {
+        self.len() + 1
+    }
+
+    fn get_ancestry(&self) -> Ancestry {
+        let (v, _) = parse_matrix(self);
+        ops::get_ancestry(&v)
+    }
+}
+
+impl Tree for TreeVec {
+    fn to_newick(&self) -> String {
+        TreeVec::to_newick(self)
+    }
+
+    fn n_leaves(&self) -> usize {
+        self.data.len() + 1
+    }
+
+    fn get_ancestry(&self) -> Ancestry {
+        TreeVec::get_ancestry(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    /// A generic function over any tree representation
+    fn summarize(tree: &impl Tree) -> (usize, Ancestry, String) {
+        (tree.n_leaves(), tree.get_ancestry(), tree.to_newick())
+    }
+
+    #[rstest]
+    #[case(vec![0, 0, 1], vec![[1, 3, 4], [0, 2, 5], [5, 4, 6]], "((0,2)5,(1,3)4)6;")]
+    #[case(vec![0, 1, 2, 3, 4], vec![[4, 5, 6], [3, 6, 7], [2, 7, 8], [1, 8, 9], [0, 9, 10]], "(0,(1,(2,(3,(4,5)6)7)8)9)10;")]
+    fn test_tree_vector_and_treevec(
+        #[case] v: Vec<usize>,
+        #[case] expected_ancestry: Ancestry,
+        #[case] expected_newick: &str,
+    ) {
+        let n_leaves = v.len() + 1;
+        let expected = (n_leaves, expected_ancestry, expected_newick.to_string());
+
+        assert_eq!(summarize(&v), expected);
+        assert_eq!(summarize(&TreeVec::new(v, None, None)), expected);
+    }
+
+    #[rstest]
+    #[cfg(feature = "matrix")]
+    fn test_tree_matrix() {
+        let m = vec![
+            vec![0.0, 0.9, 0.4],
+            vec![0.0, 0.8, 3.0],
+            vec![3.0, 0.4, 0.5],
+        ];
+        let (n_leaves, ancestry, newick) = summarize(&m);
+
+        assert_eq!(n_leaves, 4);
+        assert_eq!(ancestry, summarize(&vec![0, 0, 3]).1);
+        assert_eq!(newick, "(((0:0.9,2:0.4)4:0.8,3:3.0)5:0.4,1:0.5)6;");
+    }
+}
@@ -6,3 +6,86 @@ pub type Ancestry = Vec<[usize; 3]>;
 
 /// A type alias for the PairsVec type, which is a vector of tuples representing (child1, child2)
 pub type PairsVec = Vec<Pair>;
+
+/// A type alias for possibly missing branch lengths, as [bl_child1, bl_child2] for each cherry
+pub type OptionalBranchLengths = Vec<[Option<f32>; 2]>;
+
+/// Integer types that can store the elements of a Phylo2Vec vector
+///
+/// Vectors default to `usize`, but smaller types such as `u32` save memory
+/// when storing many trees. Functions generic over `LeafIndex` read them
+/// directly, without first copying them to a `Vec<usize>`.
+///
+/// Signed types are supported so that untyped integer literals (which
+/// default to `i32`) and R integer vectors work as before. Negative
+/// elements are rejected when they are read.
+///
+/// # Example
+/// ```
+/// use phylo2vec::tree_vec::ops::to_newick_from_vector;
+///
+/// let v: Vec<u32> = vec![0, 0, 1];
+/// assert_eq!(to_newick_from_vector(&v), "((0,2)5,(1,3)4)6;");
+/// ```
+pub trait LeafIndex: Copy {
+    /// Convert the element to a `usize`
+    fn to_usize(self) -> usize;
+
+    /// Convert a `usize` to the element type
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` does not fit in the element type
+    fn from_usize(value: usize) -> Self;
+}
+
+macro_rules! impl_leaf_index {
+    ($($t:ty),*) => {
+        $(
+            impl LeafIndex for $t {
+                #[inline]
+                fn to_usize(self) -> usize {
+                    usize::try_from(self).unwrap_or_else(|_| {
+                        panic!("{} is not a valid vector element", self)
+                    })
+                }
+
+                #[inline]
+                fn from_usize(value: usize) -> Self {
+                    <$t>::try_from(value).unwrap_or_else(|_| {
+                        panic!("{} does not fit in {}", value, stringify!($t))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_leaf_index!(u8, u16, u32, u64, usize, i32, i64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn test_leaf_index_round_trip() {
+        assert_eq!(u8::from_usize(255).to_usize(), 255);
+        assert_eq!(u16::from_usize(1000).to_usize(), 1000);
+        assert_eq!(u32::from_usize(70000).to_usize(), 70000);
+        assert_eq!(usize::from_usize(usize::MAX).to_usize(), usize::MAX);
+        assert_eq!(i32::from_usize(42).to_usize(), 42);
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_leaf_index_overflow() {
+        u8::from_usize(256);
+    }
+
+    #[rstest]
+    #[should_panic]
+    fn test_leaf_index_negative() {
+        (-1i32).to_usize();
+    }
+}
@@ -1,4 +1,12 @@
-use rand::{distributions::Uniform, prelude::Distribution, Rng};
+use rand::{
+    distributions::{Uniform, WeightedIndex},
+    prelude::Distribution,
+    rngs::StdRng,
+    Rng, SeedableRng,
+};
+
+use crate::errors::Phylo2VecError;
+use crate::tree_vec::types::LeafIndex;
 
 /// Sample a vector with `n_leaves` elements.
 ///
@@ -14,8 +22,13 @@ use rand::{distributions::Uniform, prelude::Distribution, Rng};
 /// let v2 = sample_vector(5, true);
 /// ```
 pub fn sample_vector(n_leaves: usize, ordering: bool) -> Vec<usize> {
-    let mut v: Vec<usize> = Vec::with_capacity(n_leaves);
     let mut rng = rand::thread_rng();
+    _sample_vector_rng(n_leaves, ordering, &mut rng)
+}
+
+/// Sample a vector with `n_leaves` elements using the given random number generator
+fn _sample_vector_rng<R: Rng>(n_leaves: usize, ordering: bool, rng: &mut R) -> Vec<usize> {
+    let mut v: Vec<usize> = Vec::with_capacity(n_leaves);
 
     match ordering {
         true => {
@@ -33,6 +46,103 @@ pub fn sample_vector(n_leaves: usize, ordering: bool) -> Vec<usize> {
     v
 }
 
+/// Sample a forest of `n_trees` random trees sharing a pool of `n_leaves` leaves.
+///
+/// Partition rule: the leaves are split into `n_trees` contiguous blocks
+/// whose sizes differ by at most one, the first `n_leaves % n_trees` trees
+/// receiving one extra leaf. Tree j covers the global leaves
+/// `offset_j, ..., offset_j + size_j - 1` (where `offset_j` is the total size of
+/// the previous trees), relabelled `0, ..., size_j - 1` in its vector.
+/// Each tree is then sampled uniformly (unordered), as in `sample_vector`.
+/// The random number generator is seeded with `seed`.
+///
+/// # Panics
+///
+/// Panics if `n_trees` is 0 or if there are fewer than 2 leaves per tree
+///
+/// # Examples
+///
+/// ```
+/// use phylo2vec::utils::sample_forest;
+/// let forest = sample_forest(10, 3, 42);
+/// let sizes: Vec<usize> = forest.iter().map(|v| v.len() + 1).collect();
+/// assert_eq!(sizes, vec![4, 3, 3]);
+/// ```
+pub fn sample_forest(n_leaves: usize, n_trees: usize, seed: u64) -> Vec<Vec<usize>> {
+    assert!(n_trees > 0, "The number of trees must be positive");
+    assert!(
+        n_leaves >= 2 * n_trees,
+        "Cannot partition {} leaves into {} trees of at least 2 leaves",
+        n_leaves,
+        n_trees
+    );
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let base_size = n_leaves / n_trees;
+    let n_larger = n_leaves % n_trees;
+
+    (0..n_trees)
+        .map(|j| {
+            let size = if j < n_larger {
+                base_size + 1
+            } else {
+                base_size
+            };
+            _sample_vector_rng(size, false, &mut rng)
+        })
+        .collect()
+}
+
+/// Ordering constraint of sampled trees (see `sample_vector`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleOrdering {
+    /// v_i in {0, 1, ..., i}
+    Ordered,
+    /// v_i in {0, 1, ..., 2*i}
+    Unordered,
+}
+
+/// Lazily sample random trees with `n_leaves` leaves
+///
+/// Each call to `next()` advances a random number generator seeded with
+/// `seed` and yields a new vector, as in `sample_vector`. The stream never
+/// ends, so two streams with the same arguments yield the same trees.
+///
+/// # Examples
+///
+/// ```
+/// use phylo2vec::utils::{tree_stream, SampleOrdering};
+/// let trees: Vec<Vec<usize>> = tree_stream(10, SampleOrdering::Unordered, 42).take(3).collect();
+/// assert_eq!(trees.len(), 3);
+/// ```
+pub fn tree_stream(
+    n_leaves: usize,
+    ordering: SampleOrdering,
+    seed: u64,
+) -> impl Iterator<Item = Vec<usize>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let ordered = ordering == SampleOrdering::Ordered;
+    std::iter::repeat_with(move || _sample_vector_rng(n_leaves, ordered, &mut rng))
+}
+
+/// Sample a vector with `n_leaves` elements, reproducibly
+///
+/// Same as `sample_vector`, but the random number generator is seeded with
+/// `seed`, so the same arguments always yield the same vector (on any
+/// machine). This is the first tree of `tree_stream` with the same arguments.
+///
+/// # Examples
+///
+/// ```
+/// use phylo2vec::utils::{sample_vector_seeded, SampleOrdering};
+/// let v = sample_vector_seeded(10, SampleOrdering::Unordered, 42);
+/// assert_eq!(v, sample_vector_seeded(10, SampleOrdering::Unordered, 42));
+/// ```
+pub fn sample_vector_seeded(n_leaves: usize, ordering: SampleOrdering, seed: u64) -> Vec<usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    _sample_vector_rng(n_leaves, ordering == SampleOrdering::Ordered, &mut rng)
+}
+
 /// Sample a matrix with `n_leaves` elements.
 ///
 /// If ordering is True, sample an ordered tree, by default ordering is False
@@ -78,6 +188,89 @@ pub fn sample_matrix(n_leaves: usize, ordered: bool) -> Vec<Vec<f32>> {
     m
 }
 
+/// Sample a vector with a user-supplied upper bound for each element.
+///
+/// `bounds[i]` caps the value of `v[i]`: v_i in {0, 1, ..., bounds[i]}.
+/// The random number generator is seeded with `seed`, so the same bounds
+/// and seed always yield the same vector.
+///
+/// # Errors
+///
+/// Returns `Phylo2VecError::InvalidBound` if any bound violates the
+/// Phylo2Vec constraint (bounds[i] <= 2 * i)
+///
+/// # Examples
+///
+/// ```
+/// use phylo2vec::utils::sample_with_bounds;
+/// let v = sample_with_bounds(&[0, 1, 1, 5], 42).unwrap();
+/// assert_eq!(v.len(), 4);
+/// assert!(sample_with_bounds(&[0, 3], 42).is_err());
+/// ```
+pub fn sample_with_bounds(bounds: &[usize], seed: u64) -> Result<Vec<usize>, Phylo2VecError> {
+    let mut v: Vec<usize> = Vec::with_capacity(bounds.len());
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for (i, &bound) in bounds.iter().enumerate() {
+        let max = 2 * i;
+        if bound > max {
+            return Err(Phylo2VecError::InvalidBound {
+                index: i,
+                bound,
+                max,
+            });
+        }
+        v.push(rng.gen_range(0..=bound));
+    }
+
+    Ok(v)
+}
+
+/// Sample a leaf of a tree with probability proportional to per-leaf weights.
+///
+/// `weights[i]` is the (unnormalised) weight of leaf i. The random number
+/// generator is seeded with `seed`, so the same weights and seed always
+/// yield the same leaf.
+///
+/// # Panics
+///
+/// Panics if:
+/// - `weights` does not have one entry per leaf (v.len() + 1)
+/// - Any weight is negative or not finite
+/// - All weights are zero
+///
+/// # Examples
+///
+/// ```
+/// use phylo2vec::utils::sample_leaf_weighted;
+/// let v = vec![0, 0, 1];
+/// let leaf = sample_leaf_weighted(&v, &[0.0, 0.0, 1.0, 0.0], 42);
+/// assert_eq!(leaf, 2);
+/// ```
+pub fn sample_leaf_weighted(v: &[usize], weights: &[f64], seed: u64) -> usize {
+    let n_leaves = v.len() + 1;
+    assert_eq!(
+        weights.len(),
+        n_leaves,
+        "Expected {} weights (one per leaf), found {}",
+        n_leaves,
+        weights.len()
+    );
+    for (i, &w) in weights.iter().enumerate() {
+        assert!(
+            w.is_finite() && w >= 0.0,
+            "Weights must be non-negative: weights[{}] = {}",
+            i,
+            w
+        );
+    }
+
+    let dist = WeightedIndex::new(weights).expect("At least one weight must be positive");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    dist.sample(&mut rng)
+}
+
 /// Input validation of a Phylo2Vec vector
 ///
 /// The input is checked to satisfy the Phylo2Vec constraints
@@ -93,11 +286,54 @@ pub fn sample_matrix(n_leaves: usize, ordered: bool) -> Vec<Vec<f32>> {
 /// check_v(&vec![0, 0, 1]);
 /// ```
 pub fn check_v(v: &Vec<usize>) -> () {
-    for i in 0..v.len() {
-        _check_max(i, v[i]);
+    if let Err(error) = validate_v(v) {
+        panic!("{}", error);
     }
 }
 
+/// Input validation of a Phylo2Vec vector, without panicking
+///
+/// # Errors
+///
+/// Returns `Phylo2VecError::OutOfBounds` with the first index
+/// and value that violate the constraint v[i] <= 2 * i
+///
+/// # Examples
+///
+/// ```
+/// use phylo2vec::errors::Phylo2VecError;
+/// use phylo2vec::utils::validate_v;
+///
+/// assert_eq!(validate_v(&[0, 0, 1]), Ok(()));
+/// assert_eq!(
+///     validate_v(&[0, 3, 1]),
+///     Err(Phylo2VecError::OutOfBounds { index: 1, value: 3, max: 2 })
+/// );
+/// ```
+pub fn validate_v(v: &[usize]) -> Result<(), Phylo2VecError> {
+    for (index, &value) in v.iter().enumerate() {
+        let max = 2 * index;
+        if value > max {
+            return Err(Phylo2VecError::OutOfBounds { index, value, max });
+        }
+    }
+    Ok(())
+}
+
+/// Check whether a vector satisfies the Phylo2Vec constraints
+///
+/// # Examples
+///
+/// ```
+/// use phylo2vec::utils::is_valid_v;
+///
+/// assert!(is_valid_v(&[0, 0, 1]));
+/// assert!(!is_valid_v(&[0, 3, 1]));
+/// ```
+pub fn is_valid_v(v: &[usize]) -> bool {
+    validate_v(v).is_ok()
+}
+
 /// Input validation of a Phylo2Vec matrix
 ///
 /// The input is checked for the Phylo2Vec constraints on the vector part (first column)
@@ -121,7 +357,7 @@ pub fn check_v(v: &Vec<usize>) -> () {
 ///
 pub fn check_m(matrix: &Vec<Vec<f32>>) -> () {
     // Validate the vector part (first column)
-    let vector: Vec<usize> = matrix.iter().map(|row| row[0] as usize).collect();
+    let vector: Vec<usize> = matrix.iter().map(|row| round_to_usize(row[0])).collect();
     check_v(&vector);
 
     // Ensure all branch lengths (remaining columns) are non-negative
@@ -133,6 +369,38 @@ pub fn check_m(matrix: &Vec<Vec<f32>>) -> () {
     }
 }
 
+/// Maximum distance to the nearest integer accepted by `round_to_usize`
+pub const INTEGER_TOLERANCE: f32 = 1e-4;
+
+/// Convert a float from the first column of a Phylo2Vec matrix to an integer
+///
+/// Matrices saved and loaded through other tools can store the vector part
+/// as floats such as `2e0` or `1.9999999`. These are rounded to the nearest
+/// integer, provided they lie within `INTEGER_TOLERANCE` of it.
+///
+/// # Panics
+///
+/// Panics if the value is negative or not within `INTEGER_TOLERANCE` of an integer
+///
+/// # Examples
+///
+/// ```
+/// use phylo2vec::utils::round_to_usize;
+///
+/// assert_eq!(round_to_usize(2e0), 2);
+/// assert_eq!(round_to_usize(1.9999999), 2);
+/// assert_eq!(round_to_usize("3e0".parse::<f32>().unwrap()), 3);
+/// ```
+pub fn round_to_usize(value: f32) -> usize {
+    let rounded = value.round();
+    assert!(
+        rounded >= 0.0 && (value - rounded).abs() <= INTEGER_TOLERANCE,
+        "Validation failed: {} is not a non-negative integer",
+        value
+    );
+    rounded as usize
+}
+
 /// Validate the maximum value of a Phylo2Vec vector element
 ///
 /// # Panics
@@ -172,10 +440,11 @@ fn _check_max(idx: usize, value: usize) -> () {
 ///
 /// assert_eq!(unordered, false);
 /// ```
-pub fn is_unordered(v: &Vec<usize>) -> bool {
-    for i in 0..v.len() {
-        _check_max(i, v[i]);
-        if v[i] > i + 1 {
+pub fn is_unordered<T: LeafIndex>(v: &[T]) -> bool {
+    for (i, &v_i) in v.iter().enumerate() {
+        let v_i = v_i.to_usize();
+        _check_max(i, v_i);
+        if v_i > i + 1 {
             return true;
         }
     }
@@ -228,6 +497,141 @@ mod tests {
         }
     }
 
+    #[rstest]
+    #[case(vec![0, 0, 0, 0, 0])]
+    #[case(vec![0, 1, 2, 3, 4])]
+    #[case(vec![0, 2, 4, 6, 8])]
+    fn test_sample_with_bounds(#[case] bounds: Vec<usize>) {
+        let v = sample_with_bounds(&bounds, 42).unwrap();
+        assert_eq!(v.len(), bounds.len());
+        check_v(&v);
+        for i in 0..bounds.len() {
+            assert!(v[i] <= bounds[i]);
+        }
+
+        // Same bounds and seed: same vector
+        assert_eq!(sample_with_bounds(&bounds, 42).unwrap(), v);
+    }
+
+    #[rstest]
+    #[case(vec![1], 0)]
+    #[case(vec![0, 1, 5, 1], 2)]
+    fn test_sample_with_bounds_infeasible(#[case] bounds: Vec<usize>, #[case] index: usize) {
+        let result = sample_with_bounds(&bounds, 42);
+        assert_eq!(
+            result,
+            Err(Phylo2VecError::InvalidBound {
+                index,
+                bound: bounds[index],
+                max: 2 * index,
+            })
+        );
+    }
+
+    #[rstest]
+    #[case(10, 3)]
+    #[case(10, 5)]
+    #[case(100, 7)]
+    #[case(2, 1)]
+    fn test_sample_forest(#[case] n_leaves: usize, #[case] n_trees: usize) {
+        let forest = sample_forest(n_leaves, n_trees, 42);
+        assert_eq!(forest.len(), n_trees);
+
+        let sizes: Vec<usize> = forest.iter().map(|v| v.len() + 1).collect();
+        assert_eq!(sizes.iter().sum::<usize>(), n_leaves);
+        assert!(sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 1);
+
+        for v in forest.iter() {
+            check_v(v);
+        }
+
+        // Same seed, same forest
+        assert_eq!(sample_forest(n_leaves, n_trees, 42), forest);
+    }
+
+    #[rstest]
+    #[case(10, SampleOrdering::Unordered)]
+    #[case(10, SampleOrdering::Ordered)]
+    #[case(100, SampleOrdering::Unordered)]
+    fn test_sample_vector_seeded(#[case] n_leaves: usize, #[case] ordering: SampleOrdering) {
+        let v = sample_vector_seeded(n_leaves, ordering, 42);
+        assert_eq!(v.len(), n_leaves - 1);
+        check_v(&v);
+        if ordering == SampleOrdering::Ordered {
+            assert!(v.iter().enumerate().all(|(i, &v_i)| v_i <= i));
+        }
+
+        assert_eq!(sample_vector_seeded(n_leaves, ordering, 42), v);
+        assert_eq!(tree_stream(n_leaves, ordering, 42).next().unwrap(), v);
+    }
+
+    #[rstest]
+    #[case(10, SampleOrdering::Unordered)]
+    #[case(10, SampleOrdering::Ordered)]
+    #[case(2, SampleOrdering::Unordered)]
+    fn test_tree_stream(#[case] n_leaves: usize, #[case] ordering: SampleOrdering) {
+        let trees: Vec<Vec<usize>> = tree_stream(n_leaves, ordering, 42).take(5).collect();
+        assert_eq!(trees.len(), 5);
+
+        for v in trees.iter() {
+            assert_eq!(v.len(), n_leaves - 1);
+            check_v(v);
+            if ordering == SampleOrdering::Ordered {
+                assert!(v.iter().enumerate().all(|(i, &v_i)| v_i <= i));
+            }
+        }
+
+        // Same seed, same stream
+        let trees2: Vec<Vec<usize>> = tree_stream(n_leaves, ordering, 42).take(5).collect();
+        assert_eq!(trees, trees2);
+    }
+
+    #[rstest]
+    #[should_panic]
+    #[case(5, 3)]
+    #[should_panic]
+    #[case(5, 0)]
+    fn test_sample_forest_infeasible(#[case] n_leaves: usize, #[case] n_trees: usize) {
+        sample_forest(n_leaves, n_trees, 42);
+    }
+
+    #[rstest]
+    #[case(vec![1.0, 0.0, 3.0, 6.0])]
+    #[case(vec![0.5, 0.5, 0.5, 0.5])]
+    #[case(vec![0.0, 0.0, 0.0, 2.0])]
+    fn test_sample_leaf_weighted(#[case] weights: Vec<f64>) {
+        let v = vec![0, 0, 1];
+        let n_draws = 20000;
+        let mut counts = vec![0; weights.len()];
+        for seed in 0..n_draws {
+            counts[sample_leaf_weighted(&v, &weights, seed)] += 1;
+        }
+
+        let total: f64 = weights.iter().sum();
+        for (count, w) in counts.iter().zip(weights.iter()) {
+            let freq = *count as f64 / n_draws as f64;
+            assert!(
+                (freq - w / total).abs() < 0.02,
+                "Empirical frequency {} does not match expected {}",
+                freq,
+                w / total
+            );
+        }
+    }
+
+    #[rstest]
+    #[should_panic]
+    #[case(vec![1.0, 1.0, 1.0])]
+    #[should_panic]
+    #[case(vec![1.0, -1.0, 1.0, 1.0])]
+    #[should_panic]
+    #[case(vec![0.0, 0.0, 0.0, 0.0])]
+    #[should_panic]
+    #[case(vec![1.0, f64::NAN, 1.0, 1.0])]
+    fn test_sample_leaf_weighted_invalid(#[case] weights: Vec<f64>) {
+        sample_leaf_weighted(&[0, 0, 1], &weights, 42);
+    }
+
     #[rstest]
     #[case(vec![0, 0, 1])]
     #[case(vec![0, 0, 2, 1, 8])]
@@ -237,6 +641,25 @@ mod tests {
         check_v(&v);
     }
 
+    #[rstest]
+    #[case(vec![], None)]
+    #[case(vec![0, 0, 1], None)]
+    #[case(vec![0, 0, 2, 1, 8], None)]
+    #[case(vec![0, 0, 9, 1], Some((2, 9)))]
+    #[case(vec![1, 3, 9], Some((0, 1)))]
+    fn test_validate_v(#[case] v: Vec<usize>, #[case] offending: Option<(usize, usize)>) {
+        let expected = match offending {
+            Some((index, value)) => Err(Phylo2VecError::OutOfBounds {
+                index,
+                value,
+                max: 2 * index,
+            }),
+            None => Ok(()),
+        };
+        assert_eq!(validate_v(&v), expected);
+        assert_eq!(is_valid_v(&v), offending.is_none());
+    }
+
     #[rstest]
     #[case(vec![0, 0, 0, 1, 3, 3, 1, 4, 4], false)]
     #[case(vec![0, 0, 0, 3, 2, 9, 4, 1, 12], true)]
@@ -245,4 +668,34 @@ mod tests {
     fn test_is_unordered(#[case] v: Vec<usize>, #[case] expected: bool) {
         assert_eq!(is_unordered(&v), expected);
     }
+
+    #[rstest]
+    #[case(vec![vec![0e0, 0.1, 0.2], vec![2e0, 0.3, 0.4], vec![1e0, 0.5, 0.6]])]
+    #[case(vec![vec![0.0, 0.1, 0.2], vec![1.9999999, 0.3, 0.4], vec![4.0000001, 0.5, 0.6]])]
+    #[should_panic]
+    #[case(vec![vec![0.0, 0.1, 0.2], vec![1.5, 0.3, 0.4]])]
+    #[should_panic]
+    #[case(vec![vec![0.0, 0.1, 0.2], vec![3e0, 0.3, 0.4]])]
+    fn test_check_m(#[case] m: Vec<Vec<f32>>) {
+        check_m(&m);
+    }
+
+    #[rstest]
+    #[case("2e0", 2)]
+    #[case("1E1", 10)]
+    #[case("2.0000001", 2)]
+    #[case("1.9999999", 2)]
+    #[case("0", 0)]
+    fn test_round_to_usize(#[case] value: &str, #[case] expected: usize) {
+        assert_eq!(round_to_usize(value.parse::<f32>().unwrap()), expected);
+    }
+
+    #[rstest]
+    #[should_panic]
+    #[case(2.1)]
+    #[should_panic]
+    #[case(-1e0)]
+    fn test_round_to_usize_invalid(#[case] value: f32) {
+        round_to_usize(value);
+    }
 }
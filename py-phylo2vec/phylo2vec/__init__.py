@@ -1,3 +1,3 @@
-from phylo2vec._phylo2vec_core import __version__
+from phylo2vec._phylo2vec_core import __version__, TreeVec
 
-__all__ = ['__version__']
+__all__ = ['__version__', 'TreeVec']
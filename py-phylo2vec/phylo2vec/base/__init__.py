@@ -2,7 +2,26 @@
 Methods to convert Phylo2Vec vectors to Newick format and vice-versa.
 """
 
-from .to_newick import to_newick
-from .to_vector import to_vector
+from .to_edges import to_edges
+from .to_matrix import to_matrix_batch
+from .to_newick import (
+    get_pairs,
+    get_pairs_avl,
+    to_newick,
+    to_newick_batch,
+    to_nexus,
+)
+from .to_vector import from_edges, to_vector, to_vector_batch
 
-__all__ = ["to_newick", "to_vector"]
+__all__ = [
+    "from_edges",
+    "get_pairs",
+    "get_pairs_avl",
+    "to_edges",
+    "to_matrix_batch",
+    "to_newick",
+    "to_newick_batch",
+    "to_nexus",
+    "to_vector",
+    "to_vector_batch",
+]
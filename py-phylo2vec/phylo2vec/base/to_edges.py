@@ -0,0 +1,28 @@
+"""
+Methods to convert Phylo2Vec vectors to edge lists.
+"""
+
+import numpy as np
+
+from phylo2vec import _phylo2vec_core
+
+
+def to_edges(v):
+    """Get the (parent, child) edges of the tree described by a Phylo2Vec v
+
+    Leaves are labelled from 0 to n_leaves - 1, and internal nodes from
+    n_leaves to 2 * n_leaves - 2, where 2 * n_leaves - 2 is the root.
+    The list can be fed to graph libraries (e.g., networkx.DiGraph), and
+    converted back to v with `from_edges`.
+
+    Parameters
+    ----------
+    v : numpy.ndarray
+        Phylo2Vec vector
+
+    Returns
+    -------
+    edges : list of tuple of int
+        Edges of the tree, as (parent, child) pairs
+    """
+    return _phylo2vec_core.to_edges(np.asarray(v).tolist())
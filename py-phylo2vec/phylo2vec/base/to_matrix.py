@@ -0,0 +1,37 @@
+"""
+Methods to convert Newick strings to Phylo2Vec matrices.
+"""
+
+import numpy as np
+
+from phylo2vec import _phylo2vec_core
+
+
+def to_matrix_batch(newicks):
+    """Convert a batch of Newick strings with parent labels to matrices
+
+    Parameters
+    ----------
+    newicks : list of str
+        Newick strings with branch lengths
+
+    Returns
+    -------
+    numpy.ndarray or list of numpy.ndarray
+        Stacked matrices of shape (batch, n_leaves - 1, 3) if all trees have
+        the same number of leaves, otherwise a list of 2D matrices
+
+    Raises
+    ------
+    ValueError
+        If a Newick string cannot be parsed. The message contains its index.
+    """
+    matrices = [
+        np.asarray(m, dtype=np.float32).reshape(-1, 3)
+        for m in _phylo2vec_core.to_matrix_batch(list(newicks))
+    ]
+
+    if matrices and all(m.shape == matrices[0].shape for m in matrices):
+        return np.stack(matrices)
+
+    return matrices
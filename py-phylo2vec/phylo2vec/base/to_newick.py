@@ -42,6 +42,45 @@ def _get_ancestry(v: np.ndarray) -> np.ndarray:
     return np.asarray(ancestry_list)
 
 
+def get_pairs(v):
+    """Get the pairs of nodes created by each element of a Phylo2Vec v
+
+    Naive implementation with a list, in O(n^2). Used by `to_newick` for
+    ordered vectors (i.e., with v[i] <= i + 1 for all i).
+
+    Parameters
+    ----------
+    v : numpy.array
+        Phylo2Vec vector
+
+    Returns
+    -------
+    pairs : list of tuple of int
+        Pairs of nodes (parent branch, new leaf)
+    """
+    return _phylo2vec_core.get_pairs(np.asarray(v).tolist())
+
+
+def get_pairs_avl(v):
+    """Get the pairs of nodes created by each element of a Phylo2Vec v
+
+    Implementation with an AVL tree, in O(n log n). Used by `to_newick` for
+    unordered vectors (i.e., with v[i] > i + 1 for some i).
+    Gives the same pairs as `get_pairs`.
+
+    Parameters
+    ----------
+    v : numpy.array
+        Phylo2Vec vector
+
+    Returns
+    -------
+    pairs : list of tuple of int
+        Pairs of nodes (parent branch, new leaf)
+    """
+    return _phylo2vec_core.get_pairs_avl(np.asarray(v).tolist())
+
+
 def _build_newick(ancestry: np.ndarray) -> str:
     """Build a Newick string from an "ancestry" array
 
@@ -66,17 +105,69 @@ def _build_newick(ancestry: np.ndarray) -> str:
     return _phylo2vec_core.build_newick(ancestry)
 
 
-def to_newick(v):
-    """Recover a rooted tree (in Newick format) from a Phylo2Vec v
+def to_newick(v, unrooted=False):
+    """Recover a tree (in Newick format) from a Phylo2Vec v
 
     Parameters
     ----------
     v : numpy.array
         Phylo2Vec vector
+    unrooted : bool, optional
+        Whether to write the tree as unrooted, by default False.
+        If True, the root is collapsed into a node with three children
+        and internal nodes are not labelled.
 
     Returns
     -------
     newick : str
         Newick tree
+
+    Notes
+    -----
+    The pairs of nodes are computed with `get_pairs_avl` if v is unordered
+    (i.e., v[i] > i + 1 for some i), and with `get_pairs` otherwise.
     """
+    if unrooted:
+        return _phylo2vec_core.to_newick_unrooted(v)
     return _phylo2vec_core.to_newick_from_vector(v)
+
+
+def to_newick_batch(vs):
+    """Recover rooted trees (in Newick format) from a batch of Phylo2Vec vectors
+
+    All trees are converted in a single call to the Rust core.
+
+    Parameters
+    ----------
+    vs : list of numpy.ndarray or list of list of int
+        Phylo2Vec vectors, possibly with different numbers of leaves
+
+    Returns
+    -------
+    newicks : list of str
+        Newick trees
+    """
+    return _phylo2vec_core.to_newick_batch([np.asarray(v).tolist() for v in vs])
+
+
+def to_nexus(v, taxa=None):
+    """Convert a Phylo2Vec vector to a Nexus document
+
+    The document has a TAXA block, and a TREES block with a translate
+    table and the tree, where leaf i is written as its index i + 1.
+
+    Parameters
+    ----------
+    v : numpy.ndarray
+        Phylo2Vec vector
+    taxa : list of str, optional
+        Name of each leaf. By default, leaf i is named "t{i}"
+
+    Returns
+    -------
+    nexus : str
+        Nexus document
+    """
+    return _phylo2vec_core.to_nexus(
+        np.asarray(v).tolist(), None if taxa is None else list(taxa)
+    )
@@ -151,6 +151,67 @@ def to_vector(newick: str) -> np.ndarray:
     -------
     v : numpy.ndarray
         Phylo2Vec vector
+
+    Raises
+    ------
+    ValueError
+        If the Newick string cannot be parsed
     """
     v_list = _phylo2vec_core.to_vector(newick)
     return np.asarray(v_list, dtype=np.uint64)
+
+
+def to_vector_batch(newicks):
+    """Convert a batch of Newick strings with or without
+    parent labels to vectors
+
+    All trees are converted in a single call to the Rust core.
+
+    Parameters
+    ----------
+    newicks : list of str
+        Newick strings, possibly with different numbers of leaves
+
+    Returns
+    -------
+    vs : list of numpy.ndarray
+        Phylo2Vec vectors
+
+    Raises
+    ------
+    ValueError
+        If a Newick string cannot be parsed. The message contains its index.
+    """
+    return [
+        np.asarray(v_list, dtype=np.uint64)
+        for v_list in _phylo2vec_core.to_vector_batch(list(newicks))
+    ]
+
+
+def from_edges(edges, n_leaves):
+    """Convert a list of (parent, child) edges to a vector
+
+    Leaves must be labelled from 0 to n_leaves - 1. Internal nodes can have
+    any other label, and the edges can be given in any order.
+
+    Parameters
+    ----------
+    edges : list of tuple of int
+        Edges of a rooted binary tree, as (parent, child) pairs
+    n_leaves : int
+        Number of leaves
+
+    Returns
+    -------
+    v : numpy.ndarray
+        Phylo2Vec vector
+
+    Raises
+    ------
+    ValueError
+        If the edges do not form a rooted binary tree with leaves
+        0 to n_leaves - 1
+    """
+    edges = [(int(parent), int(child)) for parent, child in edges]
+    v_list = _phylo2vec_core.from_edges(edges, n_leaves)
+    return np.asarray(v_list, dtype=np.uint64)
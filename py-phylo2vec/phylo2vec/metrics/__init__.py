@@ -2,6 +2,22 @@
 Methods to convert Phylo2Mat matrices to Newick format and vice-versa.
 """
 
-from .pairwise import cophenetic_distances, pairwise_distances
+from .balance import colless_index, count_cherries, sackin_index
+from .pairwise import (
+    cophenetic_distances,
+    cophenetic_distances_batch,
+    cophenetic_distances_with_bls,
+    pairwise_distances,
+    path_length,
+)
 
-__all__ = ["cophenetic_distances", "pairwise_distances"]
+__all__ = [
+    "colless_index",
+    "count_cherries",
+    "cophenetic_distances",
+    "cophenetic_distances_batch",
+    "cophenetic_distances_with_bls",
+    "pairwise_distances",
+    "path_length",
+    "sackin_index",
+]
@@ -0,0 +1,56 @@
+"""
+Tree balance statistics computed from Phylo2Vec vectors.
+"""
+
+from phylo2vec import _phylo2vec_core
+
+
+def sackin_index(v):
+    """Compute the Sackin index of a tree, i.e. the sum of its leaf depths
+
+    Parameters
+    ----------
+    v : numpy.ndarray
+        Phylo2Vec vector
+
+    Returns
+    -------
+    int
+        Sum of the number of edges between each leaf and the root
+    """
+    return _phylo2vec_core.sackin_index(v)
+
+
+def colless_index(v):
+    """Compute the Colless index of a tree
+
+    The Colless index is the sum, over all internal nodes, of the absolute
+    difference between the number of leaves below their two children.
+
+    Parameters
+    ----------
+    v : numpy.ndarray
+        Phylo2Vec vector
+
+    Returns
+    -------
+    int
+        0 for perfectly balanced trees, (n-1)(n-2)/2 for caterpillars
+    """
+    return _phylo2vec_core.colless_index(v)
+
+
+def count_cherries(v):
+    """Count the cherries of a tree, i.e. the pairs of leaves sharing a parent
+
+    Parameters
+    ----------
+    v : numpy.ndarray
+        Phylo2Vec vector
+
+    Returns
+    -------
+    int
+        Number of internal nodes whose two children are leaves
+    """
+    return _phylo2vec_core.count_cherries(v)
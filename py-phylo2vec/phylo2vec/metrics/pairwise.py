@@ -1,3 +1,5 @@
+import numpy as np
+
 from phylo2vec import _phylo2vec_core
 from phylo2vec.utils.validation import check_v
 
@@ -6,6 +8,81 @@ def cophenetic_distances(v, unrooted=False):
     return _phylo2vec_core.cophenetic_distances(v, unrooted)
 
 
+def cophenetic_distances_batch(vs, unrooted=False):
+    """Compute the cophenetic distances of a batch of trees
+
+    Parameters
+    ----------
+    vs : list of numpy.ndarray or numpy.ndarray
+        Phylo2Vec vectors, all with the same number of leaves
+    unrooted : bool, optional
+        Whether to treat the trees as unrooted, by default False
+
+    Returns
+    -------
+    numpy.ndarray
+        Stacked distance matrices of shape (batch, n_leaves, n_leaves)
+
+    Raises
+    ------
+    ValueError
+        If the trees do not all have the same number of leaves
+    """
+    vs = [np.asarray(v).tolist() for v in vs]
+    return np.asarray(_phylo2vec_core.cophenetic_distances_batch(vs, unrooted))
+
+
+def cophenetic_distances_with_bls(v, bls):
+    """Compute the cophenetic distances of a tree with branch lengths
+
+    Parameters
+    ----------
+    v : numpy.ndarray
+        Phylo2Vec vector
+    bls : numpy.ndarray
+        Branch lengths of shape (n_leaves - 1, 2), as in the last two
+        columns of a Phylo2Vec matrix
+
+    Returns
+    -------
+    list of list of float
+        Summed branch lengths between each pair of leaves
+    """
+    return _phylo2vec_core.cophenetic_distances_with_bls(
+        np.asarray(v).tolist(), np.asarray(bls).tolist()
+    )
+
+
+def path_length(v, leaf1, leaf2, unrooted=False):
+    """Compute the number of edges between two leaves
+
+    This is a single entry of `cophenetic_distances`,
+    without computing the full distance matrix.
+
+    Parameters
+    ----------
+    v : numpy.ndarray
+        Phylo2Vec vector
+    leaf1 : int
+        A leaf in the tree
+    leaf2 : int
+        A leaf in the tree
+    unrooted : bool, optional
+        Whether to treat the tree as unrooted, by default False
+
+    Returns
+    -------
+    int
+        Number of edges on the path between leaf1 and leaf2
+
+    Raises
+    ------
+    ValueError
+        If leaf1 or leaf2 is not a leaf of the tree
+    """
+    return _phylo2vec_core.path_length(v, leaf1, leaf2, unrooted)
+
+
 PAIRWISE_DISTANCES = {"cophenetic": cophenetic_distances}
 
 
@@ -6,14 +6,17 @@ from .newick import (
     apply_label_mapping,
     create_label_mapping,
     find_num_leaves,
+    get_cherries_with_bls,
     remove_annotations,
     remove_parent_labels,
 )
 from .random import sample_vector, seed_everything
-from .validation import check_v
+from .validation import check_v, is_valid_v
 from .vector import (
     add_leaf,
     get_common_ancestor,
+    mrca,
+    prune_to,
     remove_leaf,
     reorder_v,
     reroot_at_random,
@@ -34,7 +37,11 @@ __all__ = [
     "check_v",
     "create_label_mapping",
     "find_num_leaves",
+    "get_cherries_with_bls",
     "get_common_ancestor",
+    "is_valid_v",
+    "mrca",
+    "prune_to",
     "remove_annotations",
     "remove_leaf",
     "remove_parent_labels",
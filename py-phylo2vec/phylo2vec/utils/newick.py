@@ -2,6 +2,10 @@
 
 import re
 
+import numpy as np
+
+from phylo2vec import _phylo2vec_core
+
 
 # Pattern of an integer label on the left of a pair
 LEFT_NODE_PATTERN = re.compile(r"\(\b\d+\b")
@@ -29,6 +33,30 @@ def find_num_leaves(newick):
     )
 
 
+def get_cherries_with_bls(newick):
+    """Get the cherries and branch lengths of a Newick string with parent labels
+
+    Parameters
+    ----------
+    newick : str
+        Newick representation of a tree, with integer leaf and parent labels
+
+    Returns
+    -------
+    ancestry : numpy.ndarray
+        Cherries of the tree, as rows of [child1, child2, parent]
+    bls : numpy.ndarray
+        Branch lengths of [child1, child2] for each cherry
+
+    Raises
+    ------
+    ValueError
+        If the Newick string cannot be parsed
+    """
+    ancestry, bls = _phylo2vec_core.get_cherries_with_bls(newick)
+    return np.asarray(ancestry, dtype=np.uint64), np.asarray(bls, dtype=np.float32)
+
+
 def create_label_mapping(newick):
     """
     Create an integer-taxon label mapping (label_mapping)
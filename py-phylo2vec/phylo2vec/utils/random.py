@@ -3,11 +3,15 @@
 import os
 import random
 
+from typing import Optional
+
 import numpy as np
 
 from phylo2vec import _phylo2vec_core
 
-def sample_vector(n_leaves: int, ordered: bool = False) -> np.ndarray:
+def sample_vector(
+    n_leaves: int, ordered: bool = False, seed: Optional[int] = None
+) -> np.ndarray:
     """Sample a random tree via Phylo2Vec, in vector form.
 
     Parameters
@@ -22,6 +26,9 @@ def sample_vector(n_leaves: int, ordered: bool = False) -> np.ndarray:
 
         False:
         v_i in {0, 1, ..., 2*i} for i in (0, n_leaves-1)
+    seed : int, optional
+        Seed of the random number generator, by default None.
+        The same seed always yields the same tree, on any machine.
 
     Returns
     -------
@@ -29,7 +36,10 @@ def sample_vector(n_leaves: int, ordered: bool = False) -> np.ndarray:
         Phylo2Vec vector
     """
 
-    v_list = _phylo2vec_core.sample_vector(n_leaves, ordered)
+    if seed is None:
+        v_list = _phylo2vec_core.sample_vector(n_leaves, ordered)
+    else:
+        v_list = _phylo2vec_core.sample_vector_seeded(n_leaves, ordered, seed)
     return np.asarray(v_list)
 
 def sample_matrix(n_leaves: int, ordered: bool = False) -> np.ndarray:
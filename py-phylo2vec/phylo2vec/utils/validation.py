@@ -16,3 +16,21 @@ def check_v(v: np.ndarray) -> None:
         Phylo2Vec vector
     """
     _phylo2vec_core.check_v(v.tolist())
+
+
+def is_valid_v(v: np.ndarray) -> bool:
+    """Check whether a vector satisfies the Phylo2Vec constraints
+
+    Unlike `check_v`, this does not raise on invalid input.
+
+    Parameters
+    ----------
+    v : numpy.ndarray
+        Phylo2Vec vector
+
+    Returns
+    -------
+    bool
+        True if v[i] <= 2 * i for every index i
+    """
+    return _phylo2vec_core.is_valid_v(np.asarray(v).tolist())
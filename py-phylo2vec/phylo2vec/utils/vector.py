@@ -282,6 +282,27 @@ def add_leaf(v, leaf, pos):
 
 
 
+def prune_to(v, keep):
+    """Get the Phylo2Vec vector of the tree induced by a set of leaves
+
+    Internal nodes left with a single child are suppressed.
+
+    Parameters
+    ----------
+    v : numpy.ndarray
+        Phylo2Vec vector
+    keep : list of int
+        Leaves to keep (at least one, without duplicates)
+
+    Returns
+    -------
+    v_sub : numpy.ndarray
+        Phylo2Vec vector of the induced tree. Leaf i of the induced tree
+        is the i-th smallest leaf of `keep`.
+    """
+    return _phylo2vec_core.prune_to(v, keep)
+
+
 def get_ancestry_paths(v):
     """
     Get the ancestry paths for each node in the Phylo2Vec vector.
@@ -336,3 +357,32 @@ def get_common_ancestor(v, node1, node2):
     common_path = np.intersect1d(path1, path2)
     mrca = common_path[0]
     return mrca
+
+
+def mrca(v, leaf1, leaf2):
+    """Get the most recent common ancestor (MRCA) of two leaves
+
+    Unlike `get_common_ancestor`, this only accepts leaves
+    and does not build the ancestry path of every node.
+
+    Parameters
+    ----------
+    v : numpy.ndarray
+        Phylo2Vec vector
+    leaf1 : int
+        A leaf in the tree
+    leaf2 : int
+        A leaf in the tree
+
+    Returns
+    -------
+    mrca : int
+        Most recent common ancestor of leaf1 and leaf2
+        (leaf1 itself if leaf1 == leaf2)
+
+    Raises
+    ------
+    ValueError
+        If leaf1 or leaf2 is not a leaf of the tree
+    """
+    return _phylo2vec_core.mrca(v, leaf1, leaf2)
@@ -1,14 +1,37 @@
 use pyo3::prelude::*;
 
 use phylo2vec::tree_vec::ops;
+use phylo2vec::tree_vec::types::Ancestry;
+use phylo2vec::tree_vec::TreeVec;
 use phylo2vec::utils;
 
+/// Raise Rust errors as Python `ValueError`s with the error message
+fn _to_value_error<E: std::error::Error>(error: E) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(error.to_string())
+}
+
 #[pyfunction]
 fn to_newick_from_vector(input_vector: Vec<usize>) -> PyResult<String> {
     let newick = ops::to_newick_from_vector(&input_vector);
     Ok(newick)
 }
 
+#[pyfunction]
+fn to_newick_unrooted(input_vector: Vec<usize>) -> String {
+    ops::to_newick_unrooted(&input_vector)
+}
+
+#[pyfunction]
+fn to_newick_batch(input_vectors: Vec<Vec<usize>>) -> Vec<String> {
+    ops::to_newick_batch(&input_vectors)
+}
+
+#[pyfunction]
+#[pyo3(signature = (input_vector, taxa=None))]
+fn to_nexus(input_vector: Vec<usize>, taxa: Option<Vec<String>>) -> String {
+    ops::nexus::to_nexus(&input_vector, taxa.as_deref())
+}
+
 #[pyfunction]
 fn to_newick_from_matrix(input_matrix: Vec<Vec<f32>>) -> PyResult<String> {
     let newick = ops::to_newick_from_matrix(&input_matrix);
@@ -16,15 +39,48 @@ fn to_newick_from_matrix(input_matrix: Vec<Vec<f32>>) -> PyResult<String> {
 }
 
 #[pyfunction]
-fn to_vector(newick: &str) -> Vec<usize> {
-    let v = ops::to_vector(&newick);
-    v
+fn to_vector(newick: &str) -> PyResult<Vec<usize>> {
+    ops::try_to_vector(newick).map_err(_to_value_error)
 }
 
 #[pyfunction]
-fn to_matrix(newick: &str) -> Vec<Vec<f32>> {
-    let m = ops::matrix::to_matrix(&newick);
-    m
+fn to_vector_batch(newicks: Vec<String>) -> PyResult<Vec<Vec<usize>>> {
+    ops::to_vector_batch(&newicks).map_err(_to_value_error)
+}
+
+#[pyfunction]
+fn to_edges(input_vector: Vec<usize>) -> Vec<(usize, usize)> {
+    ops::to_edges(&input_vector)
+}
+
+#[pyfunction]
+fn from_edges(edges: Vec<(usize, usize)>, n_leaves: usize) -> PyResult<Vec<usize>> {
+    ops::from_edges(&edges, n_leaves).map_err(_to_value_error)
+}
+
+#[pyfunction]
+fn to_matrix(newick: &str) -> PyResult<Vec<Vec<f32>>> {
+    ops::matrix::to_matrix(newick).map_err(_to_value_error)
+}
+
+#[pyfunction]
+fn to_matrix_batch(newicks: Vec<String>) -> PyResult<Vec<Vec<Vec<f32>>>> {
+    ops::matrix::to_matrix_batch(&newicks).map_err(_to_value_error)
+}
+
+#[pyfunction]
+fn get_cherries_with_bls(newick: &str) -> PyResult<(Ancestry, Vec<[f32; 2]>)> {
+    ops::newick::get_cherries_with_bls(newick).map_err(_to_value_error)
+}
+
+#[pyfunction]
+fn get_pairs(input_vector: Vec<usize>) -> Vec<(usize, usize)> {
+    ops::get_pairs(&input_vector)
+}
+
+#[pyfunction]
+fn get_pairs_avl(input_vector: Vec<usize>) -> Vec<(usize, usize)> {
+    ops::get_pairs_avl(&input_vector)
 }
 
 #[pyfunction]
@@ -46,12 +102,72 @@ fn sample_vector(n_leaves: usize, ordered: bool) -> Vec<usize> {
     v
 }
 
+#[pyfunction]
+fn sample_vector_seeded(n_leaves: usize, ordered: bool, seed: u64) -> Vec<usize> {
+    let ordering = if ordered {
+        utils::SampleOrdering::Ordered
+    } else {
+        utils::SampleOrdering::Unordered
+    };
+    utils::sample_vector_seeded(n_leaves, ordering, seed)
+}
+
 #[pyfunction]
 fn cophenetic_distances(input_vector: Vec<usize>, unrooted: bool) -> Vec<Vec<usize>> {
     let distances = ops::vector::cophenetic_distances(&input_vector, unrooted);
     distances
 }
 
+#[pyfunction]
+fn cophenetic_distances_batch(
+    input_vectors: Vec<Vec<usize>>,
+    unrooted: bool,
+) -> PyResult<Vec<Vec<Vec<usize>>>> {
+    ops::vector::cophenetic_distances_batch(&input_vectors, unrooted).map_err(_to_value_error)
+}
+
+#[pyfunction]
+fn cophenetic_distances_with_bls(
+    input_vector: Vec<usize>,
+    branch_lengths: Vec<[f64; 2]>,
+) -> Vec<Vec<f64>> {
+    let branch_lengths: Vec<(f64, f64)> = branch_lengths
+        .iter()
+        .map(|&[bl1, bl2]| (bl1, bl2))
+        .collect();
+    ops::vector::cophenetic_distances_with_bls(&input_vector, &branch_lengths)
+}
+
+#[pyfunction]
+fn mrca(input_vector: Vec<usize>, leaf_a: usize, leaf_b: usize) -> PyResult<usize> {
+    ops::mrca(&input_vector, leaf_a, leaf_b).map_err(_to_value_error)
+}
+
+#[pyfunction]
+fn path_length(
+    input_vector: Vec<usize>,
+    leaf_a: usize,
+    leaf_b: usize,
+    unrooted: bool,
+) -> PyResult<usize> {
+    ops::path_length(&input_vector, leaf_a, leaf_b, unrooted).map_err(_to_value_error)
+}
+
+#[pyfunction]
+fn sackin_index(input_vector: Vec<usize>) -> usize {
+    ops::stats::sackin_index(&input_vector)
+}
+
+#[pyfunction]
+fn colless_index(input_vector: Vec<usize>) -> usize {
+    ops::stats::colless_index(&input_vector)
+}
+
+#[pyfunction]
+fn count_cherries(input_vector: Vec<usize>) -> usize {
+    ops::count_cherries(&input_vector)
+}
+
 #[pyfunction]
 fn sample_matrix(n_leaves: usize, ordered: bool) -> Vec<Vec<f32>> {
     let m = utils::sample_matrix(n_leaves, ordered);
@@ -63,6 +179,11 @@ fn check_v(input_vector: Vec<usize>) {
     utils::check_v(&input_vector);
 }
 
+#[pyfunction]
+fn is_valid_v(input_vector: Vec<usize>) -> bool {
+    utils::is_valid_v(&input_vector)
+}
+
 #[pyfunction]
 fn add_leaf(mut input_vector: Vec<usize>, leaf: usize, branch: usize) -> Vec<usize> {
     return ops::add_leaf(&mut input_vector, leaf, branch);
@@ -73,23 +194,96 @@ fn remove_leaf(mut input_vector: Vec<usize>, leaf: usize) -> (Vec<usize>, usize)
     return ops::remove_leaf(&mut input_vector, leaf);
 }
 
+#[pyfunction]
+fn prune_to(input_vector: Vec<usize>, keep: Vec<usize>) -> Vec<usize> {
+    ops::subtree::restrict_to_leaves(&input_vector, &keep)
+}
+
+/// A phylogenetic tree stored as a Phylo2Vec vector
+///
+/// Methods that edit the tree (`add_leaf`, `remove_leaf`) modify it in place.
+#[pyclass(name = "TreeVec")]
+struct PyTreeVec {
+    inner: TreeVec,
+}
+
+#[pymethods]
+impl PyTreeVec {
+    #[new]
+    fn new(input_vector: Vec<usize>) -> PyResult<Self> {
+        let inner = TreeVec::new(input_vector, None, None);
+        inner.validate().map_err(_to_value_error)?;
+        Ok(PyTreeVec { inner })
+    }
+
+    #[getter]
+    fn data(&self) -> Vec<usize> {
+        self.inner.data().to_vec()
+    }
+
+    fn to_newick(&self) -> String {
+        self.inner.to_newick()
+    }
+
+    fn get_ancestry(&self) -> Vec<[usize; 3]> {
+        self.inner.get_ancestry()
+    }
+
+    fn add_leaf(&mut self, leaf: usize, branch: usize) {
+        self.inner.add_leaf(leaf, branch);
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) -> usize {
+        self.inner.remove_leaf(leaf)
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("TreeVec({:?})", self.inner.data())
+    }
+}
+
 /// This module is exposed to Python.
 /// The line below raises an issue in DeepSource stating that this function's cyclomatic complexity is higher than threshold
 /// the analyzer does not understand that this is an API exposure function, hence the comment above to skip over this occurrence.
 #[pymodule]
 fn _phylo2vec_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(to_newick_from_vector, m)?)?;
+    m.add_function(wrap_pyfunction!(to_newick_unrooted, m)?)?;
+    m.add_function(wrap_pyfunction!(to_newick_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(to_nexus, m)?)?;
     m.add_function(wrap_pyfunction!(to_vector, m)?)?;
+    m.add_function(wrap_pyfunction!(to_vector_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(to_edges, m)?)?;
+    m.add_function(wrap_pyfunction!(from_edges, m)?)?;
     m.add_function(wrap_pyfunction!(to_newick_from_matrix, m)?)?;
     m.add_function(wrap_pyfunction!(to_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(to_matrix_batch, m)?)?;
     m.add_function(wrap_pyfunction!(build_newick, m)?)?;
     m.add_function(wrap_pyfunction!(get_ancestry, m)?)?;
+    m.add_function(wrap_pyfunction!(get_pairs, m)?)?;
+    m.add_function(wrap_pyfunction!(get_pairs_avl, m)?)?;
+    m.add_function(wrap_pyfunction!(get_cherries_with_bls, m)?)?;
     m.add_function(wrap_pyfunction!(sample_vector, m)?)?;
+    m.add_function(wrap_pyfunction!(sample_vector_seeded, m)?)?;
     m.add_function(wrap_pyfunction!(sample_matrix, m)?)?;
     m.add_function(wrap_pyfunction!(cophenetic_distances, m)?)?;
+    m.add_function(wrap_pyfunction!(cophenetic_distances_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(cophenetic_distances_with_bls, m)?)?;
+    m.add_function(wrap_pyfunction!(mrca, m)?)?;
+    m.add_function(wrap_pyfunction!(path_length, m)?)?;
+    m.add_function(wrap_pyfunction!(sackin_index, m)?)?;
+    m.add_function(wrap_pyfunction!(colless_index, m)?)?;
+    m.add_function(wrap_pyfunction!(count_cherries, m)?)?;
     m.add_function(wrap_pyfunction!(check_v, m)?)?;
+    m.add_function(wrap_pyfunction!(is_valid_v, m)?)?;
     m.add_function(wrap_pyfunction!(add_leaf, m)?)?;
     m.add_function(wrap_pyfunction!(remove_leaf, m)?)?;
+    m.add_function(wrap_pyfunction!(prune_to, m)?)?;
+    m.add_class::<PyTreeVec>()?;
     // Metadata about the package bindings
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
     Ok(())
@@ -6,9 +6,18 @@ import pytest
 from ete3 import Tree
 
 from .config import MIN_N_LEAVES, N_REPEATS
+from phylo2vec import _phylo2vec_core
 from phylo2vec.base import to_newick
-from phylo2vec.metrics import cophenetic_distances
-from phylo2vec.utils import sample_vector
+from phylo2vec.metrics import (
+    colless_index,
+    cophenetic_distances,
+    cophenetic_distances_batch,
+    count_cherries,
+    cophenetic_distances_with_bls,
+    path_length,
+    sackin_index,
+)
+from phylo2vec.utils import sample_matrix, sample_vector
 
 
 @pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
@@ -53,5 +62,116 @@ def test_cophenetic(n_leaves):
         assert np.array_equal(D_p2v_unr, D_ete3_unr)
 
 
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_cophenetic_batch(n_leaves):
+    """Test that batched cophenetic distances match per-tree distances
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    vs = [sample_vector(n_leaves) for _ in range(N_REPEATS)]
+
+    for unrooted in [False, True]:
+        D_batch = cophenetic_distances_batch(vs, unrooted=unrooted)
+
+        assert D_batch.shape == (N_REPEATS, n_leaves, n_leaves)
+
+        for v, D in zip(vs, D_batch):
+            assert np.array_equal(D, cophenetic_distances(v, unrooted=unrooted))
+
+
+def test_cophenetic_batch_mismatched_leaves():
+    """Test that trees with different numbers of leaves cannot be batched"""
+    with pytest.raises(ValueError):
+        cophenetic_distances_batch([sample_vector(5), sample_vector(6)])
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_cophenetic_with_bls(n_leaves):
+    """Test weighted cophenetic distances against ete3
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    for _ in range(N_REPEATS):
+        m = sample_matrix(n_leaves)
+        # The Newick string stores branch lengths with one decimal
+        m[:, 1:] = np.round(m[:, 1:], 1)
+        v, bls = m[:, 0].astype(int), m[:, 1:]
+
+        tr = Tree(_phylo2vec_core.to_newick_from_matrix(m.tolist()), format=1)
+
+        D_p2v = np.asarray(cophenetic_distances_with_bls(v, bls))
+
+        D_ete3 = np.zeros((n_leaves, n_leaves))
+        for i in range(n_leaves):
+            for j in range(i):
+                D_ete3[i, j] = tr.get_distance(f"{i}", f"{j}")
+        D_ete3 += D_ete3.T
+
+        assert np.allclose(D_p2v, D_ete3, atol=1e-4)
+        assert np.allclose(D_p2v, D_p2v.T)
+        assert np.all(np.diag(D_p2v) == 0)
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_path_length(n_leaves):
+    """Test that path lengths match the cophenetic distances
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    for _ in range(N_REPEATS):
+        v = sample_vector(n_leaves)
+
+        for unrooted in [False, True]:
+            D = cophenetic_distances(v, unrooted=unrooted)
+
+            leaf1, leaf2 = np.random.choice(np.arange(n_leaves), 2)
+
+            assert path_length(v, leaf1, leaf2, unrooted=unrooted) == D[leaf1][leaf2]
+
+        with pytest.raises(ValueError):
+            path_length(v, 0, n_leaves)
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_tree_shape(n_leaves):
+    """Test the tree shape statistics against ete3
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    for _ in range(N_REPEATS):
+        v = sample_vector(n_leaves)
+
+        tr = Tree(to_newick(v))
+        internal_nodes = [node for node in tr.traverse() if not node.is_leaf()]
+
+        # Each leaf is counted once per internal node above it
+        assert sackin_index(v) == sum(len(node) for node in internal_nodes)
+        assert colless_index(v) == sum(
+            abs(len(node.children[0]) - len(node.children[1]))
+            for node in internal_nodes
+        )
+        assert count_cherries(v) == sum(
+            all(child.is_leaf() for child in node.children) for node in internal_nodes
+        )
+
+    # Caterpillar tree
+    v = np.arange(n_leaves - 1)
+    assert count_cherries(v) == 1
+    assert sackin_index(v) == n_leaves * (n_leaves + 1) // 2 - 1
+    assert colless_index(v) == (n_leaves - 1) * (n_leaves - 2) // 2
+
+
 if __name__ == "__main__":
     pytest.main()
@@ -0,0 +1,43 @@
+"""Tests for the TreeVec class."""
+
+import numpy as np
+import pytest
+
+from phylo2vec import TreeVec
+from phylo2vec.base import to_newick
+from phylo2vec.utils import add_leaf, remove_leaf, sample_vector
+from .config import MIN_N_LEAVES, N_REPEATS
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_tree_vec(n_leaves):
+    """Test that TreeVec methods match the corresponding functions
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    for _ in range(N_REPEATS):
+        v = sample_vector(n_leaves)
+        tree = TreeVec(v.tolist())
+
+        assert np.array_equal(tree.data, v)
+        assert tree.to_newick() == to_newick(v)
+        assert str(tree) == to_newick(v)
+
+        # Editing methods modify the tree in place
+        v_add = add_leaf(v, n_leaves // 2, 0)
+        tree.add_leaf(n_leaves // 2, 0)
+        assert np.array_equal(tree.data, v_add)
+
+        v_rm, sister = remove_leaf(v_add, n_leaves // 2)
+        assert tree.remove_leaf(n_leaves // 2) == sister
+        assert np.array_equal(tree.data, v_rm)
+        assert np.array_equal(tree.data, v)
+
+
+def test_tree_vec_invalid():
+    """Test that an invalid vector raises a ValueError"""
+    with pytest.raises(ValueError):
+        TreeVec([0, 3])
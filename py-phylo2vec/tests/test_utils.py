@@ -13,7 +13,11 @@ from phylo2vec.utils import (
     check_v,
     create_label_mapping,
     find_num_leaves,
+    get_cherries_with_bls,
     get_common_ancestor,
+    is_valid_v,
+    mrca,
+    prune_to,
     read_vector_csv,
     read_newick_file,
     read_newick_file_labeled,
@@ -98,6 +102,29 @@ def test_sample(n_leaves):
     for _ in range(N_REPEATS):
         v = sample_vector(n_leaves)
         check_v(v)  # Asserts that v is valid
+        assert is_valid_v(v)
+
+
+@pytest.mark.parametrize("v", [[1], [0, 3], [0, 0, 9, 1]])
+def test_is_valid_v_invalid(v):
+    assert not is_valid_v(v)
+
+
+@pytest.mark.parametrize("n_leaves", [MIN_N_LEAVES, MAX_N_LEAVES + 1])
+@pytest.mark.parametrize("ordered", [False, True])
+def test_sample_seeded(n_leaves, ordered):
+    """Test that seeded sampling is reproducible
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    ordered : bool
+        Whether to sample ordered trees
+    """
+    v = sample_vector(n_leaves, ordered=ordered, seed=42)
+    check_v(v)
+    assert np.array_equal(v, sample_vector(n_leaves, ordered=ordered, seed=42))
 
 
 @pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, MAX_N_LEAVES + 1))
@@ -116,6 +143,31 @@ def test_find_num_leaves(n_leaves):
         assert find_num_leaves(newick) == n_leaves
 
 
+def test_get_cherries_with_bls():
+    """Test get_cherries_with_bls on a small annotated tree"""
+    newick = "((0:0.1,2:0.2)3:0.3,1:0.4)4;"
+
+    ancestry, bls = get_cherries_with_bls(newick)
+
+    assert np.array_equal(ancestry, np.array([[0, 2, 3], [3, 1, 4]]))
+    assert np.allclose(bls, np.array([[0.1, 0.2], [0.3, 0.4]]))
+
+
+@pytest.mark.parametrize(
+    "newick", ["((0:0.1,a:0.2)3:0.3,1:0.4)4;", "((0:0.1,2:abc)3:0.3,1:0.4)4;"]
+)
+def test_get_cherries_with_bls_invalid(newick):
+    """Test that get_cherries_with_bls raises a ValueError on invalid input
+
+    Parameters
+    ----------
+    newick : str
+        Invalid Newick string
+    """
+    with pytest.raises(ValueError):
+        get_cherries_with_bls(newick)
+
+
 @pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, MAX_N_LEAVES + 1))
 def test_create_and_apply_label_mapping(n_leaves):
     """Test create_label_mapping and apply_label_mapping
@@ -187,5 +239,48 @@ def test_get_common_ancestor(n_leaves):
         assert p2v_common_ancestor == ete3_common_ancestor
 
 
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, MAX_N_LEAVES + 1))
+def test_mrca(n_leaves):
+    for _ in range(N_REPEATS):
+        v = sample_vector(n_leaves)
+
+        leaf1, leaf2 = np.random.choice(np.arange(n_leaves), 2, replace=False)
+
+        assert mrca(v, leaf1, leaf2) == get_common_ancestor(v, leaf1, leaf2)
+        assert mrca(v, leaf1, leaf1) == leaf1
+
+        with pytest.raises(ValueError):
+            mrca(v, leaf1, n_leaves)
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, MAX_N_LEAVES + 1))
+def test_prune_to(n_leaves):
+    """Test that the induced subtree matches pruning with ete3
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    for _ in range(N_REPEATS):
+        v = sample_vector(n_leaves)
+        n_keep = np.random.randint(2, n_leaves + 1)
+        keep = np.random.choice(np.arange(n_leaves), n_keep, replace=False).tolist()
+
+        v_sub = prune_to(v, keep)
+        assert len(v_sub) == n_keep - 1
+
+        tr = Tree(to_newick(v), format=8)
+        tr.prune([str(leaf) for leaf in keep])
+        for leaf in tr:
+            leaf.name = str(sorted(keep).index(int(leaf.name)))
+
+        tr_sub = Tree(to_newick(v_sub), format=8)
+        assert tr.robinson_foulds(tr_sub, unrooted_trees=False)[0] == 0
+
+    # Keeping all leaves gives the same tree
+    assert np.array_equal(prune_to(v, list(range(n_leaves))), v)
+
+
 if __name__ == "__main__":
     pytest.main()
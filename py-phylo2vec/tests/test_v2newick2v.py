@@ -6,14 +6,26 @@ import pytest
 from ete3 import Tree
 
 from .config import MIN_N_LEAVES, MAX_N_LEAVES, N_REPEATS
-from phylo2vec.base import to_newick, to_vector
+from phylo2vec import _phylo2vec_core
+from phylo2vec.base import (
+    from_edges,
+    get_pairs,
+    get_pairs_avl,
+    to_edges,
+    to_matrix_batch,
+    to_newick,
+    to_newick_batch,
+    to_nexus,
+    to_vector,
+    to_vector_batch,
+)
 from phylo2vec.base.to_vector import (
     _find_cherries,
     _order_cherries_no_parents,
     _reduce,
     _reduce_no_parents,
 )
-from phylo2vec.utils import sample_vector
+from phylo2vec.utils import sample_matrix, sample_vector
 
 
 MIN_N_LEAVES = 5
@@ -110,5 +122,219 @@ def test_ladderize(n_leaves):
         assert np.array_equal(to_vector(nw), to_vector(nw_ladderized))
 
 
+@pytest.mark.parametrize(
+    "newick", ["((0,2),(1,3);", "((0,2)),(1,3));", "((0,2),(1,3))", "((0,2),(1,4));"]
+)
+def test_to_vector_invalid(newick):
+    """Test that malformed Newick strings raise a ValueError
+
+    Parameters
+    ----------
+    newick : str
+        Malformed Newick string
+    """
+    with pytest.raises(ValueError):
+        to_vector(newick)
+
+
+@pytest.mark.parametrize(
+    "newick",
+    [
+        "(0:0.1,1:abc)2;",
+        "(0:0.1,1:1.2e)2;",
+        "((0:0.1,1:0.2)2;",
+        "(0,0)2;",
+        "(0,5)2;",
+        "((0,1)2,3)4;",
+    ],
+)
+def test_to_matrix_invalid(newick):
+    """Test that invalid branch lengths or node labels raise a ValueError
+
+    Parameters
+    ----------
+    newick : str
+        Malformed Newick string
+    """
+    with pytest.raises(ValueError):
+        _phylo2vec_core.to_matrix(newick)
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_to_matrix_batch(n_leaves):
+    """Test that batched matrices match per-tree matrices
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    newicks = [
+        _phylo2vec_core.to_newick_from_matrix(sample_matrix(n_leaves).tolist())
+        for _ in range(N_REPEATS)
+    ]
+
+    m_batch = to_matrix_batch(newicks)
+
+    assert m_batch.shape == (N_REPEATS, n_leaves - 1, 3)
+
+    for newick, m in zip(newicks, m_batch):
+        m_single = np.asarray(_phylo2vec_core.to_matrix(newick), dtype=np.float32)
+        assert np.array_equal(m, m_single)
+
+    # Different numbers of leaves: list of matrices
+    m_list = to_matrix_batch([newicks[0], "(0:0.1,1:0.2)2;"])
+    assert isinstance(m_list, list) and m_list[1].shape == (1, 3)
+
+    with pytest.raises(ValueError, match="index 1"):
+        to_matrix_batch([newicks[0], "((0:0.1,2:abc)3:0.3,1:0.4)4;"])
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_to_newick_unrooted(n_leaves):
+    """Test that unrooted Newick strings have a trifurcation at the top level
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    for _ in range(N_REPEATS):
+        v = sample_vector(n_leaves)
+
+        tr_unrooted = Tree(to_newick(v, unrooted=True))
+        tr_rooted = Tree(to_newick(v), format=8)
+
+        assert len(tr_unrooted.children) == 3
+        assert sorted(tr_unrooted.get_leaf_names()) == sorted(
+            tr_rooted.get_leaf_names()
+        )
+        assert tr_unrooted.robinson_foulds(tr_rooted, unrooted_trees=True)[0] == 0
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_to_edges(n_leaves):
+    """Test that the edges match the ete3 tree built from the Newick string
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    for _ in range(N_REPEATS):
+        v = sample_vector(n_leaves)
+        edges = to_edges(v)
+
+        tr = Tree(to_newick(v), format=8)
+        expected = {
+            (int(node.up.name), int(node.name)) for node in tr.traverse() if node.up
+        }
+
+        assert len(edges) == 2 * n_leaves - 2
+        assert all(isinstance(edge, tuple) for edge in edges)
+        assert set(edges) == expected
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_from_edges(n_leaves):
+    """Test that edges from to_edges (in any order and with any
+    internal labels) lead back to v
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    rng = np.random.default_rng()
+    for _ in range(N_REPEATS):
+        v = sample_vector(n_leaves)
+        edges = to_edges(v)
+
+        assert np.array_equal(from_edges(edges, n_leaves), v)
+
+        internal_nodes = rng.permutation(range(n_leaves, 2 * n_leaves - 1))
+        labels = {node: 1000 + i for i, node in enumerate(internal_nodes)}
+        relabelled = [(labels[p], labels.get(c, c)) for p, c in edges]
+        rng.shuffle(relabelled)
+
+        assert np.array_equal(from_edges(relabelled, n_leaves), v)
+
+    with pytest.raises(ValueError, match="Invalid edge list"):
+        from_edges(edges[1:], n_leaves)
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_get_pairs(n_leaves):
+    """Test that the naive and AVL implementations of get_pairs agree
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    for ordered in [False, True]:
+        v = sample_vector(n_leaves, ordered=ordered)
+
+        pairs = get_pairs(v)
+
+        assert len(pairs) == n_leaves - 1
+        assert all(isinstance(pair, tuple) for pair in pairs)
+        assert pairs == get_pairs_avl(v)
+
+
+def test_to_newick_batch():
+    """Test that batched Newick strings match per-vector Newick strings"""
+    vs = [sample_vector(n_leaves) for n_leaves in range(MIN_N_LEAVES, 51)]
+
+    newicks = to_newick_batch(vs)
+
+    assert newicks == [to_newick(v) for v in vs]
+    assert to_newick_batch([]) == []
+
+
+def test_to_vector_batch():
+    """Test that batched vectors match per-tree vectors"""
+    vs = [sample_vector(n_leaves) for n_leaves in range(MIN_N_LEAVES, 51)]
+
+    vs_batch = to_vector_batch(to_newick_batch(vs))
+
+    assert len(vs_batch) == len(vs)
+    for v, v_batch in zip(vs, vs_batch):
+        assert np.array_equal(v, v_batch)
+
+    with pytest.raises(ValueError, match="index 1"):
+        to_vector_batch([to_newick(vs[0]), "((0,2),(1,3);"])
+
+
+@pytest.mark.parametrize("n_leaves", range(MIN_N_LEAVES, 51))
+def test_to_nexus(n_leaves):
+    """Test that the Nexus tree matches the Newick tree and translate table
+
+    Parameters
+    ----------
+    n_leaves : int
+        Number of leaves
+    """
+    v = sample_vector(n_leaves)
+    taxa = [f"taxon_{i}" for i in range(n_leaves)]
+
+    nexus = to_nexus(v, taxa)
+
+    assert nexus.startswith("#NEXUS")
+    assert f"DIMENSIONS NTAX={n_leaves};" in nexus
+
+    tree_line = next(line for line in nexus.splitlines() if "TREE tree1" in line)
+    tr_nexus = Tree(tree_line.split("[&R] ")[1], format=9)
+    translate = {str(i + 1): taxon for i, taxon in enumerate(taxa)}
+    for leaf in tr_nexus:
+        leaf.name = translate[leaf.name]
+
+    tr_newick = Tree(to_newick(v), format=8)
+    for leaf in tr_newick:
+        leaf.name = taxa[int(leaf.name)]
+
+    assert tr_nexus.robinson_foulds(tr_newick, unrooted_trees=False)[0] == 0
+
+
 if __name__ == "__main__":
     pytest.main()
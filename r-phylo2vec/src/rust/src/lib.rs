@@ -12,13 +12,24 @@ fn sample(n_leaves: usize, ordered: bool) -> Vec<i32> {
     v.iter().map(|&x| x as i32).collect()
 }
 
+/// Sample a random tree via Phylo2Vec, reproducibly from a seed
+/// @export
+#[extendr]
+fn sample_seeded(n_leaves: usize, ordered: bool, seed: i32) -> Vec<i32> {
+    let ordering = if ordered {
+        utils::SampleOrdering::Ordered
+    } else {
+        utils::SampleOrdering::Unordered
+    };
+    let v = utils::sample_vector_seeded(n_leaves, ordering, seed as u64);
+    v.iter().map(|&x| x as i32).collect()
+}
+
 /// Recover a rooted tree (in Newick format) from a Phylo2Vec vector
 /// @export
 #[extendr]
 fn to_newick_from_vector(input_integers: Vec<i32>) -> String {
-    let input_vector = input_integers.iter().map(|&x| x as usize).collect();
-    let newick = ops::to_newick_from_vector(&input_vector);
-    newick
+    ops::to_newick_from_vector(&input_integers)
 }
 
 /// Recover a rooted tree (in Newick format) from a Phylo2Vec matrix
@@ -30,6 +41,17 @@ fn to_newick_from_matrix(input_integers: Robj) -> String {
     newick
 }
 
+/// Convert a newick string (with parent labels and branch lengths) to a Phylo2Vec matrix
+/// @export
+#[extendr]
+fn to_matrix(newick: &str) -> Result<RMatrix<f64>, String> {
+    let matrix = ops::matrix::to_matrix(newick).map_err(|error| error.to_string())?;
+    // Each row is [v[i], branch length 1, branch length 2]
+    Ok(RMatrix::new_matrix(matrix.len(), 3, |row, col| {
+        matrix[row][col] as f64
+    }))
+}
+
 // Convert R matrix to Rust Vec<Vec<f32>>
 fn convert_from_rmatrix(matrix: &Robj) -> Result<Vec<Vec<f32>>, &'static str> {
     let data = matrix.as_real_slice().ok_or("Expected numeric matrix")?;
@@ -63,14 +85,66 @@ fn check_v(input_integers: Vec<i32>) {
     utils::check_v(&input_vector);
 }
 
+/// Get the number of edges on the path between two leaves of a Phylo2Vec vector
+/// @export
+#[extendr]
+fn path_length(
+    input_integers: Vec<i32>,
+    leaf_a: i32,
+    leaf_b: i32,
+    unrooted: bool,
+) -> Result<i32, String> {
+    let input_vector: Vec<usize> = input_integers.iter().map(|&x| x as usize).collect();
+    ops::path_length(&input_vector, leaf_a as usize, leaf_b as usize, unrooted)
+        .map(|n_edges| n_edges as i32)
+        .map_err(|error| error.to_string())
+}
+
+/// Get the cophenetic distances (number of edges) between all pairs of leaves of a Phylo2Vec vector
+/// @export
+#[extendr]
+fn cophenetic_distances(input_integers: Vec<i32>, unrooted: bool) -> RMatrix<i32> {
+    let input_vector: Vec<usize> = input_integers.iter().map(|&x| x as usize).collect();
+    let distances = ops::cophenetic_distances(&input_vector, unrooted);
+    let n_leaves = distances.len();
+    RMatrix::new_matrix(n_leaves, n_leaves, |r, c| distances[r][c] as i32)
+}
+
+/// Add a leaf to a Phylo2Vec vector, branching out from a given branch
+/// @export
+#[extendr]
+fn add_leaf(input_integers: Vec<i32>, leaf: i32, branch: i32) -> Vec<i32> {
+    let mut input_vector: Vec<usize> = input_integers.iter().map(|&x| x as usize).collect();
+    let v = ops::add_leaf(&mut input_vector, leaf as usize, branch as usize);
+    v.iter().map(|&x| x as i32).collect()
+}
+
+/// Remove a leaf from a Phylo2Vec vector
+///
+/// Returns a list with the new vector (`v`) and the sister node of the removed leaf (`sister`)
+/// @export
+#[extendr]
+fn remove_leaf(input_integers: Vec<i32>, leaf: i32) -> List {
+    let mut input_vector: Vec<usize> = input_integers.iter().map(|&x| x as usize).collect();
+    let (v, sister) = ops::remove_leaf(&mut input_vector, leaf as usize);
+    let v: Vec<i32> = v.iter().map(|&x| x as i32).collect();
+    list!(v = v, sister = sister as i32)
+}
+
 // Macro to generate exports.
 // This ensures exported functions are registered with R.
 // See corresponding C code in `entrypoint.c`.
 extendr_module! {
     mod phylo2vec;
     fn sample;
+    fn sample_seeded;
     fn to_newick_from_vector;
     fn to_newick_from_matrix;
+    fn to_matrix;
     fn to_vector;
     fn check_v;
+    fn path_length;
+    fn cophenetic_distances;
+    fn add_leaf;
+    fn remove_leaf;
 }